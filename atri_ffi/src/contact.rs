@@ -1,7 +1,35 @@
-use crate::ManagedCloneable;
+use crate::{ManagedCloneable, RustString};
 
 #[repr(C)]
 pub struct FFIMember {
     pub is_named: bool,
     pub inner: ManagedCloneable,
 }
+
+#[repr(C)]
+pub struct FFIAnnouncement {
+    pub fid: RustString,
+    pub sender: i64,
+    pub time: i64,
+    pub content: RustString,
+}
+
+#[repr(C)]
+pub struct FFIGroupSettings {
+    pub mute_all: bool,
+    pub allow_anonymous: bool,
+    pub allow_member_invite: bool,
+}
+
+/// 群文件或文件夹, `is_folder`为`true`时`size`, `uploader`无意义
+#[repr(C)]
+pub struct FFIGroupFile {
+    pub is_folder: bool,
+    pub id: RustString,
+    pub name: RustString,
+    pub folder: RustString,
+    pub size: u64,
+    pub uploader: i64,
+    pub time: i64,
+    pub file_count: u32,
+}
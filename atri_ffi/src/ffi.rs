@@ -1,3 +1,4 @@
+use crate::plugin::AbiInfo;
 use crate::ManagedCloneable;
 
 #[repr(C)]
@@ -5,6 +6,8 @@ pub struct AtriManager {
     pub manager_ptr: *const (),
     pub handle: usize,
     pub get_fun: extern "C" fn(sig: u16) -> *const (),
+    /// 宿主的ABI信息, 插件以此检查兼容性
+    pub abi: AbiInfo,
 }
 
 #[repr(C)]
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr::null_mut;
 use std::{mem, slice};
@@ -36,6 +37,10 @@ impl RustString {
     }
 }
 
+// 独占其指向的字符串, 与`String`相同
+unsafe impl Send for RustString {}
+unsafe impl Sync for RustString {}
+
 impl From<String> for RustString {
     fn from(s: String) -> Self {
         let mut ma = ManuallyDrop::new(s);
@@ -53,8 +58,7 @@ impl From<String> for RustString {
 
 impl From<RustString> for String {
     fn from(s: RustString) -> Self {
-        let str = unsafe { String::from_raw_parts(s.ptr, s.len, s.capacity) };
-        str
+        unsafe { String::from_raw_parts(s.ptr, s.len, s.capacity) }
     }
 }
 
@@ -67,9 +71,9 @@ impl AsRef<str> for RustString {
     }
 }
 
-impl ToString for RustString {
-    fn to_string(&self) -> String {
-        self.as_ref().to_string()
+impl Display for RustString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
     }
 }
 
@@ -103,9 +107,9 @@ impl AsRef<str> for RustStr {
     }
 }
 
-impl ToString for RustStr {
-    fn to_string(&self) -> String {
-        self.as_ref().to_string()
+impl Display for RustStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_ref())
     }
 }
 
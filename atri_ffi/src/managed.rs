@@ -50,7 +50,8 @@ impl Managed {
 
     /// Consume this managed value, turning it into the type
     ///
-    /// Safety: This is unsafe because we don't know the type
+    /// # Safety
+    /// This is unsafe because we don't know the type
     /// behind the raw pointer
     pub unsafe fn into_value<T>(self) -> T {
         let ma = ManuallyDrop::new(self);
@@ -59,7 +60,8 @@ impl Managed {
 
     /// Construct a managed null value
     ///
-    /// Safety: This is unsafe because caller's behavior is unknown
+    /// # Safety
+    /// This is unsafe because caller's behavior is unknown
     pub unsafe fn null() -> Self {
         extern "C" fn _drop_null(_: *mut ()) {}
 
@@ -104,13 +106,15 @@ impl ManagedCloneable {
 
     /// Consume this managed value, turning it into the type
     ///
-    /// Safety: This is unsafe because we don't know the type
+    /// # Safety
+    /// This is unsafe because we don't know the type
     /// behind the raw pointer
     pub unsafe fn into_value<T>(self) -> T {
         self.value.into_value()
     }
 
-    /// Safety: use this as option
+    /// # Safety
+    /// Use this as option
     pub unsafe fn null() -> Self {
         extern "C" fn _clone_null(_: *const ()) -> ManagedCloneable {
             unsafe { ManagedCloneable::null() }
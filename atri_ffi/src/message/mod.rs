@@ -1,6 +1,6 @@
 use crate::message::meta::FFIMessageMetadata;
 use crate::{Managed, ManagedCloneable, RustString, RustVec};
-use std::mem::ManuallyDrop;
+use std::mem::{self, ManuallyDrop};
 
 pub mod forward;
 pub mod meta;
@@ -13,6 +13,15 @@ pub enum MessageElementFlag {
     At = 2,
     AtAll = 3,
     Face = 4,
+    Voice = 5,
+    Video = 6,
+    File = 7,
+    RichCard = 8,
+    Poke = 9,
+    Dice = 10,
+    MarketFace = 11,
+    FlashImage = 12,
+    LightApp = 13,
     Unknown = 255,
 }
 
@@ -33,6 +42,15 @@ impl TryFrom<u8> for MessageElementFlag {
             2 => Self::At,
             3 => Self::AtAll,
             4 => Self::Face,
+            5 => Self::Voice,
+            6 => Self::Video,
+            7 => Self::File,
+            8 => Self::RichCard,
+            9 => Self::Poke,
+            10 => Self::Dice,
+            11 => Self::MarketFace,
+            12 => Self::FlashImage,
+            13 => Self::LightApp,
             255 => Self::Unknown,
             _ => return Err(flag),
         })
@@ -51,6 +69,13 @@ pub struct FFIMessageElement {
     pub union: MessageElementUnion,
 }
 
+// 各成员均独占其数据, `market_face`与`Box`相同
+unsafe impl Send for MessageElementUnion {}
+unsafe impl Sync for MessageElementUnion {}
+
+// 元素以数组传递, 联合体变大会改变数组的步长, 新增的成员不应大于`FFIAt`
+const _: () = assert!(mem::size_of::<MessageElementUnion>() == mem::size_of::<FFIAt>());
+
 #[repr(C)]
 pub struct FFIMessageReceipt {
     pub seqs: RustVec<i32>,
@@ -65,6 +90,16 @@ pub union MessageElementUnion {
     pub at: ManuallyDrop<FFIAt>,
     pub at_all: (),
     pub face: ManuallyDrop<FFIFace>,
+    pub voice: ManuallyDrop<ManagedCloneable>,
+    pub video: ManuallyDrop<ManagedCloneable>,
+    pub file: ManuallyDrop<ManagedCloneable>,
+    pub rich_card: ManuallyDrop<FFIRichCard>,
+    pub poke: ManuallyDrop<FFIPoke>,
+    pub dice: i32,
+    /// 由`Box`分配, 以免增大联合体
+    pub market_face: *mut FFIMarketFace,
+    pub flash_image: ManuallyDrop<ManagedCloneable>,
+    pub light_app: ManuallyDrop<RustString>,
     pub unknown: ManuallyDrop<ManagedCloneable>,
 }
 
@@ -91,3 +126,28 @@ pub struct FFIFace {
     pub index: i32,
     pub name: RustString,
 }
+
+/// `is_xml`为`false`时`content`为json
+#[repr(C)]
+pub struct FFIRichCard {
+    pub is_xml: bool,
+    pub service_id: i32,
+    pub content: RustString,
+}
+
+#[repr(C)]
+pub struct FFIPoke {
+    pub kind: i32,
+    pub name: RustString,
+}
+
+#[repr(C)]
+pub struct FFIMarketFace {
+    pub name: RustString,
+    pub face_id: RustVec<u8>,
+    pub tab_id: u32,
+    pub item_type: u32,
+    pub sub_type: u32,
+    pub media_type: u32,
+    pub encrypt_key: RustVec<u8>,
+}
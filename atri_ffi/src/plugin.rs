@@ -1,9 +1,12 @@
-use crate::RustStr;
+use crate::{RustSlice, RustStr, RustVec};
+use std::fmt::{Display, Formatter};
 
+/// 插件导出的`on_init`函数的返回值, 宿主确认ABI兼容后才会调用`on_init`
 #[repr(C)]
 pub struct PluginInstance {
     pub should_drop: bool,
     pub vtb: PluginVTable,
+    /// ABI主版本号, 位置不可改变, 以便旧版本的宿主拒绝布局不同的插件
     pub abi_ver: u8,
     pub name: RustStr,
 }
@@ -16,7 +19,164 @@ pub struct PluginVTable {
     pub drop: extern "C" fn(*mut ()),
 }
 
+/// 插件的元数据, 由插件导出的`plugin_info`函数提供
+///
+/// 未导出该函数的插件视为元数据均为空
+#[repr(C)]
+pub struct PluginInfo {
+    pub version: RustStr,
+    pub authors: RustVec<RustStr>,
+    pub description: RustStr,
+    pub homepage: RustStr,
+    /// 依赖的插件, 格式为`插件名 版本要求`, 如`other_plugin >= 1.2`
+    pub depends: RustVec<RustStr>,
+}
+
+/// 编译时的ABI信息
+///
+/// 插件通过导出的`atri_abi_info`函数提供指向该结构的指针, 宿主通过[`AtriManager`](crate::ffi::AtriManager)提供其自身的信息.
+/// `version`须为首个字段, 以便在主版本号不一致时仍可读取
+#[repr(C)]
+pub struct AbiInfo {
+    pub version: AbiVersion,
+    /// 插件为`atri_plugin`的版本, 宿主为其自身的版本
+    pub crate_version: RustStr,
+    /// 编译时函数表包含的签名
+    pub signatures: RustSlice<u16>,
+}
+
+// 仅借用静态数据
+unsafe impl Send for AbiInfo {}
+unsafe impl Sync for AbiInfo {}
+
+impl AbiInfo {
+    pub fn new(crate_version: &'static str, signatures: &'static [u16]) -> Self {
+        Self {
+            version: AbiVersion::current(),
+            crate_version: RustStr::from(crate_version),
+            signatures: RustSlice::from(signatures),
+        }
+    }
+}
+
+/// 主版本号不同表示布局不兼容, 次版本号在新增函数时递增
+///
+/// 布局不可改变
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiVersion {
+    pub major: u8,
+    pub minor: u16,
+}
+
+impl AbiVersion {
+    pub const fn current() -> Self {
+        Self {
+            major: abi_version(),
+            minor: abi_minor_version(),
+        }
+    }
+}
+
+impl Display for AbiVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// ABI不兼容, 插件须使用对应版本的`atri_plugin`重新编译
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiMismatch {
+    pub host: AbiVersion,
+    pub plugin: AbiVersion,
+    /// 次版本号不低于插件的宿主仍缺少的函数签名, 主版本号不一致时为空
+    pub missing: Vec<u16>,
+}
+
+impl Display for AbiMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "incompatible abi: host {}, plugin {}",
+            self.host, self.plugin
+        )?;
+        if !self.missing.is_empty() {
+            write!(f, ", host is missing functions {:?}", self.missing)?;
+        }
+        f.write_str(", please rebuild the plugin with a compatible atri_plugin")
+    }
+}
+
+impl std::error::Error for AbiMismatch {}
+
+/// 检查宿主与插件的ABI是否兼容, 宿主加载插件与插件初始化时均以此判断
+///
+/// - 主版本号不一致时不兼容, 此时不会读取`version`以外的字段
+/// - 插件的次版本号较新时, 可能需要宿主尚未提供的函数, 插件调用这些函数时将得到`NotSupported`
+/// - 宿主的次版本号不低于插件时应提供插件需要的全部函数, 否则视为宿主移除了函数, 不兼容
+///
+/// 兼容时返回插件需要而宿主未提供的函数签名
+pub fn check_compatibility(host: &AbiInfo, plugin: &AbiInfo) -> Result<Vec<u16>, AbiMismatch> {
+    let mismatch = |missing| AbiMismatch {
+        host: host.version,
+        plugin: plugin.version,
+        missing,
+    };
+
+    if host.version.major != plugin.version.major {
+        return Err(mismatch(vec![]));
+    }
+
+    let provided = host.signatures.as_slice();
+    let missing: Vec<u16> = plugin
+        .signatures
+        .as_slice()
+        .iter()
+        .copied()
+        .filter(|sig| !provided.contains(sig))
+        .collect();
+
+    if !missing.is_empty() && plugin.version.minor <= host.version.minor {
+        return Err(mismatch(missing));
+    }
+
+    Ok(missing)
+}
+
 #[inline]
 pub const fn abi_version() -> u8 {
     include!("../abi-version")
 }
+
+#[inline]
+pub const fn abi_minor_version() -> u16 {
+    include!("../abi-minor-version")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::{check_compatibility, AbiInfo, AbiVersion};
+
+    #[test]
+    fn compatibility() {
+        let host = AbiInfo::new("0.1.0", &[0, 1, 100, 406]);
+        let mut plugin = AbiInfo::new("0.9.0", &[0, 1, 406, 411]);
+        plugin.version.minor = host.version.minor + 1;
+        assert_eq!(check_compatibility(&host, &plugin), Ok(vec![411]));
+
+        let older = AbiInfo::new("0.9.0", &[0, 406]);
+        assert_eq!(check_compatibility(&host, &older), Ok(vec![]));
+
+        plugin.version.minor = host.version.minor;
+        let err = check_compatibility(&host, &plugin).unwrap_err();
+        assert_eq!(err.missing, [411]);
+
+        let mut old = AbiInfo::new("0.8.0", &[0, 1]);
+        old.version = AbiVersion {
+            major: host.version.major - 1,
+            minor: 5,
+        };
+        let err = check_compatibility(&host, &old).unwrap_err();
+        assert_eq!(err.plugin.major, host.version.major - 1);
+    }
+}
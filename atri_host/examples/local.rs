@@ -0,0 +1,72 @@
+//! 使用空后端在本地运行插件
+//!
+//! `cargo run -p atri_host --example local -- <插件目录>`
+//!
+//! 每行输入将作为群消息分发给插件, 以`/f `开头的行则作为好友消息
+
+use atri_host::stub::{StubBackend, StubClient, StubFriend, StubGroup, StubMember};
+use atri_host::PluginManager;
+use std::io::BufRead;
+
+const CLIENT: i64 = 10000;
+const GROUP: i64 = 100;
+const USER: i64 = 20000;
+
+fn main() {
+    let dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("plugins"));
+
+    let backend = StubBackend::new().client(
+        StubClient::new(CLIENT, "Atri")
+            .group(StubGroup::new(GROUP, "本地测试群").member(StubMember::new(USER, "user")))
+            .friend(StubFriend::new(USER, "user")),
+    );
+
+    let mut manager = PluginManager::new(backend.clone()).expect("无法创建插件管理器");
+    for result in manager.load_plugins(&dir) {
+        if let Err(e) = result {
+            eprintln!("加载插件失败: {}", e);
+        }
+    }
+
+    for plugin in manager.plugins() {
+        let info = plugin.info();
+        println!(
+            "已加载插件: {} {} {:?} {}",
+            plugin.name(),
+            info.version,
+            info.authors,
+            info.description
+        );
+    }
+    manager.enable_all();
+
+    let dispatcher = manager.dispatcher();
+    let mut tasks = vec![];
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        let event = match line.strip_prefix("/f ") {
+            Some(msg) => backend.friend_message(CLIENT, USER, msg),
+            None => backend.group_message(CLIENT, GROUP, USER, line),
+        };
+
+        // 处理器可能会等待后续的事件, 故每个事件都在单独的协程中分发
+        if let Some(event) = event {
+            let dispatcher = dispatcher.clone();
+            tasks.push(manager.runtime().spawn(async move {
+                if dispatcher.dispatch(event).await {
+                    println!("[stub] 事件已被拦截");
+                }
+            }));
+        }
+    }
+
+    for task in tasks {
+        let _ = manager.runtime().block_on(task);
+    }
+    manager.disable_all();
+}
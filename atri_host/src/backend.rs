@@ -0,0 +1,436 @@
+use crate::error::{HostError, HostResult};
+use crate::message::forward::ForwardNode;
+use crate::message::meta::{Anonymous, MessageReceipt};
+use crate::message::{Image, MessageChain, Voice};
+use std::future::{ready, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// 日志等级, 与插件侧的日志宏一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn from_u8(level: u8) -> Self {
+        match level {
+            0 => Self::Trace,
+            1 => Self::Debug,
+            2 => Self::Info,
+            3 => Self::Warn,
+            _ => Self::Error,
+        }
+    }
+}
+
+/// 协议实现的入口, 由插件管理器持有
+///
+/// 返回`&str`的方法所返回的字符串需在对象存活期间保持有效, 插件会直接借用它们
+pub trait Backend: Send + Sync + 'static {
+    fn clients(&self) -> Vec<Arc<dyn BotClient>>;
+
+    fn find_client(&self, id: i64) -> Option<Arc<dyn BotClient>> {
+        self.clients().into_iter().find(|c| c.id() == id)
+    }
+
+    fn log(&self, plugin: &str, level: LogLevel, msg: &str) {
+        eprintln!("[{:?}][{}] {}", level, plugin, msg);
+    }
+}
+
+pub trait BotClient: Send + Sync {
+    fn id(&self) -> i64;
+
+    fn nickname(&self) -> String;
+
+    fn find_group(&self, id: i64) -> Option<Arc<dyn BotGroup>>;
+
+    fn find_friend(&self, id: i64) -> Option<Arc<dyn BotFriend>>;
+
+    fn groups(&self) -> Vec<Arc<dyn BotGroup>>;
+
+    fn friends(&self) -> Vec<Arc<dyn BotFriend>>;
+
+    /// 查找陌生人, 不存在或无法查询时为`None`
+    fn find_stranger(&self, _id: i64) -> BoxFuture<Option<Arc<dyn BotStranger>>> {
+        Box::pin(ready(None))
+    }
+}
+
+pub trait BotGroup: Send + Sync {
+    fn id(&self) -> i64;
+
+    fn name(&self) -> &str;
+
+    fn client(&self) -> Arc<dyn BotClient>;
+
+    fn members(self: Arc<Self>) -> BoxFuture<Vec<Arc<dyn BotMember>>>;
+
+    fn find_member(self: Arc<Self>, id: i64) -> BoxFuture<Option<Arc<dyn BotMember>>>;
+
+    fn send_message(self: Arc<Self>, chain: MessageChain) -> BoxFuture<HostResult<MessageReceipt>>;
+
+    fn send_forward_message(
+        self: Arc<Self>,
+        _nodes: Vec<ForwardNode>,
+    ) -> BoxFuture<HostResult<MessageReceipt>> {
+        not_supported()
+    }
+
+    fn upload_image(self: Arc<Self>, data: Vec<u8>) -> BoxFuture<HostResult<Image>>;
+
+    fn upload_voice(self: Arc<Self>, _data: Vec<u8>) -> BoxFuture<HostResult<Voice>> {
+        not_supported()
+    }
+
+    fn change_name(self: Arc<Self>, _name: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn invite(self: Arc<Self>, _id: i64) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 撤回消息, 撤回其他成员的消息需要管理员权限
+    fn recall(self: Arc<Self>, _receipt: MessageReceipt) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 退出群, 若已退出则返回`false`
+    fn quit(self: Arc<Self>) -> BoxFuture<bool> {
+        Box::pin(ready(false))
+    }
+
+    fn member_count(&self) -> u32;
+
+    fn owner(self: Arc<Self>) -> BoxFuture<Option<Arc<dyn BotMember>>> {
+        let members = self.members();
+        Box::pin(async move {
+            members
+                .await
+                .into_iter()
+                .find(|m| m.role() == MemberRole::Owner)
+        })
+    }
+
+    fn settings(&self) -> GroupSettings {
+        GroupSettings::default()
+    }
+
+    fn mute_all(self: Arc<Self>, _mute: bool) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 禁言匿名成员, 以`anon_id`区分不同的匿名身份
+    fn mute_anonymous(
+        self: Arc<Self>,
+        _anonymous: Anonymous,
+        _duration: Duration,
+    ) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn set_anonymous(self: Arc<Self>, _allow: bool) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn change_avatar(self: Arc<Self>, _data: Vec<u8>) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn announcements(self: Arc<Self>) -> BoxFuture<HostResult<Vec<Announcement>>> {
+        not_supported()
+    }
+
+    /// 发布群公告, 返回公告的id
+    fn publish_announcement(self: Arc<Self>, _content: String) -> BoxFuture<HostResult<String>> {
+        not_supported()
+    }
+
+    fn delete_announcement(self: Arc<Self>, _fid: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 列出文件夹中的文件与子文件夹, 根目录为`/`
+    fn list_files(self: Arc<Self>, _folder: String) -> BoxFuture<HostResult<Vec<GroupFileEntry>>> {
+        not_supported()
+    }
+
+    fn upload_file(
+        self: Arc<Self>,
+        _folder: String,
+        _name: String,
+        _data: Vec<u8>,
+    ) -> BoxFuture<HostResult<GroupFile>> {
+        not_supported()
+    }
+
+    fn download_file(self: Arc<Self>, _id: String) -> BoxFuture<HostResult<Vec<u8>>> {
+        not_supported()
+    }
+
+    fn rename_file(self: Arc<Self>, _id: String, _name: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn move_file(self: Arc<Self>, _id: String, _folder: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn delete_file(self: Arc<Self>, _id: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 从消息缓存中获取消息, 不在缓存中时为`None`
+    fn get_message(self: Arc<Self>, _seq: i32) -> BoxFuture<HostResult<Option<MessageChain>>> {
+        not_supported()
+    }
+
+    /// 序号小于`before_seq`的最近`count`条消息, 按时间顺序排列
+    fn history(
+        self: Arc<Self>,
+        _before_seq: i32,
+        _count: u32,
+    ) -> BoxFuture<HostResult<Vec<MessageChain>>> {
+        not_supported()
+    }
+}
+
+/// 群公告
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub fid: String,
+    pub sender: i64,
+    pub time: i64,
+    pub content: String,
+}
+
+/// 群文件系统中的一项
+#[derive(Debug, Clone)]
+pub enum GroupFileEntry {
+    Folder(GroupFolder),
+    File(GroupFile),
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupFolder {
+    pub id: String,
+    pub name: String,
+    /// 上级文件夹的id
+    pub parent: String,
+    pub file_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupFile {
+    pub id: String,
+    pub name: String,
+    /// 所在文件夹的id
+    pub folder: String,
+    pub size: u64,
+    pub uploader: i64,
+    /// 上传时间, 单位为秒
+    pub upload_time: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupSettings {
+    pub mute_all: bool,
+    pub allow_anonymous: bool,
+    pub allow_member_invite: bool,
+}
+
+pub trait BotFriend: Send + Sync {
+    fn id(&self) -> i64;
+
+    fn nickname(&self) -> &str;
+
+    fn client(&self) -> Arc<dyn BotClient>;
+
+    fn send_message(self: Arc<Self>, chain: MessageChain) -> BoxFuture<HostResult<MessageReceipt>>;
+
+    fn upload_image(self: Arc<Self>, data: Vec<u8>) -> BoxFuture<HostResult<Image>>;
+
+    fn upload_voice(self: Arc<Self>, _data: Vec<u8>) -> BoxFuture<HostResult<Voice>> {
+        not_supported()
+    }
+
+    fn recall(self: Arc<Self>, _receipt: MessageReceipt) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 从消息缓存中获取消息, 不在缓存中时为`None`
+    fn get_message(self: Arc<Self>, _seq: i32) -> BoxFuture<HostResult<Option<MessageChain>>> {
+        not_supported()
+    }
+
+    /// 序号小于`before_seq`的最近`count`条消息, 按时间顺序排列
+    fn history(
+        self: Arc<Self>,
+        _before_seq: i32,
+        _count: u32,
+    ) -> BoxFuture<HostResult<Vec<MessageChain>>> {
+        not_supported()
+    }
+}
+
+pub trait BotStranger: Send + Sync {
+    fn id(&self) -> i64;
+
+    fn nickname(&self) -> &str;
+
+    fn client(&self) -> Arc<dyn BotClient>;
+
+    fn send_message(
+        self: Arc<Self>,
+        _chain: MessageChain,
+    ) -> BoxFuture<HostResult<MessageReceipt>> {
+        not_supported()
+    }
+
+    fn upload_image(self: Arc<Self>, _data: Vec<u8>) -> BoxFuture<HostResult<Image>> {
+        not_supported()
+    }
+}
+
+pub trait BotMember: Send + Sync {
+    fn id(&self) -> i64;
+
+    fn nickname(&self) -> &str;
+
+    fn card_name(&self) -> &str;
+
+    fn group(&self) -> Arc<dyn BotGroup>;
+
+    fn role(&self) -> MemberRole {
+        MemberRole::Member
+    }
+
+    /// 入群时间, 单位为秒
+    fn join_time(&self) -> i64 {
+        0
+    }
+
+    /// 最后发言时间, 单位为秒
+    fn last_speak_time(&self) -> i64 {
+        0
+    }
+
+    fn level(&self) -> u16 {
+        0
+    }
+
+    /// 通过临时会话发送消息
+    fn send_message(
+        self: Arc<Self>,
+        _chain: MessageChain,
+    ) -> BoxFuture<HostResult<MessageReceipt>> {
+        not_supported()
+    }
+
+    fn upload_image(self: Arc<Self>, _data: Vec<u8>) -> BoxFuture<HostResult<Image>> {
+        not_supported()
+    }
+
+    fn change_card_name(self: Arc<Self>, _card: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 禁言, 时长为0时解除禁言
+    fn mute(self: Arc<Self>, _duration: Duration) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn kick(self: Arc<Self>, _reason: String, _block: bool) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn set_admin(self: Arc<Self>, _admin: bool) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    fn set_special_title(self: Arc<Self>, _title: String) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+}
+
+/// 好友申请, 加群申请或入群邀请
+///
+/// 同一申请只应被处理一次, 重复处理时返回错误
+pub trait BotRequest: Send + Sync {
+    fn accept(self: Arc<Self>) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+
+    /// 拒绝申请, `block`为`true`时不再接受来自对方的申请
+    fn reject(self: Arc<Self>, _reason: String, _block: bool) -> BoxFuture<HostResult<()>> {
+        not_supported()
+    }
+}
+
+/// 群成员的身份, 与插件侧的`MemberRole`一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemberRole {
+    Member = 0,
+    Administrator = 1,
+    Owner = 2,
+}
+
+pub trait BotImage: Send + Sync {
+    fn id(&self) -> &str;
+
+    fn url(&self) -> String;
+}
+
+pub trait BotVideo: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// 文件大小, 单位为字节
+    fn size(&self) -> u64;
+
+    fn url(&self) -> String;
+
+    fn download(self: Arc<Self>) -> BoxFuture<HostResult<Vec<u8>>> {
+        not_supported()
+    }
+}
+
+pub trait BotFile: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// 文件大小, 单位为字节
+    fn size(&self) -> u64;
+
+    fn url(&self) -> String;
+
+    fn download(self: Arc<Self>) -> BoxFuture<HostResult<Vec<u8>>> {
+        not_supported()
+    }
+}
+
+pub trait BotVoice: Send + Sync {
+    fn url(&self) -> String;
+
+    fn md5(&self) -> &[u8];
+
+    /// 文件大小, 单位为字节
+    fn size(&self) -> u32;
+
+    fn duration(&self) -> Duration;
+
+    fn download(self: Arc<Self>) -> BoxFuture<HostResult<Vec<u8>>> {
+        not_supported()
+    }
+}
+
+fn not_supported<T: Send + 'static>() -> BoxFuture<HostResult<T>> {
+    Box::pin(ready(Err(HostError::NotSupported)))
+}
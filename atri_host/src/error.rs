@@ -0,0 +1,78 @@
+use atri_ffi::plugin::AbiMismatch;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+pub type HostResult<T> = Result<T, HostError>;
+
+/// 后端执行操作时产生的错误, 将以字符串形式传递给插件
+#[derive(Debug)]
+pub enum HostError {
+    NotSupported,
+    ClientError(String),
+}
+
+impl Display for HostError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for HostError {}
+
+/// 加载插件时产生的错误
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Library {
+        path: PathBuf,
+        error: libloading::Error,
+    },
+    AbiMismatch {
+        path: PathBuf,
+        error: AbiMismatch,
+    },
+    /// 插件声明的元数据无效
+    Metadata {
+        path: PathBuf,
+        error: String,
+    },
+    /// 依赖的插件不存在或版本不满足要求
+    Dependency {
+        plugin: String,
+        dependency: String,
+        found: Option<String>,
+    },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Library { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::AbiMismatch { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::Metadata { path, error } => write!(f, "{}: {}", path.display(), error),
+            Self::Dependency {
+                plugin,
+                dependency,
+                found: Some(version),
+            } => write!(
+                f,
+                "{}: requires {}, found version {}",
+                plugin, dependency, version
+            ),
+            Self::Dependency {
+                plugin,
+                dependency,
+                found: None,
+            } => write!(f, "{}: requires {}, not found", plugin, dependency),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
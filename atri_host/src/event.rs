@@ -0,0 +1,391 @@
+use crate::backend::{BotClient, BotFriend, BotGroup, BotMember, BotRequest};
+use crate::handle::OwnedHandle;
+use crate::message::meta::Anonymous;
+use crate::message::MessageChain;
+use atri_ffi::ManagedCloneable;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// 由后端产生, 交由插件处理的事件
+pub enum HostEvent {
+    ClientLogin {
+        client: Arc<dyn BotClient>,
+    },
+    GroupMessage {
+        group: Arc<dyn BotGroup>,
+        sender: Member,
+        message: MessageChain,
+    },
+    FriendMessage {
+        friend: Arc<dyn BotFriend>,
+        message: MessageChain,
+    },
+    NewFriend {
+        friend: Arc<dyn BotFriend>,
+    },
+    DeleteFriend {
+        friend: Arc<dyn BotFriend>,
+    },
+    GroupMemberJoin {
+        group: Arc<dyn BotGroup>,
+        member: Arc<dyn BotMember>,
+        /// 邀请或同意入群的成员
+        operator: Option<Arc<dyn BotMember>>,
+    },
+    GroupMemberLeave {
+        group: Arc<dyn BotGroup>,
+        member: Arc<dyn BotMember>,
+        /// 移出该成员的管理员, 主动退群或管理员已不在群中时为`None`
+        operator: Option<Arc<dyn BotMember>>,
+        /// 是否被移出群
+        kicked: bool,
+    },
+    GroupRecall {
+        group: Arc<dyn BotGroup>,
+        operator_id: i64,
+        /// 撤回消息的成员, 已不在群中时为`None`
+        operator: Option<Arc<dyn BotMember>>,
+        sender: i64,
+        seqs: Vec<i32>,
+        time: i32,
+    },
+    FriendRecall {
+        friend: Arc<dyn BotFriend>,
+        operator_id: i64,
+        sender: i64,
+        seqs: Vec<i32>,
+        time: i32,
+    },
+    FriendRequest {
+        client: Arc<dyn BotClient>,
+        requester_id: i64,
+        requester_nickname: String,
+        message: String,
+        request: Arc<dyn BotRequest>,
+    },
+    GroupInvited {
+        client: Arc<dyn BotClient>,
+        group_id: i64,
+        group_name: String,
+        inviter_id: i64,
+        inviter_nickname: String,
+        request: Arc<dyn BotRequest>,
+    },
+    GroupJoinRequest {
+        group: Arc<dyn BotGroup>,
+        requester_id: i64,
+        requester_nickname: String,
+        message: String,
+        request: Arc<dyn BotRequest>,
+    },
+    /// 群成员通过临时会话发送的消息
+    GroupTempMessage {
+        group: Arc<dyn BotGroup>,
+        sender: Arc<dyn BotMember>,
+        message: MessageChain,
+    },
+}
+
+#[derive(Clone)]
+pub enum Member {
+    Named(Arc<dyn BotMember>),
+    Anonymous(Anonymous),
+}
+
+impl HostEvent {
+    /// 转换为事件标签与事件的宿主侧数据
+    pub(crate) fn into_raw(self, intercepted: &Arc<AtomicBool>) -> (u8, ManagedCloneable) {
+        fn data<T: Clone>(intercepted: &Arc<AtomicBool>, inner: T) -> ManagedCloneable {
+            ManagedCloneable::from_value(EventData {
+                intercepted: intercepted.clone(),
+                inner,
+            })
+        }
+
+        match self {
+            Self::ClientLogin { client } => (0, data(intercepted, OwnedHandle::new(client))),
+            Self::GroupMessage {
+                group,
+                sender,
+                message,
+            } => (
+                1,
+                data(
+                    intercepted,
+                    GroupMessageData {
+                        group: OwnedHandle::new(group),
+                        sender,
+                        message,
+                    },
+                ),
+            ),
+            Self::FriendMessage { friend, message } => (
+                2,
+                data(
+                    intercepted,
+                    FriendMessageData {
+                        friend: OwnedHandle::new(friend),
+                        message,
+                    },
+                ),
+            ),
+            Self::NewFriend { friend } => (3, data(intercepted, OwnedHandle::new(friend))),
+            Self::DeleteFriend { friend } => (
+                4,
+                data(
+                    intercepted,
+                    DeleteFriendData {
+                        client: OwnedHandle::new(friend.client()),
+                        friend_id: friend.id(),
+                    },
+                ),
+            ),
+            Self::GroupMemberJoin {
+                group,
+                member,
+                operator,
+            } => (
+                5,
+                data(
+                    intercepted,
+                    GroupMemberData {
+                        group: OwnedHandle::new(group),
+                        member,
+                        operator,
+                        kicked: false,
+                    },
+                ),
+            ),
+            Self::GroupMemberLeave {
+                group,
+                member,
+                operator,
+                kicked,
+            } => (
+                6,
+                data(
+                    intercepted,
+                    GroupMemberData {
+                        group: OwnedHandle::new(group),
+                        member,
+                        operator,
+                        kicked,
+                    },
+                ),
+            ),
+            Self::GroupRecall {
+                group,
+                operator_id,
+                operator,
+                sender,
+                seqs,
+                time,
+            } => (
+                7,
+                data(
+                    intercepted,
+                    GroupRecallData {
+                        group: OwnedHandle::new(group),
+                        operator_id,
+                        operator,
+                        sender,
+                        seqs,
+                        time,
+                    },
+                ),
+            ),
+            Self::FriendRecall {
+                friend,
+                operator_id,
+                sender,
+                seqs,
+                time,
+            } => (
+                8,
+                data(
+                    intercepted,
+                    FriendRecallData {
+                        friend: OwnedHandle::new(friend),
+                        operator_id,
+                        sender,
+                        seqs,
+                        time,
+                    },
+                ),
+            ),
+            Self::FriendRequest {
+                client,
+                requester_id,
+                requester_nickname,
+                message,
+                request,
+            } => (
+                9,
+                data(
+                    intercepted,
+                    FriendRequestData {
+                        client: OwnedHandle::new(client),
+                        requester_id,
+                        requester_nickname,
+                        message,
+                        request,
+                    },
+                ),
+            ),
+            Self::GroupInvited {
+                client,
+                group_id,
+                group_name,
+                inviter_id,
+                inviter_nickname,
+                request,
+            } => (
+                10,
+                data(
+                    intercepted,
+                    GroupInvitedData {
+                        client: OwnedHandle::new(client),
+                        group_id,
+                        group_name,
+                        inviter_id,
+                        inviter_nickname,
+                        request,
+                    },
+                ),
+            ),
+            Self::GroupJoinRequest {
+                group,
+                requester_id,
+                requester_nickname,
+                message,
+                request,
+            } => (
+                11,
+                data(
+                    intercepted,
+                    GroupJoinRequestData {
+                        group: OwnedHandle::new(group),
+                        requester_id,
+                        requester_nickname,
+                        message,
+                        request,
+                    },
+                ),
+            ),
+            Self::GroupTempMessage {
+                group,
+                sender,
+                message,
+            } => (
+                12,
+                data(
+                    intercepted,
+                    GroupTempMessageData {
+                        group: OwnedHandle::new(group),
+                        sender,
+                        message,
+                    },
+                ),
+            ),
+        }
+    }
+}
+
+/// 事件的宿主侧数据, 拦截标志随事件一同克隆
+#[derive(Clone)]
+pub(crate) struct EventData<T> {
+    // 插件持有指向该标志的指针
+    #[allow(dead_code)]
+    pub intercepted: Arc<AtomicBool>,
+    pub inner: T,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupMessageData {
+    pub group: OwnedHandle<dyn BotGroup>,
+    pub sender: Member,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendMessageData {
+    pub friend: OwnedHandle<dyn BotFriend>,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupTempMessageData {
+    pub group: OwnedHandle<dyn BotGroup>,
+    pub sender: Arc<dyn BotMember>,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) struct DeleteFriendData {
+    pub client: OwnedHandle<dyn BotClient>,
+    pub friend_id: i64,
+}
+
+/// 插件持有的匿名成员, 附带其所在的群
+#[derive(Clone)]
+pub(crate) struct AnonymousMemberData {
+    pub group: Arc<dyn BotGroup>,
+    pub anonymous: Anonymous,
+}
+
+/// 成员入群与离开事件共用
+#[derive(Clone)]
+pub(crate) struct GroupMemberData {
+    pub group: OwnedHandle<dyn BotGroup>,
+    pub member: Arc<dyn BotMember>,
+    pub operator: Option<Arc<dyn BotMember>>,
+    /// 仅用于离开事件
+    pub kicked: bool,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupRecallData {
+    pub group: OwnedHandle<dyn BotGroup>,
+    pub operator_id: i64,
+    pub operator: Option<Arc<dyn BotMember>>,
+    pub sender: i64,
+    pub seqs: Vec<i32>,
+    pub time: i32,
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendRecallData {
+    pub friend: OwnedHandle<dyn BotFriend>,
+    pub operator_id: i64,
+    pub sender: i64,
+    pub seqs: Vec<i32>,
+    pub time: i32,
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendRequestData {
+    pub client: OwnedHandle<dyn BotClient>,
+    pub requester_id: i64,
+    pub requester_nickname: String,
+    pub message: String,
+    pub request: Arc<dyn BotRequest>,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupInvitedData {
+    pub client: OwnedHandle<dyn BotClient>,
+    pub group_id: i64,
+    pub group_name: String,
+    pub inviter_id: i64,
+    pub inviter_nickname: String,
+    pub request: Arc<dyn BotRequest>,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupJoinRequestData {
+    pub group: OwnedHandle<dyn BotGroup>,
+    pub requester_id: i64,
+    pub requester_nickname: String,
+    pub message: String,
+    pub request: Arc<dyn BotRequest>,
+}
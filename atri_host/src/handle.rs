@@ -0,0 +1,66 @@
+use atri_ffi::{Handle, PHandle};
+use std::marker::PhantomData;
+use std::ptr::null;
+use std::sync::Arc;
+
+// 句柄指向装箱后的`Arc`, 以便以瘦指针的形式传递trait对象
+
+pub(crate) fn into_handle<T: ?Sized>(arc: Arc<T>) -> Handle {
+    Box::into_raw(Box::new(arc)) as Handle
+}
+
+pub(crate) fn opt_into_handle<T: ?Sized>(arc: Option<Arc<T>>) -> Handle {
+    arc.map(into_handle).unwrap_or_else(null)
+}
+
+/// # Safety
+/// 句柄必须由[`into_handle`]以相同的类型构造, 且尚未释放
+pub(crate) unsafe fn handle_ref<'a, T: ?Sized>(handle: Handle) -> &'a Arc<T> {
+    &*(handle as *const Arc<T>)
+}
+
+/// # Safety
+/// 同[`handle_ref`]
+pub(crate) unsafe fn clone_handle<T: ?Sized>(handle: Handle) -> Handle {
+    into_handle(handle_ref::<T>(handle).clone())
+}
+
+/// # Safety
+/// 同[`handle_ref`], 释放后不可再使用该句柄
+pub(crate) unsafe fn drop_handle<T: ?Sized>(handle: Handle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle as *mut Arc<T>));
+    }
+}
+
+/// 由宿主持有的句柄, 供事件以`PHandle`的形式借出
+pub(crate) struct OwnedHandle<T: ?Sized>(Handle, PhantomData<Arc<T>>);
+
+unsafe impl<T: ?Sized + Send + Sync> Send for OwnedHandle<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for OwnedHandle<T> {}
+
+impl<T: ?Sized> OwnedHandle<T> {
+    pub fn new(arc: Arc<T>) -> Self {
+        Self(into_handle(arc), PhantomData)
+    }
+
+    pub fn as_phandle(&self) -> PHandle {
+        &self.0
+    }
+
+    pub fn get(&self) -> &Arc<T> {
+        unsafe { handle_ref(self.0) }
+    }
+}
+
+impl<T: ?Sized> Clone for OwnedHandle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get().clone())
+    }
+}
+
+impl<T: ?Sized> Drop for OwnedHandle<T> {
+    fn drop(&mut self) {
+        unsafe { drop_handle::<T>(self.0) }
+    }
+}
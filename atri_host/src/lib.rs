@@ -0,0 +1,19 @@
+//! AtriPlugin插件宿主的参考实现
+//!
+//! 负责加载插件动态库, 驱动插件的生命周期,
+//! 并将插件的调用转交给实现了[`Backend`](backend::Backend)的协议后端
+
+pub mod backend;
+pub mod error;
+pub mod event;
+mod handle;
+mod listener;
+mod manager;
+pub mod message;
+pub mod metadata;
+mod plugin;
+pub mod stub;
+mod vtable;
+
+pub use manager::{Dispatcher, PluginManager};
+pub use plugin::Plugin;
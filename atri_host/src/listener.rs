@@ -0,0 +1,158 @@
+use atri_ffi::closure::FFIFn;
+use atri_ffi::ffi::FFIEvent;
+use atri_ffi::future::FFIFuture;
+use atri_ffi::{FFIOption, ManagedCloneable};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::oneshot;
+
+#[derive(Default)]
+pub(crate) struct Listeners {
+    entries: RwLock<Vec<Arc<ListenerEntry>>>,
+    next_id: AtomicU64,
+}
+
+struct ListenerEntry {
+    id: u64,
+    priority: u8,
+    kind: ListenerKind,
+}
+
+enum ListenerKind {
+    Handler {
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+        /// 非并发的监听器需等待上一个事件处理完毕
+        gate: Option<tokio::sync::Mutex<()>>,
+    },
+    Waiter {
+        filter: FFIFn<FFIEvent, bool>,
+        sender: Mutex<Option<oneshot::Sender<FFIEvent>>>,
+    },
+}
+
+/// 释放时关闭监听器
+pub(crate) struct ListenerHandle {
+    id: u64,
+    listeners: Weak<Listeners>,
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            listeners.remove(self.id);
+        }
+    }
+}
+
+impl Listeners {
+    fn push(self: &Arc<Self>, priority: u8, kind: ListenerKind) -> ListenerHandle {
+        let mut entries = self.entries.write().unwrap();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        entries.push(Arc::new(ListenerEntry { id, priority, kind }));
+        ListenerHandle {
+            id,
+            listeners: Arc::downgrade(self),
+        }
+    }
+
+    pub fn add(
+        self: &Arc<Self>,
+        concurrent: bool,
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+        priority: u8,
+    ) -> ListenerHandle {
+        let gate = (!concurrent).then(tokio::sync::Mutex::default);
+        self.push(priority, ListenerKind::Handler { handler, gate })
+    }
+
+    /// 等待下一个满足条件的事件, 计时器在调用时即注册到给定的运行时
+    pub fn next_event(
+        self: &Arc<Self>,
+        runtime: &Handle,
+        timeout: Duration,
+        filter: FFIFn<FFIEvent, bool>,
+        priority: u8,
+    ) -> impl Future<Output = FFIOption<FFIEvent>> {
+        let (tx, rx) = oneshot::channel();
+        let handle = self.push(
+            priority,
+            ListenerKind::Waiter {
+                filter,
+                sender: Mutex::new(Some(tx)),
+            },
+        );
+
+        let timeout = {
+            let _enter = runtime.enter();
+            tokio::time::timeout(timeout, rx)
+        };
+
+        async move {
+            let event = timeout.await.ok().and_then(Result::ok);
+            drop(handle);
+            FFIOption::from(event)
+        }
+    }
+
+    pub fn remove(&self, id: u64) {
+        self.entries.write().unwrap().retain(|l| l.id != id);
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// 按优先级将事件依次交给各监听器处理, 同一优先级下等待事件的监听器优先
+    ///
+    /// 每个监听器处理完毕后才会交给下一个监听器, 事件被拦截后不再传递
+    pub async fn dispatch(&self, t: u8, intercepted: &Arc<AtomicBool>, base: ManagedCloneable) {
+        let ffi = || FFIEvent::from(t, Arc::as_ptr(intercepted).cast(), base.clone());
+
+        let mut entries = self.entries.read().unwrap().clone();
+        entries.sort_by_key(|l| {
+            let is_handler = matches!(l.kind, ListenerKind::Handler { .. });
+            (l.priority, is_handler, l.id)
+        });
+
+        for l in entries {
+            if intercepted.load(Ordering::SeqCst) {
+                break;
+            }
+
+            match &l.kind {
+                ListenerKind::Handler { handler, gate } => {
+                    let fu = handler.invoke(ffi());
+                    let keep = match gate {
+                        Some(gate) => {
+                            let _guard = gate.lock().await;
+                            fu.await
+                        }
+                        None => fu.await,
+                    };
+
+                    if !keep {
+                        self.remove(l.id);
+                    }
+                }
+                ListenerKind::Waiter { filter, sender } => {
+                    let mut sender = sender.lock().unwrap();
+                    if sender.is_some() && filter.invoke(ffi()) {
+                        if let Some(tx) = sender.take() {
+                            let _ = tx.send(ffi());
+                        }
+                        drop(sender);
+                        self.remove(l.id);
+                    }
+                }
+            }
+        }
+    }
+}
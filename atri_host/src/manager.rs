@@ -0,0 +1,531 @@
+use crate::backend::{Backend, LogLevel};
+use crate::error::LoadError;
+use crate::event::HostEvent;
+use crate::listener::Listeners;
+use crate::metadata::load_order;
+use crate::plugin::Plugin;
+use libloading::Library;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+
+/// 插件管理器与插件共享的状态, 其指针即为传递给插件的`manager_ptr`
+pub(crate) struct ManagerState {
+    pub backend: Arc<dyn Backend>,
+    pub runtime: Handle,
+    pub listeners: Arc<Listeners>,
+    pub workspaces: RwLock<PathBuf>,
+    /// 插件句柄到插件名称的映射
+    pub names: RwLock<HashMap<usize, String>>,
+}
+
+impl ManagerState {
+    pub fn plugin_name(&self, handle: usize) -> String {
+        self.names
+            .read()
+            .unwrap()
+            .get(&handle)
+            .cloned()
+            .unwrap_or_else(|| format!("Plugin({})", handle))
+    }
+}
+
+// 旧版本的插件注册监听器与获取客户端时不携带管理器指针, 此时使用默认管理器
+static DEFAULT: RwLock<Option<Weak<ManagerState>>> = RwLock::new(None);
+
+/// 默认管理器, 即创建时没有其他存活管理器的管理器
+pub(crate) fn default_manager() -> Option<Arc<ManagerState>> {
+    DEFAULT.read().unwrap().as_ref().and_then(Weak::upgrade)
+}
+
+/// 负责加载插件, 驱动插件的生命周期并向插件分发事件
+///
+/// 每个插件管理器的状态相互独立, 插件调用宿主函数时携带其所属管理器的指针;
+/// 旧版本的插件只能使用默认管理器, 即创建时没有其他存活管理器的管理器
+pub struct PluginManager {
+    state: Arc<ManagerState>,
+    plugins: Vec<Plugin>,
+    dependencies: Vec<Library>,
+    next_handle: usize,
+    runtime: Option<Runtime>,
+}
+
+impl PluginManager {
+    /// 使用自行构造的多线程运行时
+    pub fn new<B: Backend>(backend: B) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("atri-plugin")
+            .enable_all()
+            .build()?;
+
+        let mut manager = Self::with_runtime(backend, runtime.handle().clone())?;
+        manager.runtime = Some(runtime);
+        Ok(manager)
+    }
+
+    /// 使用已有的运行时, 运行时需启用计时器
+    pub fn with_runtime<B: Backend>(backend: B, runtime: Handle) -> io::Result<Self> {
+        let state = Arc::new(ManagerState {
+            backend: Arc::new(backend),
+            runtime,
+            listeners: Arc::default(),
+            workspaces: RwLock::new(PathBuf::from("workspaces")),
+            names: RwLock::default(),
+        });
+
+        let mut default = DEFAULT.write().unwrap();
+        if default.as_ref().and_then(Weak::upgrade).is_none() {
+            *default = Some(Arc::downgrade(&state));
+        }
+        drop(default);
+
+        Ok(Self {
+            state,
+            plugins: vec![],
+            dependencies: vec![],
+            next_handle: 0,
+            runtime: None,
+        })
+    }
+
+    /// 设置插件工作目录的根目录, 每个插件的工作目录为`<root>/<插件名>`
+    pub fn set_workspaces<P: Into<PathBuf>>(&mut self, root: P) {
+        *self.state.workspaces.write().unwrap() = root.into();
+    }
+
+    /// 加载目录下的所有插件
+    ///
+    /// 在加载插件前会先加载`<dir>/dependencies`内的所有动态库,
+    /// 单个插件加载失败不影响其他插件
+    ///
+    /// 加载完毕后将卸载依赖不满足的插件, 并按依赖关系调整插件的启用顺序
+    pub fn load_plugins<P: AsRef<Path>>(&mut self, dir: P) -> Vec<Result<usize, LoadError>> {
+        let dir = dir.as_ref();
+
+        let mut results = vec![];
+        match library_files(&dir.join("dependencies")) {
+            Ok(deps) => {
+                for path in deps {
+                    match unsafe { Library::new(&path) } {
+                        Ok(lib) => self.dependencies.push(lib),
+                        Err(error) => results.push(Err(LoadError::Library { path, error })),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => results.push(Err(e.into())),
+        }
+
+        match library_files(dir) {
+            Ok(plugins) => {
+                for path in plugins {
+                    results.push(self.load_plugin(path));
+                }
+            }
+            Err(e) => results.push(Err(e.into())),
+        }
+
+        self.resolve_dependencies(&mut results);
+
+        results
+    }
+
+    fn resolve_dependencies(&mut self, results: &mut [Result<usize, LoadError>]) {
+        // 卸载插件后依赖它的插件也将不满足, 故需重复检查
+        loop {
+            let unsatisfied = self.plugins.iter().enumerate().find_map(|(i, plugin)| {
+                let dep = plugin.info().depends.iter().find(|dep| {
+                    !self
+                        .plugins
+                        .iter()
+                        .any(|p| p.name() == dep.name && dep.matches(&p.info().version))
+                })?;
+
+                let found = self
+                    .plugins
+                    .iter()
+                    .find(|p| p.name() == dep.name)
+                    .map(|p| p.info().version.clone());
+
+                Some((
+                    i,
+                    LoadError::Dependency {
+                        plugin: plugin.name().to_owned(),
+                        dependency: dep.to_string(),
+                        found,
+                    },
+                ))
+            });
+
+            let Some((i, error)) = unsatisfied else {
+                break;
+            };
+
+            let plugin = self.plugins.remove(i);
+            self.state.names.write().unwrap().remove(&plugin.handle());
+            match results
+                .iter_mut()
+                .find(|r| matches!(r, Ok(handle) if *handle == plugin.handle()))
+            {
+                Some(result) => *result = Err(error),
+                None => self
+                    .state
+                    .backend
+                    .log(plugin.name(), LogLevel::Error, &error.to_string()),
+            }
+        }
+
+        let order = {
+            let names: Vec<_> = self.plugins.iter().map(Plugin::name).collect();
+            let infos: Vec<_> = self.plugins.iter().map(Plugin::info).collect();
+            load_order(&names, &infos)
+        };
+
+        let mut plugins: Vec<_> = self.plugins.drain(..).map(Some).collect();
+        self.plugins = order
+            .into_iter()
+            .filter_map(|i| plugins[i].take())
+            .collect();
+    }
+
+    /// 加载单个插件, 返回插件句柄
+    ///
+    /// 不检查插件的依赖
+    pub fn load_plugin<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, LoadError> {
+        let handle = self.next_handle;
+        let plugin = Plugin::load(path.as_ref(), handle, &self.state)?;
+        self.next_handle += 1;
+
+        self.state
+            .names
+            .write()
+            .unwrap()
+            .insert(handle, plugin.name().to_owned());
+        self.plugins.push(plugin);
+        Ok(handle)
+    }
+
+    pub fn plugins(&self) -> std::slice::Iter<'_, Plugin> {
+        self.plugins.iter()
+    }
+
+    pub fn plugin(&self, handle: usize) -> Option<&Plugin> {
+        self.plugins.iter().find(|p| p.handle() == handle)
+    }
+
+    fn plugin_mut(&mut self, handle: usize) -> Option<&mut Plugin> {
+        self.plugins.iter_mut().find(|p| p.handle() == handle)
+    }
+
+    /// 启用插件, 若插件不存在或已启用则返回`false`
+    pub fn enable(&mut self, handle: usize) -> bool {
+        self.plugin_mut(handle).is_some_and(Plugin::enable)
+    }
+
+    /// 禁用插件, 若插件不存在或未启用则返回`false`
+    pub fn disable(&mut self, handle: usize) -> bool {
+        self.plugin_mut(handle).is_some_and(Plugin::disable)
+    }
+
+    pub fn enable_all(&mut self) {
+        for plugin in &mut self.plugins {
+            plugin.enable();
+        }
+    }
+
+    pub fn disable_all(&mut self) {
+        for plugin in self.plugins.iter_mut().rev() {
+            plugin.disable();
+        }
+    }
+
+    pub fn runtime(&self) -> &Handle {
+        &self.state.runtime
+    }
+
+    pub fn dispatcher(&self) -> Dispatcher {
+        Dispatcher(Arc::downgrade(&self.state))
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.state.listeners.len()
+    }
+}
+
+impl Drop for PluginManager {
+    fn drop(&mut self) {
+        // 插件的代码与数据均位于动态库内, 须在卸载动态库前释放
+        {
+            for plugin in self.plugins.iter_mut().rev() {
+                plugin.destroy();
+            }
+        }
+        self.state.listeners.clear();
+
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_timeout(Duration::from_secs(3));
+        }
+
+        for plugin in self.plugins.drain(..).rev() {
+            drop(plugin);
+        }
+        while let Some(lib) = self.dependencies.pop() {
+            drop(lib);
+        }
+    }
+}
+
+/// 向插件分发事件, 可在后端中克隆并跨线程使用
+#[derive(Clone)]
+pub struct Dispatcher(Weak<ManagerState>);
+
+impl Dispatcher {
+    /// 分发事件, 返回事件是否被拦截
+    ///
+    /// 处理器会依次执行, 后端应为每个事件单独派生协程进行分发;
+    /// 插件管理器被释放后不再分发事件
+    pub async fn dispatch(&self, event: HostEvent) -> bool {
+        let Some(state) = self.0.upgrade() else {
+            return false;
+        };
+        let listeners = state.listeners.clone();
+        drop(state);
+
+        let intercepted = Arc::new(AtomicBool::new(false));
+        let (t, base) = event.into_raw(&intercepted);
+
+        listeners.dispatch(t, &intercepted, base).await;
+        intercepted.load(Ordering::SeqCst)
+    }
+}
+
+fn library_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_lib = path.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION);
+
+        if is_lib {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::LoadError;
+    use crate::plugin::Plugin;
+    use crate::stub::{StubBackend, StubClient, StubGroup, StubMember};
+    use crate::PluginManager;
+    use atri_ffi::closure::FFIFn;
+    use atri_ffi::ffi::FFIEvent;
+    use atri_ffi::future::FFIFuture;
+    use atri_ffi::plugin::{AbiInfo, PluginInstance, PluginVTable};
+    use atri_ffi::{Handle, Managed, RustStr};
+    use std::cell::Cell;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    const NEW: usize = 0;
+    const ENABLE: usize = 1;
+    const DISABLE: usize = 2;
+    const DROP: usize = 3;
+
+    thread_local! {
+        // 插件的生命周期函数在调用者的线程中执行, 各测试的计数互不影响
+        static COUNTERS: Cell<[usize; 4]> = const { Cell::new([0; 4]) };
+    }
+
+    fn count(i: usize) {
+        COUNTERS.with(|c| {
+            let mut counters = c.get();
+            counters[i] += 1;
+            c.set(counters);
+        });
+    }
+
+    fn counters() -> [usize; 4] {
+        COUNTERS.with(Cell::get)
+    }
+
+    fn instance(should_drop: bool) -> PluginInstance {
+        extern "C" fn new() -> *mut () {
+            count(NEW);
+            Box::into_raw(Box::new(0u8)).cast()
+        }
+
+        extern "C" fn enable(_: *mut ()) {
+            count(ENABLE);
+        }
+
+        extern "C" fn disable(_: *mut ()) {
+            count(DISABLE);
+        }
+
+        extern "C" fn drop(ptr: *mut ()) {
+            count(DROP);
+            std::mem::drop(unsafe { Box::from_raw(ptr.cast::<u8>()) });
+        }
+
+        PluginInstance {
+            should_drop,
+            vtb: PluginVTable {
+                new,
+                enable,
+                disable,
+                drop,
+            },
+            abi_ver: atri_ffi::plugin::abi_version(),
+            name: RustStr::from("test"),
+        }
+    }
+
+    /// 次版本号较新, 需要宿主未提供的函数
+    fn abi() -> AbiInfo {
+        let mut abi = AbiInfo::new("0.0.0", &[0, 1, 411, 65535]);
+        abi.version.minor += 1;
+        abi
+    }
+
+    #[test]
+    fn lifecycle() {
+        let path = Path::new("test");
+
+        let mut incompatible = abi();
+        incompatible.version.major += 1;
+        let err = Plugin::from_instance(path, 0, &incompatible, || unreachable!(), None);
+        assert!(
+            matches!(err, Err(LoadError::AbiMismatch { error, .. }) if error.plugin == incompatible.version)
+        );
+
+        let mut legacy = instance(true);
+        legacy.abi_ver = 2;
+        let err = Plugin::legacy_mismatch(path, legacy);
+        assert!(
+            matches!(err, LoadError::AbiMismatch { error, .. } if error.plugin.major == 2 && error.missing.is_empty())
+        );
+
+        let mut plugin = Plugin::from_instance(path, 0, &abi(), || instance(true), None)
+            .unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(plugin.name(), "test");
+        assert_eq!(plugin.crate_version(), "0.0.0");
+        assert_eq!(plugin.missing_signatures(), [65535]);
+        assert!(plugin.enable());
+        assert!(!plugin.enable());
+        assert!(plugin.disable());
+        assert!(!plugin.disable());
+        assert!(plugin.enable());
+        drop(plugin);
+        assert_eq!(counters(), [2, 2, 2, 2]);
+
+        let mut plugin = Plugin::from_instance(path, 1, &abi(), || instance(false), None)
+            .unwrap_or_else(|e| panic!("{}", e));
+        plugin.enable();
+        plugin.disable();
+        plugin.enable();
+        plugin.disable();
+        assert_eq!(counters(), [3, 4, 4, 2]);
+        drop(plugin);
+        assert_eq!(counters(), [3, 4, 4, 3]);
+    }
+
+    #[test]
+    fn dispatch() {
+        type NewListener =
+            extern "C" fn(*const (), bool, FFIFn<FFIEvent, FFIFuture<bool>>, u8) -> Managed;
+        type Intercept = extern "C" fn(*const ());
+
+        let get = |sig| crate::vtable::get_fun(sig);
+        let new_listener: NewListener = unsafe { std::mem::transmute(get(102)) };
+        let intercept: Intercept = unsafe { std::mem::transmute(get(200)) };
+
+        let backend = StubBackend::new().client(
+            StubClient::new(1, "bot")
+                .group(StubGroup::new(100, "test").member(StubMember::new(2, "user"))),
+        );
+        let manager = PluginManager::new(backend.clone()).unwrap();
+        let dispatcher = manager.dispatcher();
+        let manager_ptr = Arc::as_ptr(&manager.state).cast::<()>();
+
+        let order = Arc::new(Mutex::new(vec![]));
+        let listener = |name: &'static str, priority: u8, keep: bool, stop: bool| {
+            let order = order.clone();
+            new_listener(
+                manager_ptr,
+                true,
+                FFIFn::from_static(move |e: FFIEvent| {
+                    order.lock().unwrap().push(name);
+                    let (_, intercepted, _) = e.get();
+                    if stop {
+                        intercept(intercepted);
+                    }
+                    FFIFuture::from_static(std::future::ready(keep))
+                }),
+                priority,
+            )
+        };
+
+        let _low = listener("low", 3, true, false);
+        let _once = listener("once", 2, false, false);
+        let _top = listener("top", 0, true, false);
+        assert_eq!(manager.listener_count(), 3);
+
+        let event = || backend.group_message(1, 100, 2, "hello").unwrap();
+        let intercepted = manager.runtime().block_on(dispatcher.dispatch(event()));
+        assert!(!intercepted);
+        assert_eq!(*order.lock().unwrap(), ["top", "once", "low"]);
+        assert_eq!(manager.listener_count(), 2);
+
+        order.lock().unwrap().clear();
+        let _stop = listener("stop", 1, true, true);
+        let intercepted = manager.runtime().block_on(dispatcher.dispatch(event()));
+        assert!(intercepted);
+        assert_eq!(*order.lock().unwrap(), ["top", "stop"]);
+
+        drop(_stop);
+        assert_eq!(manager.listener_count(), 2);
+
+        // 插件管理器释放后不再分发事件
+        let event = event();
+        drop(manager);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert!(!runtime.block_on(dispatcher.dispatch(event)));
+    }
+
+    #[test]
+    fn independent_managers() {
+        type FindClient = extern "C" fn(*const (), i64) -> Handle;
+        type NewListener =
+            extern "C" fn(*const (), bool, FFIFn<FFIEvent, FFIFuture<bool>>, u8) -> Managed;
+
+        let find_client: FindClient = unsafe { std::mem::transmute(crate::vtable::get_fun(310)) };
+        let new_listener: NewListener = unsafe { std::mem::transmute(crate::vtable::get_fun(102)) };
+
+        let first = PluginManager::new(StubBackend::new().client(StubClient::new(1, "a"))).unwrap();
+        let second =
+            PluginManager::new(StubBackend::new().client(StubClient::new(2, "b"))).unwrap();
+        let ptr = |manager: &PluginManager| Arc::as_ptr(&manager.state).cast::<()>();
+
+        assert!(!find_client(ptr(&first), 1).is_null());
+        assert!(find_client(ptr(&first), 2).is_null());
+        assert!(!find_client(ptr(&second), 2).is_null());
+
+        let handler =
+            || FFIFn::from_static(|_: FFIEvent| FFIFuture::from_static(std::future::ready(true)));
+        let _guard = new_listener(ptr(&second), false, handler(), 0);
+        assert_eq!(first.listener_count(), 0);
+        assert_eq!(second.listener_count(), 1);
+    }
+}
@@ -0,0 +1,554 @@
+use crate::message::forward::{ForwardNode, ForwardNodeInfo};
+use crate::message::meta::{Anonymous, MessageMetadata, MessageReceipt, Reply};
+use crate::message::{
+    At, Face, File, Image, MarketFace, MessageChain, MessageElement, Poke, RichCard, Video, Voice,
+};
+use atri_ffi::ffi::ForFFI;
+use atri_ffi::message::forward::{FFIForwardNode, FFIForwardNodeInfo, ForwardNodeUnion};
+use atri_ffi::message::meta::{
+    FFIAnonymous, FFIMessageMetadata, FFIReply, ANONYMOUS_FLAG, NONE_META, REPLY_FLAG,
+};
+use atri_ffi::message::{
+    FFIAt, FFIFace, FFIMarketFace, FFIMessageChain, FFIMessageElement, FFIMessageReceipt, FFIPoke,
+    FFIRichCard, MessageElementFlag, MessageElementUnion,
+};
+use atri_ffi::{ManagedCloneable, RustString, RustVec};
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+impl ForFFI for MessageChain {
+    type FFIValue = FFIMessageChain;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        FFIMessageChain {
+            meta: self.meta.into_ffi(),
+            inner: elements_into_ffi(self.elements),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        Self {
+            meta: MessageMetadata::from_ffi(ffi.meta),
+            elements: elements_from_ffi(ffi.inner),
+        }
+    }
+}
+
+fn elements_into_ffi(elements: Vec<MessageElement>) -> RustVec<FFIMessageElement> {
+    elements
+        .into_iter()
+        .map(MessageElement::into_ffi)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn elements_from_ffi(ffi: RustVec<FFIMessageElement>) -> Vec<MessageElement> {
+    ffi.into_vec()
+        .into_iter()
+        .map(MessageElement::from_ffi)
+        .collect()
+}
+
+impl ForFFI for MessageElement {
+    type FFIValue = FFIMessageElement;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        match self {
+            Self::Text(s) => FFIMessageElement {
+                t: MessageElementFlag::Text.value(),
+                union: MessageElementUnion {
+                    text: ManuallyDrop::new(RustString::from(s)),
+                },
+            },
+            Self::Image(img) => FFIMessageElement {
+                t: MessageElementFlag::Image.value(),
+                union: MessageElementUnion {
+                    image: ManuallyDrop::new(ManagedCloneable::from_value(img)),
+                },
+            },
+            Self::At(At { target, display }) => FFIMessageElement {
+                t: MessageElementFlag::At.value(),
+                union: MessageElementUnion {
+                    at: ManuallyDrop::new(FFIAt {
+                        target,
+                        display: RustString::from(display),
+                    }),
+                },
+            },
+            Self::AtAll => FFIMessageElement {
+                t: MessageElementFlag::AtAll.value(),
+                union: MessageElementUnion { at_all: () },
+            },
+            Self::Face(Face { index, name }) => FFIMessageElement {
+                t: MessageElementFlag::Face.value(),
+                union: MessageElementUnion {
+                    face: ManuallyDrop::new(FFIFace {
+                        index,
+                        name: RustString::from(name),
+                    }),
+                },
+            },
+            Self::Voice(voice) => FFIMessageElement {
+                t: MessageElementFlag::Voice.value(),
+                union: MessageElementUnion {
+                    voice: ManuallyDrop::new(ManagedCloneable::from_value(voice)),
+                },
+            },
+            Self::Video(video) => FFIMessageElement {
+                t: MessageElementFlag::Video.value(),
+                union: MessageElementUnion {
+                    video: ManuallyDrop::new(ManagedCloneable::from_value(video)),
+                },
+            },
+            Self::File(file) => FFIMessageElement {
+                t: MessageElementFlag::File.value(),
+                union: MessageElementUnion {
+                    file: ManuallyDrop::new(ManagedCloneable::from_value(file)),
+                },
+            },
+            Self::RichCard(card) => FFIMessageElement {
+                t: MessageElementFlag::RichCard.value(),
+                union: MessageElementUnion {
+                    rich_card: ManuallyDrop::new(card.into_ffi()),
+                },
+            },
+            Self::Poke(poke) => FFIMessageElement {
+                t: MessageElementFlag::Poke.value(),
+                union: MessageElementUnion {
+                    poke: ManuallyDrop::new(poke.into_ffi()),
+                },
+            },
+            Self::Dice(value) => FFIMessageElement {
+                t: MessageElementFlag::Dice.value(),
+                union: MessageElementUnion { dice: value },
+            },
+            Self::MarketFace(face) => FFIMessageElement {
+                t: MessageElementFlag::MarketFace.value(),
+                union: MessageElementUnion {
+                    market_face: Box::into_raw(Box::new(face.into_ffi())),
+                },
+            },
+            Self::FlashImage(img) => FFIMessageElement {
+                t: MessageElementFlag::FlashImage.value(),
+                union: MessageElementUnion {
+                    flash_image: ManuallyDrop::new(ManagedCloneable::from_value(img)),
+                },
+            },
+            Self::LightApp(content) => FFIMessageElement {
+                t: MessageElementFlag::LightApp.value(),
+                union: MessageElementUnion {
+                    light_app: ManuallyDrop::new(RustString::from(content)),
+                },
+            },
+            Self::Unknown(ma) => FFIMessageElement {
+                t: MessageElementFlag::Unknown.value(),
+                union: MessageElementUnion {
+                    unknown: ManuallyDrop::new(ma),
+                },
+            },
+        }
+    }
+
+    fn from_ffi(value: Self::FFIValue) -> Self {
+        unsafe {
+            let Ok(flag) = MessageElementFlag::try_from(value.t) else {
+                // 来自更新版本插件的元素, 无法得知其布局, 只能放弃其内容
+                return Self::Unknown(ManagedCloneable::null());
+            };
+
+            match flag {
+                MessageElementFlag::Text => {
+                    Self::Text(ManuallyDrop::into_inner(value.union.text).into())
+                }
+                // 插件持有的图片均由宿主构造
+                MessageElementFlag::Image => {
+                    Self::Image(ManuallyDrop::into_inner(value.union.image).into_value::<Image>())
+                }
+                MessageElementFlag::At => {
+                    let FFIAt { target, display } = ManuallyDrop::into_inner(value.union.at);
+                    Self::At(At {
+                        target,
+                        display: display.into(),
+                    })
+                }
+                MessageElementFlag::AtAll => Self::AtAll,
+                MessageElementFlag::Face => {
+                    let FFIFace { index, name } = ManuallyDrop::into_inner(value.union.face);
+                    Self::Face(Face {
+                        index,
+                        name: name.into(),
+                    })
+                }
+                MessageElementFlag::Voice => {
+                    Self::Voice(ManuallyDrop::into_inner(value.union.voice).into_value::<Voice>())
+                }
+                MessageElementFlag::Video => {
+                    Self::Video(ManuallyDrop::into_inner(value.union.video).into_value::<Video>())
+                }
+                MessageElementFlag::File => {
+                    Self::File(ManuallyDrop::into_inner(value.union.file).into_value::<File>())
+                }
+                MessageElementFlag::RichCard => Self::RichCard(RichCard::from_ffi(
+                    ManuallyDrop::into_inner(value.union.rich_card),
+                )),
+                MessageElementFlag::Poke => {
+                    Self::Poke(Poke::from_ffi(ManuallyDrop::into_inner(value.union.poke)))
+                }
+                MessageElementFlag::Dice => Self::Dice(value.union.dice),
+                MessageElementFlag::MarketFace => Self::MarketFace(MarketFace::from_ffi(
+                    *Box::from_raw(value.union.market_face),
+                )),
+                MessageElementFlag::FlashImage => Self::FlashImage(
+                    ManuallyDrop::into_inner(value.union.flash_image).into_value::<Image>(),
+                ),
+                MessageElementFlag::LightApp => {
+                    Self::LightApp(ManuallyDrop::into_inner(value.union.light_app).into())
+                }
+                MessageElementFlag::Unknown => {
+                    Self::Unknown(ManuallyDrop::into_inner(value.union.unknown))
+                }
+            }
+        }
+    }
+}
+
+impl ForFFI for MessageMetadata {
+    type FFIValue = FFIMessageMetadata;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let Self {
+            seqs,
+            rands,
+            time,
+            sender,
+            anonymous,
+            reply,
+        } = self;
+
+        let mut flags = NONE_META;
+
+        let mut ffi_anonymous = MaybeUninit::uninit();
+        if let Some(ano) = anonymous {
+            flags |= ANONYMOUS_FLAG;
+            ffi_anonymous.write(ano.into_ffi());
+        }
+
+        let mut ffi_reply = MaybeUninit::uninit();
+        if let Some(reply) = reply {
+            flags |= REPLY_FLAG;
+            ffi_reply.write(reply.into_ffi());
+        }
+
+        FFIMessageMetadata {
+            seqs: seqs.into(),
+            rands: rands.into(),
+            time,
+            sender,
+            flags,
+            anonymous: ffi_anonymous,
+            reply: ffi_reply,
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        let FFIMessageMetadata {
+            seqs,
+            rands,
+            time,
+            sender,
+            flags,
+            anonymous,
+            reply,
+        } = ffi;
+
+        unsafe {
+            Self {
+                seqs: seqs.into_vec(),
+                rands: rands.into_vec(),
+                time,
+                sender,
+                anonymous: if flags & ANONYMOUS_FLAG != 0 {
+                    Some(Anonymous::from_ffi(anonymous.assume_init()))
+                } else {
+                    None
+                },
+                reply: if flags & REPLY_FLAG != 0 {
+                    Some(Reply::from_ffi(reply.assume_init()))
+                } else {
+                    None
+                },
+            }
+        }
+    }
+}
+
+impl ForFFI for Reply {
+    type FFIValue = FFIReply;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let Self {
+            reply_seq,
+            sender,
+            time,
+            elements,
+        } = self;
+
+        FFIReply {
+            reply_seq,
+            sender,
+            time,
+            elements: elements_into_ffi(elements),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        let FFIReply {
+            reply_seq,
+            sender,
+            time,
+            elements,
+        } = ffi;
+
+        Self {
+            reply_seq,
+            sender,
+            time,
+            elements: elements_from_ffi(elements),
+        }
+    }
+}
+
+impl ForFFI for Anonymous {
+    type FFIValue = FFIAnonymous;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let Self {
+            anon_id,
+            nick,
+            portrait_index,
+            bubble_index,
+            expire_time,
+            color,
+        } = self;
+
+        FFIAnonymous {
+            anon_id: anon_id.into(),
+            nick: nick.into(),
+            portrait_index,
+            bubble_index,
+            expire_time,
+            color: color.into(),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        let FFIAnonymous {
+            anon_id,
+            nick,
+            portrait_index,
+            bubble_index,
+            expire_time,
+            color,
+        } = ffi;
+
+        Self {
+            anon_id: anon_id.into_vec(),
+            nick: nick.into(),
+            portrait_index,
+            bubble_index,
+            expire_time,
+            color: color.into(),
+        }
+    }
+}
+
+impl ForFFI for MessageReceipt {
+    type FFIValue = FFIMessageReceipt;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let MessageReceipt { seqs, rands, time } = self;
+
+        FFIMessageReceipt {
+            seqs: seqs.into(),
+            rands: rands.into(),
+            time,
+        }
+    }
+
+    fn from_ffi(FFIMessageReceipt { seqs, rands, time }: Self::FFIValue) -> Self {
+        Self {
+            seqs: seqs.into_vec(),
+            rands: rands.into_vec(),
+            time,
+        }
+    }
+}
+
+impl ForFFI for ForwardNode {
+    type FFIValue = FFIForwardNode;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        match self {
+            Self::NormalMessage { info, chain } => FFIForwardNode {
+                is_normal: true,
+                info: info.into_ffi(),
+                inner: ForwardNodeUnion {
+                    normal: ManuallyDrop::new(chain.into_ffi()),
+                },
+            },
+            Self::ForwardMessage { info, forward } => FFIForwardNode {
+                is_normal: false,
+                info: info.into_ffi(),
+                inner: ForwardNodeUnion {
+                    forward: ManuallyDrop::new(forward_into_ffi(forward)),
+                },
+            },
+        }
+    }
+
+    fn from_ffi(
+        FFIForwardNode {
+            is_normal,
+            info,
+            inner,
+        }: Self::FFIValue,
+    ) -> Self {
+        unsafe {
+            if is_normal {
+                Self::NormalMessage {
+                    info: ForwardNodeInfo::from_ffi(info),
+                    chain: MessageChain::from_ffi(ManuallyDrop::into_inner(inner.normal)),
+                }
+            } else {
+                Self::ForwardMessage {
+                    info: ForwardNodeInfo::from_ffi(info),
+                    forward: forward_from_ffi(ManuallyDrop::into_inner(inner.forward)),
+                }
+            }
+        }
+    }
+}
+
+impl ForFFI for ForwardNodeInfo {
+    type FFIValue = FFIForwardNodeInfo;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let ForwardNodeInfo {
+            sender_id,
+            sender_name,
+            time,
+        } = self;
+
+        FFIForwardNodeInfo {
+            sender_id,
+            sender_name: sender_name.into(),
+            time,
+        }
+    }
+
+    fn from_ffi(
+        FFIForwardNodeInfo {
+            sender_id,
+            sender_name,
+            time,
+        }: Self::FFIValue,
+    ) -> Self {
+        Self {
+            sender_id,
+            sender_name: sender_name.into(),
+            time,
+        }
+    }
+}
+
+fn forward_into_ffi(nodes: Vec<ForwardNode>) -> RustVec<FFIForwardNode> {
+    nodes
+        .into_iter()
+        .map(ForwardNode::into_ffi)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+pub(crate) fn forward_from_ffi(ffi: RustVec<FFIForwardNode>) -> Vec<ForwardNode> {
+    ffi.into_vec()
+        .into_iter()
+        .map(ForwardNode::from_ffi)
+        .collect()
+}
+
+impl ForFFI for RichCard {
+    type FFIValue = FFIRichCard;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        let (is_xml, service_id, content) = match self {
+            Self::Json(content) => (false, 0, content),
+            Self::Xml {
+                service_id,
+                content,
+            } => (true, service_id, content),
+        };
+
+        FFIRichCard {
+            is_xml,
+            service_id,
+            content: content.into(),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        let content = String::from(ffi.content);
+        if ffi.is_xml {
+            Self::Xml {
+                service_id: ffi.service_id,
+                content,
+            }
+        } else {
+            Self::Json(content)
+        }
+    }
+}
+
+impl ForFFI for Poke {
+    type FFIValue = FFIPoke;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        FFIPoke {
+            kind: self.kind,
+            name: self.name.into(),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        Self {
+            kind: ffi.kind,
+            name: ffi.name.into(),
+        }
+    }
+}
+
+impl ForFFI for MarketFace {
+    type FFIValue = FFIMarketFace;
+
+    fn into_ffi(self) -> Self::FFIValue {
+        FFIMarketFace {
+            name: self.name.into(),
+            face_id: self.face_id.into(),
+            tab_id: self.tab_id,
+            item_type: self.item_type,
+            sub_type: self.sub_type,
+            media_type: self.media_type,
+            encrypt_key: self.encrypt_key.into(),
+        }
+    }
+
+    fn from_ffi(ffi: Self::FFIValue) -> Self {
+        Self {
+            name: ffi.name.into(),
+            face_id: ffi.face_id.into_vec(),
+            tab_id: ffi.tab_id,
+            item_type: ffi.item_type,
+            sub_type: ffi.sub_type,
+            media_type: ffi.media_type,
+            encrypt_key: ffi.encrypt_key.into_vec(),
+        }
+    }
+}
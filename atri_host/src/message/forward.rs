@@ -0,0 +1,20 @@
+use crate::message::MessageChain;
+
+#[derive(Clone)]
+pub enum ForwardNode {
+    NormalMessage {
+        info: ForwardNodeInfo,
+        chain: MessageChain,
+    },
+    ForwardMessage {
+        info: ForwardNodeInfo,
+        forward: Vec<ForwardNode>,
+    },
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct ForwardNodeInfo {
+    pub sender_id: i64,
+    pub sender_name: String,
+    pub time: i32,
+}
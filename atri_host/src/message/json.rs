@@ -0,0 +1,168 @@
+use crate::error::{HostError, HostResult};
+use crate::message::{At, Face, MarketFace, MessageChain, MessageElement, Poke, RichCard};
+use serde_json::{json, Value};
+
+impl MessageElement {
+    /// 序列化为json对象, 无法识别的元素为`None`
+    pub fn to_json(&self) -> Option<String> {
+        self.to_json_value().map(|value| value.to_string())
+    }
+
+    fn to_json_value(&self) -> Option<Value> {
+        Some(match self {
+            MessageElement::Text(text) => json!({ "type": "Text", "content": text }),
+            MessageElement::Image(img) => {
+                json!({ "type": "Image", "id": img.id(), "url": img.url() })
+            }
+            MessageElement::At(At { target, display }) => {
+                json!({ "type": "At", "target": target, "display": display })
+            }
+            MessageElement::AtAll => json!({ "type": "AtAll" }),
+            MessageElement::Face(Face { index, name }) => {
+                json!({ "type": "Face", "index": index, "name": name })
+            }
+            MessageElement::Voice(voice) => json!({
+                "type": "Voice",
+                "url": voice.url(),
+                "md5": hex(voice.md5()),
+                "size": voice.size(),
+                "duration": voice.duration().as_secs(),
+            }),
+            MessageElement::Video(video) => json!({
+                "type": "Video",
+                "name": video.name(),
+                "size": video.size(),
+                "url": video.url(),
+            }),
+            MessageElement::File(file) => json!({
+                "type": "File",
+                "name": file.name(),
+                "size": file.size(),
+                "url": file.url(),
+            }),
+            MessageElement::RichCard(RichCard::Json(content)) => {
+                json!({ "type": "Json", "content": content })
+            }
+            MessageElement::RichCard(RichCard::Xml {
+                service_id,
+                content,
+            }) => json!({ "type": "Xml", "service_id": service_id, "content": content }),
+            MessageElement::Poke(Poke { kind, name }) => {
+                json!({ "type": "Poke", "kind": kind, "name": name })
+            }
+            MessageElement::Dice(value) => json!({ "type": "Dice", "value": value }),
+            MessageElement::MarketFace(face) => json!({
+                "type": "MarketFace",
+                "name": face.name,
+                "face_id": hex(&face.face_id),
+                "tab_id": face.tab_id,
+                "item_type": face.item_type,
+                "sub_type": face.sub_type,
+                "media_type": face.media_type,
+                "encrypt_key": hex(&face.encrypt_key),
+            }),
+            MessageElement::FlashImage(img) => {
+                json!({ "type": "FlashImage", "id": img.id(), "url": img.url() })
+            }
+            MessageElement::LightApp(content) => {
+                json!({ "type": "LightApp", "content": content })
+            }
+            MessageElement::Unknown(_) => return None,
+        })
+    }
+}
+
+impl MessageChain {
+    /// 序列化为json数组, 无法识别的元素将被忽略
+    pub fn to_json(&self) -> String {
+        let elements: Vec<Value> = self
+            .iter()
+            .filter_map(MessageElement::to_json_value)
+            .collect();
+
+        Value::Array(elements).to_string()
+    }
+
+    /// 从json数组反序列化, 图片, 语音等需由后端上传获得, 故不支持反序列化
+    pub fn from_json(json: &str) -> HostResult<Self> {
+        let err = |msg: String| HostError::ClientError(msg);
+
+        let value: Value = serde_json::from_str(json).map_err(|e| err(e.to_string()))?;
+        let Value::Array(values) = value else {
+            return Err(err(format!("Expected json array, found {}", value)));
+        };
+
+        let mut elements = Vec::with_capacity(values.len());
+        for value in values {
+            let str_field = |name: &str| -> HostResult<String> {
+                value[name]
+                    .as_str()
+                    .map(String::from)
+                    .ok_or_else(|| err(format!("Missing field `{}` in {}", name, value)))
+            };
+            let int_field = |name: &str| -> HostResult<i64> {
+                value[name]
+                    .as_i64()
+                    .ok_or_else(|| err(format!("Missing field `{}` in {}", name, value)))
+            };
+
+            let elem = match value["type"].as_str() {
+                Some("Text") => MessageElement::Text(str_field("content")?),
+                Some("At") => MessageElement::At(At {
+                    target: int_field("target")?,
+                    display: str_field("display")?,
+                }),
+                Some("AtAll") => MessageElement::AtAll,
+                Some("Face") => MessageElement::Face(Face {
+                    index: int_field("index")? as i32,
+                    name: str_field("name")?,
+                }),
+                Some("Json") => MessageElement::RichCard(RichCard::Json(str_field("content")?)),
+                Some("Xml") => MessageElement::RichCard(RichCard::Xml {
+                    service_id: int_field("service_id")? as i32,
+                    content: str_field("content")?,
+                }),
+                Some("Poke") => MessageElement::Poke(Poke {
+                    kind: int_field("kind")? as i32,
+                    name: str_field("name")?,
+                }),
+                Some("Dice") => MessageElement::Dice(int_field("value")? as i32),
+                Some("MarketFace") => {
+                    let bytes_field = |name: &str| {
+                        let s = str_field(name)?;
+                        unhex(&s).ok_or_else(|| err(format!("Invalid hex `{}` in {}", s, value)))
+                    };
+                    MessageElement::MarketFace(MarketFace {
+                        name: str_field("name")?,
+                        face_id: bytes_field("face_id")?,
+                        tab_id: int_field("tab_id")? as u32,
+                        item_type: int_field("item_type")? as u32,
+                        sub_type: int_field("sub_type")? as u32,
+                        media_type: int_field("media_type")? as u32,
+                        encrypt_key: bytes_field("encrypt_key")?,
+                    })
+                }
+                Some("LightApp") => MessageElement::LightApp(str_field("content")?),
+                _ => return Err(err(format!("Unsupported message element: {}", value))),
+            };
+            elements.push(elem);
+        }
+
+        Ok(Self::new(elements))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,312 @@
+pub(crate) mod ffi;
+pub mod forward;
+mod json;
+pub mod meta;
+
+use crate::backend::{BotFile, BotImage, BotVideo, BotVoice};
+use crate::message::meta::MessageMetadata;
+use atri_ffi::ManagedCloneable;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// 由后端提供的图片
+pub type Image = Arc<dyn BotImage>;
+
+/// 由后端提供的语音
+pub type Voice = Arc<dyn BotVoice>;
+
+/// 由后端提供的视频
+pub type Video = Arc<dyn BotVideo>;
+
+/// 由后端提供的文件, 如离线文件或群文件
+pub type File = Arc<dyn BotFile>;
+
+/// 宿主侧的消息链, 与插件侧的消息链通过FFI互相转换
+#[derive(Default, Clone)]
+pub struct MessageChain {
+    pub meta: MessageMetadata,
+    pub elements: Vec<MessageElement>,
+}
+
+impl MessageChain {
+    pub fn new(elements: Vec<MessageElement>) -> Self {
+        Self {
+            meta: MessageMetadata::default(),
+            elements,
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, MessageElement> {
+        self.elements.iter()
+    }
+}
+
+impl From<String> for MessageChain {
+    fn from(s: String) -> Self {
+        Self::new(vec![MessageElement::Text(s)])
+    }
+}
+
+impl From<&str> for MessageChain {
+    fn from(s: &str) -> Self {
+        Self::from(s.to_owned())
+    }
+}
+
+impl Display for MessageChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for value in self.iter() {
+            Display::fmt(value, f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub enum MessageElement {
+    Text(String),
+    Image(Image),
+    At(At),
+    AtAll,
+    Face(Face),
+    Voice(Voice),
+    Video(Video),
+    File(File),
+    RichCard(RichCard),
+    Poke(Poke),
+    /// 骰子, 点数为1到6
+    Dice(i32),
+    MarketFace(MarketFace),
+    FlashImage(Image),
+    /// 小程序, 内容为json
+    LightApp(String),
+    /// 宿主无法识别的元素, 原样传递
+    Unknown(ManagedCloneable),
+}
+
+impl Display for MessageElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(text),
+            Self::Image(img) => write!(f, "$[Image:{}]", img.url()),
+            Self::At(At { target, display }) => write!(f, "$[At:{}({})]", display, target),
+            Self::AtAll => f.write_str("$[AtAll]"),
+            Self::Face(face) => write!(f, "$[Face:{}]", face.name),
+            Self::Voice(voice) => write!(f, "$[Voice:{}]", voice.url()),
+            Self::Video(video) => write!(f, "$[Video:{}]", video.name()),
+            Self::File(file) => write!(f, "$[File:{}]", file.name()),
+            Self::RichCard(RichCard::Json(json)) => write!(f, "$[Json:{}]", json),
+            Self::RichCard(RichCard::Xml { content, .. }) => write!(f, "$[Xml:{}]", content),
+            Self::Poke(Poke { kind, name }) => write!(f, "$[Poke:{}({})]", name, kind),
+            Self::Dice(value) => write!(f, "$[Dice:{}]", value),
+            Self::MarketFace(face) => write!(f, "$[MarketFace:{}]", face.name),
+            Self::FlashImage(img) => write!(f, "$[FlashImage:{}]", img.url()),
+            Self::LightApp(content) => write!(f, "$[LightApp:{}]", content),
+            Self::Unknown(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct At {
+    pub target: i64,
+    pub display: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Face {
+    pub index: i32,
+    pub name: String,
+}
+
+/// json或xml卡片
+#[derive(Clone, Debug)]
+pub enum RichCard {
+    Json(String),
+    Xml { service_id: i32, content: String },
+}
+
+/// 戳一戳
+#[derive(Clone, Debug, Default)]
+pub struct Poke {
+    pub kind: i32,
+    pub name: String,
+}
+
+/// 商城表情
+#[derive(Clone, Debug, Default)]
+pub struct MarketFace {
+    pub name: String,
+    pub face_id: Vec<u8>,
+    pub tab_id: u32,
+    pub item_type: u32,
+    pub sub_type: u32,
+    pub media_type: u32,
+    pub encrypt_key: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::{BotFile, BotImage, BotVoice};
+    use crate::message::meta::Reply;
+    use crate::message::{At, Face, MarketFace, MessageChain, MessageElement, Poke, RichCard};
+    use atri_ffi::ffi::ForFFI;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct TestImage;
+
+    impl BotImage for TestImage {
+        fn id(&self) -> &str {
+            "{114514}.png"
+        }
+
+        fn url(&self) -> String {
+            String::from("https://atri/114514.png")
+        }
+    }
+
+    struct TestVoice;
+
+    impl BotVoice for TestVoice {
+        fn url(&self) -> String {
+            String::from("https://atri/114514.amr")
+        }
+
+        fn md5(&self) -> &[u8] {
+            &[0x11, 0x45, 0x14]
+        }
+
+        fn size(&self) -> u32 {
+            1919
+        }
+
+        fn duration(&self) -> Duration {
+            Duration::from_secs(8)
+        }
+    }
+
+    struct TestFile;
+
+    impl BotFile for TestFile {
+        fn name(&self) -> &str {
+            "atri.zip"
+        }
+
+        fn size(&self) -> u64 {
+            810
+        }
+
+        fn url(&self) -> String {
+            String::from("https://atri/atri.zip")
+        }
+    }
+
+    fn chain() -> MessageChain {
+        let mut chain = MessageChain::new(vec![
+            MessageElement::Text(String::from("hello ")),
+            MessageElement::At(At {
+                target: 10,
+                display: String::from("@user"),
+            }),
+            MessageElement::AtAll,
+            MessageElement::Face(Face {
+                index: 1,
+                name: String::from("smile"),
+            }),
+            MessageElement::RichCard(RichCard::Xml {
+                service_id: 1,
+                content: String::from("<msg/>"),
+            }),
+            MessageElement::Poke(Poke {
+                kind: 1,
+                name: String::from("戳一戳"),
+            }),
+            MessageElement::Dice(6),
+            MessageElement::MarketFace(MarketFace {
+                name: String::from("[贴贴]"),
+                face_id: vec![0xab, 0x01],
+                encrypt_key: vec![0xff],
+                ..MarketFace::default()
+            }),
+            MessageElement::LightApp(String::from("{}")),
+        ]);
+        chain.meta.seqs = vec![1, 2];
+        chain.meta.sender = 10;
+        chain.meta.reply = Some(Reply {
+            reply_seq: 0,
+            sender: 11,
+            time: 0,
+            elements: vec![MessageElement::Text(String::from("quoted"))],
+        });
+        chain
+    }
+
+    #[test]
+    fn ffi() {
+        let mut chain = chain();
+        chain
+            .elements
+            .push(MessageElement::Image(Arc::new(TestImage)));
+        chain
+            .elements
+            .push(MessageElement::File(Arc::new(TestFile)));
+        chain
+            .elements
+            .push(MessageElement::Voice(Arc::new(TestVoice)));
+
+        let back = MessageChain::from_ffi(chain.clone().into_ffi());
+        assert_eq!(back.to_string(), chain.to_string());
+        assert_eq!(back.meta.seqs, [1, 2]);
+        assert_eq!(back.meta.sender, 10);
+        assert_eq!(back.meta.reply.as_ref().map(|r| r.sender), Some(11));
+        assert!(matches!(
+            &back.elements[back.elements.len() - 3],
+            MessageElement::Image(img) if img.id() == "{114514}.png"
+        ));
+        assert!(matches!(
+            back.elements.last(),
+            Some(MessageElement::Voice(voice)) if voice.size() == 1919
+        ));
+        assert!(back
+            .to_string()
+            .ends_with("$[Voice:https://atri/114514.amr]"));
+
+        let json = back.to_json();
+        assert!(json.contains(r#""md5":"114514""#), "{}", json);
+    }
+
+    #[test]
+    fn unknown_flag() {
+        use atri_ffi::message::{FFIMessageElement, MessageElementUnion};
+
+        let elem = FFIMessageElement {
+            t: 200,
+            union: MessageElementUnion { at_all: () },
+        };
+        let elem = MessageElement::from_ffi(elem);
+        assert!(matches!(elem, MessageElement::Unknown(_)));
+        assert_eq!(elem.to_string(), "");
+        assert_eq!(elem.to_json(), None);
+    }
+
+    #[test]
+    fn json() {
+        let chain = chain();
+        let json = chain.to_json();
+        let back = MessageChain::from_json(&json).unwrap();
+        assert_eq!(back.to_string(), chain.to_string());
+        assert_eq!(
+            MessageElement::AtAll.to_json().as_deref(),
+            Some(r#"{"type":"AtAll"}"#)
+        );
+
+        assert!(matches!(
+            &back.elements[7],
+            MessageElement::MarketFace(face) if face.face_id == [0xab, 0x01] && face.encrypt_key == [0xff]
+        ));
+
+        assert!(MessageChain::from_json("{}").is_err());
+        assert!(MessageChain::from_json(r#"[{"type":"Image","id":"a"}]"#).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+/// 插件通过`#[plugin]`声明的元数据
+#[derive(Debug, Clone, Default)]
+pub struct PluginInfo {
+    pub version: String,
+    pub authors: Vec<String>,
+    pub description: String,
+    pub homepage: String,
+    pub depends: Vec<Dependency>,
+}
+
+impl PluginInfo {
+    pub(crate) fn from_ffi(ffi: atri_ffi::plugin::PluginInfo) -> Result<Self, String> {
+        let strings = |v: atri_ffi::RustVec<atri_ffi::RustStr>| {
+            v.into_vec()
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        Ok(Self {
+            version: ffi.version.to_string(),
+            authors: strings(ffi.authors),
+            description: ffi.description.to_string(),
+            homepage: ffi.homepage.to_string(),
+            depends: strings(ffi.depends)
+                .iter()
+                .map(|s| s.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// 插件依赖, 格式为`插件名 版本要求`
+///
+/// 版本要求可由逗号分隔多个条件, 支持`=`, `>`, `>=`, `<`, `<=`与`^`,
+/// 省略运算符时视为`^`, 省略版本要求时接受任意版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    requirements: Vec<(Op, Vec<u64>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Caret,
+}
+
+impl Dependency {
+    /// 版本号是否满足要求, 无法解析的版本号只满足无要求的依赖
+    pub fn matches(&self, version: &str) -> bool {
+        if self.requirements.is_empty() {
+            return true;
+        }
+
+        let Some(version) = parse_version(version) else {
+            return false;
+        };
+
+        self.requirements.iter().all(|(op, req)| {
+            let ord = compare(&version, req);
+            match op {
+                Op::Eq => ord == Ordering::Equal,
+                Op::Gt => ord == Ordering::Greater,
+                Op::Ge => ord != Ordering::Less,
+                Op::Lt => ord == Ordering::Less,
+                Op::Le => ord != Ordering::Greater,
+                Op::Caret => {
+                    // 与cargo一致, 第一个非零的部分须相同
+                    let n = req.iter().position(|&x| x != 0).unwrap_or(req.len() - 1);
+                    ord != Ordering::Less
+                        && (0..=n).all(|i| version.get(i).copied().unwrap_or(0) == req[i])
+                }
+            }
+        })
+    }
+}
+
+impl std::str::FromStr for Dependency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, req) = s
+            .split_once(|c: char| c.is_whitespace() || "=<>^".contains(c))
+            .map(|(name, _)| (name, &s[name.len()..]))
+            .unwrap_or((s, ""));
+
+        if name.is_empty() {
+            return Err(format!("Invalid dependency: {}", s));
+        }
+
+        let mut requirements = vec![];
+        for cond in req.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            let (op, version) = [
+                (">=", Op::Ge),
+                ("<=", Op::Le),
+                ("==", Op::Eq),
+                ("=", Op::Eq),
+                (">", Op::Gt),
+                ("<", Op::Lt),
+                ("^", Op::Caret),
+            ]
+            .into_iter()
+            .find_map(|(prefix, op)| cond.strip_prefix(prefix).map(|v| (op, v)))
+            .unwrap_or((Op::Caret, cond));
+
+            let version = parse_version(version.trim())
+                .ok_or_else(|| format!("Invalid version requirement: {}", s))?;
+            requirements.push((op, version));
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            requirements,
+        })
+    }
+}
+
+impl Display for Dependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)?;
+
+        for (i, (op, version)) in self.requirements.iter().enumerate() {
+            let op = match op {
+                Op::Eq => "=",
+                Op::Gt => ">",
+                Op::Ge => ">=",
+                Op::Lt => "<",
+                Op::Le => "<=",
+                Op::Caret => "^",
+            };
+            let version = version
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(".");
+
+            let sep = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}{}", sep, op, version)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 仅比较数字部分, 忽略预发布与构建信息
+fn parse_version(s: &str) -> Option<Vec<u64>> {
+    let s = s.split(['-', '+']).next().unwrap_or(s);
+    s.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ord| ord.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// 按依赖关系排序, 返回排序后的下标; 存在循环依赖时其余插件保持原有顺序
+pub(crate) fn load_order(names: &[&str], infos: &[&PluginInfo]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(names.len());
+    let mut placed = vec![false; names.len()];
+
+    while order.len() < names.len() {
+        let ready = (0..names.len()).find(|&i| {
+            !placed[i]
+                && infos[i].depends.iter().all(|dep| {
+                    names
+                        .iter()
+                        .enumerate()
+                        .all(|(j, name)| *name != dep.name || placed[j] || j == i)
+                })
+        });
+
+        let next = ready.unwrap_or_else(|| placed.iter().position(|p| !p).unwrap());
+        placed[next] = true;
+        order.push(next);
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::{load_order, Dependency, PluginInfo};
+
+    #[test]
+    fn dependency() {
+        let dep: Dependency = "other_plugin >= 1.2".parse().unwrap();
+        assert_eq!(dep.name, "other_plugin");
+        assert!(dep.matches("1.2.0"));
+        assert!(dep.matches("2.0"));
+        assert!(!dep.matches("1.1.9"));
+
+        let dep: Dependency = "other_plugin>=1.2, <2".parse().unwrap();
+        assert!(dep.matches("1.9.9"));
+        assert!(!dep.matches("2.0.0"));
+        assert_eq!(dep.to_string(), "other_plugin >=1.2, <2");
+
+        let dep: Dependency = "other_plugin 0.3".parse().unwrap();
+        assert!(dep.matches("0.3.5"));
+        assert!(!dep.matches("0.4.0"));
+
+        let dep: Dependency = "other_plugin".parse().unwrap();
+        assert!(dep.matches("anything"));
+
+        assert!("other_plugin >= x".parse::<Dependency>().is_err());
+    }
+
+    #[test]
+    fn order() {
+        let info = |depends: &[&str]| PluginInfo {
+            depends: depends.iter().map(|d| d.parse().unwrap()).collect(),
+            ..Default::default()
+        };
+
+        let infos = [info(&["b", "c >= 1"]), info(&["c"]), info(&[]), info(&[])];
+        let names = ["a", "b", "c", "d"];
+        assert_eq!(load_order(&names, &infos.each_ref()), [2, 1, 0, 3]);
+
+        let infos = [info(&["b"]), info(&["a"]), info(&[])];
+        let names = ["a", "b", "c"];
+        assert_eq!(load_order(&names, &infos.each_ref()), [2, 0, 1]);
+    }
+}
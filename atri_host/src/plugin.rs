@@ -0,0 +1,251 @@
+use crate::backend::LogLevel;
+use crate::error::LoadError;
+use crate::manager::ManagerState;
+use crate::metadata::PluginInfo;
+use atri_ffi::ffi::AtriManager;
+use atri_ffi::plugin::{
+    check_compatibility, AbiInfo, AbiMismatch, AbiVersion, PluginInfo as FFIPluginInfo,
+    PluginInstance, PluginVTable,
+};
+use libloading::Library;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 已加载的插件
+///
+/// 插件实例在首次启用时构造, 若`should_drop`为`true`则在禁用后销毁
+pub struct Plugin {
+    handle: usize,
+    name: String,
+    path: PathBuf,
+    should_drop: bool,
+    vtb: PluginVTable,
+    abi: AbiVersion,
+    crate_version: String,
+    missing: Vec<u16>,
+    info: PluginInfo,
+    instance: Option<*mut ()>,
+    enabled: bool,
+    // 须最后释放
+    _lib: Option<Library>,
+}
+
+impl Plugin {
+    pub(crate) fn load(
+        path: &Path,
+        handle: usize,
+        state: &Arc<ManagerState>,
+    ) -> Result<Self, LoadError> {
+        let lib_err = |error| LoadError::Library {
+            path: path.to_owned(),
+            error,
+        };
+
+        let lib = unsafe { Library::new(path) }.map_err(lib_err)?;
+
+        let (abi, on_init, info, init) = unsafe {
+            let on_init = *lib
+                .get::<extern "C" fn() -> PluginInstance>(b"on_init")
+                .map_err(lib_err)?;
+
+            // 早于ABI握手的插件未导出ABI信息, 只能通过`on_init`返回的主版本号判断
+            let Ok(abi) = lib.get::<extern "C" fn() -> *const AbiInfo>(b"atri_abi_info") else {
+                return Err(Self::legacy_mismatch(path, on_init()));
+            };
+            let init = *lib
+                .get::<unsafe extern "C" fn(AtriManager) -> bool>(b"atri_manager_init")
+                .map_err(lib_err)?;
+
+            // 旧版本的插件未导出元数据
+            let info = lib
+                .get::<extern "C" fn() -> FFIPluginInfo>(b"plugin_info")
+                .ok()
+                .map(|f| f());
+
+            (&*(*abi)(), on_init, info, init)
+        };
+
+        let mut plugin = Self::from_instance(path, handle, abi, || on_init(), Some(lib))?;
+        if let Some(info) = info {
+            plugin.info = PluginInfo::from_ffi(info).map_err(|error| LoadError::Metadata {
+                path: path.to_owned(),
+                error,
+            })?;
+        }
+
+        if !plugin.missing.is_empty() {
+            state.backend.log(
+                &plugin.name,
+                LogLevel::Warn,
+                &format!(
+                    "插件(atri_plugin {}, abi {})需要的以下函数未被宿主提供: {:?}",
+                    plugin.crate_version, plugin.abi, plugin.missing
+                ),
+            );
+        }
+
+        // 插件以同样的规则检查宿主, 不兼容时不会初始化
+        let compatible = unsafe {
+            init(AtriManager {
+                manager_ptr: Arc::as_ptr(state).cast(),
+                handle,
+                get_fun: crate::vtable::get_fun,
+                abi: host_abi(),
+            })
+        };
+        if !compatible {
+            return Err(LoadError::AbiMismatch {
+                path: path.to_owned(),
+                error: AbiMismatch {
+                    host: AbiVersion::current(),
+                    plugin: plugin.abi,
+                    missing: vec![],
+                },
+            });
+        }
+
+        Ok(plugin)
+    }
+
+    /// 早于ABI握手的插件, 其主版本号均低于当前版本, 视为不兼容
+    pub(crate) fn legacy_mismatch(path: &Path, instance: PluginInstance) -> LoadError {
+        LoadError::AbiMismatch {
+            path: path.to_owned(),
+            error: AbiMismatch {
+                host: AbiVersion::current(),
+                plugin: AbiVersion {
+                    major: instance.abi_ver,
+                    minor: 0,
+                },
+                missing: vec![],
+            },
+        }
+    }
+
+    /// 确认ABI兼容后才调用`on_init`, 以免不兼容的插件代码被执行
+    pub(crate) fn from_instance(
+        path: &Path,
+        handle: usize,
+        abi: &AbiInfo,
+        on_init: impl FnOnce() -> PluginInstance,
+        lib: Option<Library>,
+    ) -> Result<Self, LoadError> {
+        let missing =
+            check_compatibility(&host_abi(), abi).map_err(|error| LoadError::AbiMismatch {
+                path: path.to_owned(),
+                error,
+            })?;
+
+        let PluginInstance {
+            should_drop,
+            vtb,
+            name,
+            ..
+        } = on_init();
+
+        Ok(Self {
+            handle,
+            name: name.to_string(),
+            path: path.to_owned(),
+            should_drop,
+            vtb,
+            abi: abi.version,
+            crate_version: abi.crate_version.to_string(),
+            missing,
+            info: PluginInfo::default(),
+            instance: None,
+            enabled: false,
+            _lib: lib,
+        })
+    }
+
+    pub fn handle(&self) -> usize {
+        self.handle
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn should_drop(&self) -> bool {
+        self.should_drop
+    }
+
+    pub fn info(&self) -> &PluginInfo {
+        &self.info
+    }
+
+    /// 插件编译时的ABI版本
+    pub fn abi_version(&self) -> AbiVersion {
+        self.abi
+    }
+
+    /// 插件编译时使用的`atri_plugin`版本
+    pub fn crate_version(&self) -> &str {
+        &self.crate_version
+    }
+
+    /// 插件需要而宿主未提供的函数签名
+    pub fn missing_signatures(&self) -> &[u16] {
+        &self.missing
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn enable(&mut self) -> bool {
+        if self.enabled {
+            return false;
+        }
+
+        let new = self.vtb.new;
+        let ptr = *self.instance.get_or_insert_with(|| new());
+        (self.vtb.enable)(ptr);
+        self.enabled = true;
+
+        true
+    }
+
+    pub(crate) fn disable(&mut self) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let Some(ptr) = self.instance else {
+            return false;
+        };
+        (self.vtb.disable)(ptr);
+        self.enabled = false;
+
+        if self.should_drop {
+            self.instance = None;
+            (self.vtb.drop)(ptr);
+        }
+
+        true
+    }
+
+    /// 禁用并销毁插件实例, 在卸载动态库前调用
+    pub(crate) fn destroy(&mut self) {
+        self.disable();
+        if let Some(ptr) = self.instance.take() {
+            (self.vtb.drop)(ptr);
+        }
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
+
+/// 宿主的ABI信息
+fn host_abi() -> AbiInfo {
+    AbiInfo::new(env!("CARGO_PKG_VERSION"), crate::vtable::SIGNATURES)
+}
@@ -0,0 +1,744 @@
+//! 不连接任何协议的后端, 用于在本地运行插件
+//!
+//! 所有联系人均由构造时给出, 插件发出的消息会被打印并记录
+
+use crate::backend::{
+    Backend, BotClient, BotFriend, BotGroup, BotImage, BotMember, BoxFuture, MemberRole,
+};
+use crate::error::{HostError, HostResult};
+use crate::event::{HostEvent, Member};
+use crate::message::meta::MessageReceipt;
+use crate::message::{Image, MessageChain};
+use std::future::ready;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Default)]
+pub struct StubBackend(Arc<StubState>);
+
+#[derive(Default)]
+struct StubState {
+    clients: Mutex<Vec<Arc<ClientData>>>,
+    outbox: Mutex<Vec<Outgoing>>,
+    next_seq: AtomicI32,
+}
+
+/// 插件通过后端发出的消息
+#[derive(Clone)]
+pub enum Outgoing {
+    Group {
+        group: i64,
+        message: MessageChain,
+    },
+    Friend {
+        friend: i64,
+        message: MessageChain,
+    },
+    /// 临时会话消息
+    Member {
+        group: i64,
+        member: i64,
+        message: MessageChain,
+    },
+    GroupRecall {
+        group: i64,
+        seqs: Vec<i32>,
+    },
+    FriendRecall {
+        friend: i64,
+        seqs: Vec<i32>,
+    },
+}
+
+impl StubBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn client(self, client: StubClient) -> Self {
+        let data = ClientData::build(client, Arc::downgrade(&self.0));
+        self.0.clients.lock().unwrap().push(data);
+        self
+    }
+
+    pub fn outbox(&self) -> Vec<Outgoing> {
+        self.0.outbox.lock().unwrap().clone()
+    }
+
+    /// 构造由群成员发出的群消息事件
+    pub fn group_message<M: Into<MessageChain>>(
+        &self,
+        client: i64,
+        group: i64,
+        sender: i64,
+        message: M,
+    ) -> Option<HostEvent> {
+        let group = self.0.find_client(client)?.group(group)?;
+        let member = group.member(sender)?;
+
+        let mut message = message.into();
+        self.0.incoming(&mut message, sender);
+
+        Some(HostEvent::GroupMessage {
+            group: group as Arc<dyn BotGroup>,
+            sender: Member::Named(member),
+            message,
+        })
+    }
+
+    /// 构造由群成员通过临时会话发出的消息事件
+    pub fn group_temp_message<M: Into<MessageChain>>(
+        &self,
+        client: i64,
+        group: i64,
+        sender: i64,
+        message: M,
+    ) -> Option<HostEvent> {
+        let group = self.0.find_client(client)?.group(group)?;
+        let sender = group.member(sender)?;
+
+        let mut message = message.into();
+        self.0.incoming(&mut message, sender.id());
+
+        Some(HostEvent::GroupTempMessage {
+            group: group as Arc<dyn BotGroup>,
+            sender,
+            message,
+        })
+    }
+
+    /// 构造由好友发出的好友消息事件
+    pub fn friend_message<M: Into<MessageChain>>(
+        &self,
+        client: i64,
+        friend: i64,
+        message: M,
+    ) -> Option<HostEvent> {
+        let friend = self.find_client(client)?.find_friend(friend)?;
+
+        let mut message = message.into();
+        self.0.incoming(&mut message, friend.id());
+
+        Some(HostEvent::FriendMessage { friend, message })
+    }
+
+    /// 将成员加入群, 并构造对应的入群事件
+    pub fn member_join(
+        &self,
+        client: i64,
+        group: i64,
+        member: StubMember,
+        operator: Option<i64>,
+    ) -> Option<HostEvent> {
+        let group = self.0.find_client(client)?.group(group)?;
+        let operator = operator.and_then(|id| group.member(id));
+        let member = group.add_member(member);
+
+        Some(HostEvent::GroupMemberJoin {
+            group: group as Arc<dyn BotGroup>,
+            member,
+            operator,
+        })
+    }
+
+    /// 将成员移出群, 并构造对应的离开事件; `operator`为`None`时视为主动退群,
+    /// 否则视为被移出, 即使操作者已不在群中
+    pub fn member_leave(
+        &self,
+        client: i64,
+        group: i64,
+        member: i64,
+        operator: Option<i64>,
+    ) -> Option<HostEvent> {
+        let group = self.0.find_client(client)?.group(group)?;
+        let kicked = operator.is_some();
+        let operator = operator.and_then(|id| group.member(id));
+        let member = group.remove_member(member)?;
+
+        Some(HostEvent::GroupMemberLeave {
+            group: group as Arc<dyn BotGroup>,
+            member,
+            operator,
+            kicked,
+        })
+    }
+}
+
+impl StubState {
+    fn find_client(&self, id: i64) -> Option<Arc<ClientData>> {
+        let clients = self.clients.lock().unwrap();
+        clients.iter().find(|c| c.id == id).cloned()
+    }
+
+    fn incoming(&self, message: &mut MessageChain, sender: i64) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+
+        message.meta.seqs = vec![seq];
+        message.meta.rands = vec![seq];
+        message.meta.time = now() as i32;
+        message.meta.sender = sender;
+    }
+
+    fn send(&self, out: Outgoing) -> MessageReceipt {
+        match &out {
+            Outgoing::Group { group, message } => {
+                println!("[stub] Group({}) <- {}", group, message)
+            }
+            Outgoing::Friend { friend, message } => {
+                println!("[stub] Friend({}) <- {}", friend, message)
+            }
+            Outgoing::Member {
+                group,
+                member,
+                message,
+            } => {
+                println!("[stub] Member({}@{}) <- {}", member, group, message)
+            }
+            Outgoing::GroupRecall { group, seqs } => {
+                println!("[stub] Group({}) recall {:?}", group, seqs)
+            }
+            Outgoing::FriendRecall { friend, seqs } => {
+                println!("[stub] Friend({}) recall {:?}", friend, seqs)
+            }
+        }
+        self.outbox.lock().unwrap().push(out);
+
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        MessageReceipt {
+            seqs: vec![seq],
+            rands: vec![seq],
+            time: now(),
+        }
+    }
+}
+
+impl Backend for StubBackend {
+    fn clients(&self) -> Vec<Arc<dyn BotClient>> {
+        let clients = self.0.clients.lock().unwrap();
+        clients
+            .iter()
+            .map(|c| c.clone() as Arc<dyn BotClient>)
+            .collect()
+    }
+}
+
+pub struct StubClient {
+    id: i64,
+    nickname: String,
+    groups: Vec<StubGroup>,
+    friends: Vec<StubFriend>,
+}
+
+impl StubClient {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+            groups: vec![],
+            friends: vec![],
+        }
+    }
+
+    pub fn group(mut self, group: StubGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn friend(mut self, friend: StubFriend) -> Self {
+        self.friends.push(friend);
+        self
+    }
+}
+
+pub struct StubGroup {
+    id: i64,
+    name: String,
+    members: Vec<StubMember>,
+}
+
+impl StubGroup {
+    pub fn new<S: Into<String>>(id: i64, name: S) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            members: vec![],
+        }
+    }
+
+    pub fn member(mut self, member: StubMember) -> Self {
+        self.members.push(member);
+        self
+    }
+}
+
+pub struct StubFriend {
+    id: i64,
+    nickname: String,
+}
+
+impl StubFriend {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+        }
+    }
+}
+
+pub struct StubMember {
+    id: i64,
+    nickname: String,
+    card_name: String,
+    role: MemberRole,
+}
+
+impl StubMember {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+            card_name: String::new(),
+            role: MemberRole::Member,
+        }
+    }
+
+    pub fn card_name<S: Into<String>>(mut self, card_name: S) -> Self {
+        self.card_name = card_name.into();
+        self
+    }
+
+    pub fn role(mut self, role: MemberRole) -> Self {
+        self.role = role;
+        self
+    }
+}
+
+/// 借出的字符串, 旧值会一直保留到本值被销毁, 以保证借出的切片有效
+struct StrCell(Mutex<Vec<Box<str>>>);
+
+impl StrCell {
+    fn new(s: String) -> Self {
+        Self(Mutex::new(vec![s.into_boxed_str()]))
+    }
+
+    fn get(&self) -> &str {
+        let v = self.0.lock().unwrap();
+        let s: *const str = &**v.last().unwrap();
+        unsafe { &*s }
+    }
+
+    fn set(&self, s: String) {
+        self.0.lock().unwrap().push(s.into_boxed_str());
+    }
+}
+
+struct ClientData {
+    id: i64,
+    nickname: String,
+    groups: Mutex<Vec<Arc<GroupData>>>,
+    friends: Vec<Arc<FriendData>>,
+    state: Weak<StubState>,
+}
+
+impl ClientData {
+    fn build(client: StubClient, state: Weak<StubState>) -> Arc<Self> {
+        Arc::new_cyclic(|weak: &Weak<ClientData>| Self {
+            id: client.id,
+            nickname: client.nickname,
+            groups: Mutex::new(
+                client
+                    .groups
+                    .into_iter()
+                    .map(|g| GroupData::build(g, weak.clone()))
+                    .collect(),
+            ),
+            friends: client
+                .friends
+                .into_iter()
+                .map(|f| {
+                    Arc::new(FriendData {
+                        id: f.id,
+                        nickname: f.nickname,
+                        client: weak.clone(),
+                    })
+                })
+                .collect(),
+            state,
+        })
+    }
+
+    fn group(&self, id: i64) -> Option<Arc<GroupData>> {
+        let groups = self.groups.lock().unwrap();
+        groups.iter().find(|g| g.id == id).cloned()
+    }
+
+    fn send(&self, out: Outgoing) -> HostResult<MessageReceipt> {
+        self.state
+            .upgrade()
+            .map(|state| state.send(out))
+            .ok_or_else(|| HostError::ClientError(String::from("Backend has been dropped")))
+    }
+}
+
+impl BotClient for ClientData {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn nickname(&self) -> String {
+        self.nickname.clone()
+    }
+
+    fn find_group(&self, id: i64) -> Option<Arc<dyn BotGroup>> {
+        self.group(id).map(|g| g as Arc<dyn BotGroup>)
+    }
+
+    fn find_friend(&self, id: i64) -> Option<Arc<dyn BotFriend>> {
+        let friend = self.friends.iter().find(|f| f.id == id).cloned();
+        friend.map(|f| f as Arc<dyn BotFriend>)
+    }
+
+    fn groups(&self) -> Vec<Arc<dyn BotGroup>> {
+        let groups = self.groups.lock().unwrap();
+        groups
+            .iter()
+            .map(|g| g.clone() as Arc<dyn BotGroup>)
+            .collect()
+    }
+
+    fn friends(&self) -> Vec<Arc<dyn BotFriend>> {
+        self.friends
+            .iter()
+            .map(|f| f.clone() as Arc<dyn BotFriend>)
+            .collect()
+    }
+}
+
+struct GroupData {
+    id: i64,
+    name: StrCell,
+    client: Weak<ClientData>,
+    members: Mutex<Vec<Arc<MemberData>>>,
+    quit: AtomicBool,
+}
+
+impl GroupData {
+    fn build(group: StubGroup, client: Weak<ClientData>) -> Arc<Self> {
+        Arc::new_cyclic(|weak: &Weak<GroupData>| Self {
+            id: group.id,
+            name: StrCell::new(group.name),
+            client,
+            members: Mutex::new(
+                group
+                    .members
+                    .into_iter()
+                    .map(|m| MemberData::build(m, weak.clone()))
+                    .collect(),
+            ),
+            quit: AtomicBool::new(false),
+        })
+    }
+
+    fn member(&self, id: i64) -> Option<Arc<dyn BotMember>> {
+        let members = self.members.lock().unwrap();
+        let member = members.iter().find(|m| m.id == id).cloned();
+        member.map(|m| m as Arc<dyn BotMember>)
+    }
+
+    fn add_member(self: &Arc<Self>, member: StubMember) -> Arc<dyn BotMember> {
+        let member = MemberData::build(member, Arc::downgrade(self));
+        self.members.lock().unwrap().push(member.clone());
+        member
+    }
+
+    fn remove_member(&self, id: i64) -> Option<Arc<dyn BotMember>> {
+        let mut members = self.members.lock().unwrap();
+        let pos = members.iter().position(|m| m.id == id)?;
+        Some(members.remove(pos))
+    }
+
+    fn client_data(&self) -> Arc<ClientData> {
+        self.client.upgrade().expect("Client has been dropped")
+    }
+}
+
+impl BotGroup for GroupData {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        self.name.get()
+    }
+
+    fn client(&self) -> Arc<dyn BotClient> {
+        self.client_data()
+    }
+
+    fn members(self: Arc<Self>) -> BoxFuture<Vec<Arc<dyn BotMember>>> {
+        let members = self
+            .members
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|m| m.clone() as Arc<dyn BotMember>)
+            .collect();
+
+        Box::pin(ready(members))
+    }
+
+    fn find_member(self: Arc<Self>, id: i64) -> BoxFuture<Option<Arc<dyn BotMember>>> {
+        Box::pin(ready(self.member(id)))
+    }
+
+    fn send_message(self: Arc<Self>, chain: MessageChain) -> BoxFuture<HostResult<MessageReceipt>> {
+        let result = if self.quit.load(Ordering::SeqCst) {
+            Err(HostError::ClientError(format!(
+                "Group({}) has been quit",
+                self.id
+            )))
+        } else {
+            self.client_data().send(Outgoing::Group {
+                group: self.id,
+                message: chain,
+            })
+        };
+
+        Box::pin(ready(result))
+    }
+
+    fn upload_image(self: Arc<Self>, data: Vec<u8>) -> BoxFuture<HostResult<Image>> {
+        Box::pin(ready(Ok(StubImage::from_bytes(&data))))
+    }
+
+    fn recall(self: Arc<Self>, receipt: MessageReceipt) -> BoxFuture<HostResult<()>> {
+        let result = self.client_data().send(Outgoing::GroupRecall {
+            group: self.id,
+            seqs: receipt.seqs,
+        });
+
+        Box::pin(ready(result.map(|_| ())))
+    }
+
+    fn change_name(self: Arc<Self>, name: String) -> BoxFuture<HostResult<()>> {
+        self.name.set(name);
+        Box::pin(ready(Ok(())))
+    }
+
+    fn member_count(&self) -> u32 {
+        self.members.lock().unwrap().len() as u32
+    }
+
+    fn quit(self: Arc<Self>) -> BoxFuture<bool> {
+        let quit = !self.quit.swap(true, Ordering::SeqCst);
+        if quit {
+            let client = self.client_data();
+            client.groups.lock().unwrap().retain(|g| g.id != self.id);
+        }
+
+        Box::pin(ready(quit))
+    }
+}
+
+struct FriendData {
+    id: i64,
+    nickname: String,
+    client: Weak<ClientData>,
+}
+
+impl BotFriend for FriendData {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn nickname(&self) -> &str {
+        &self.nickname
+    }
+
+    fn client(&self) -> Arc<dyn BotClient> {
+        self.client.upgrade().expect("Client has been dropped")
+    }
+
+    fn send_message(self: Arc<Self>, chain: MessageChain) -> BoxFuture<HostResult<MessageReceipt>> {
+        let result = match self.client.upgrade() {
+            Some(client) => client.send(Outgoing::Friend {
+                friend: self.id,
+                message: chain,
+            }),
+            None => Err(HostError::ClientError(String::from(
+                "Client has been dropped",
+            ))),
+        };
+
+        Box::pin(ready(result))
+    }
+
+    fn upload_image(self: Arc<Self>, data: Vec<u8>) -> BoxFuture<HostResult<Image>> {
+        Box::pin(ready(Ok(StubImage::from_bytes(&data))))
+    }
+
+    fn recall(self: Arc<Self>, receipt: MessageReceipt) -> BoxFuture<HostResult<()>> {
+        let result = match self.client.upgrade() {
+            Some(client) => client.send(Outgoing::FriendRecall {
+                friend: self.id,
+                seqs: receipt.seqs,
+            }),
+            None => Err(HostError::ClientError(String::from(
+                "Client has been dropped",
+            ))),
+        };
+
+        Box::pin(ready(result.map(|_| ())))
+    }
+}
+
+struct MemberData {
+    id: i64,
+    nickname: String,
+    card_name: StrCell,
+    role: Mutex<MemberRole>,
+    join_time: i64,
+    group: Weak<GroupData>,
+}
+
+impl MemberData {
+    fn build(member: StubMember, group: Weak<GroupData>) -> Arc<Self> {
+        Arc::new(Self {
+            id: member.id,
+            nickname: member.nickname,
+            card_name: StrCell::new(member.card_name),
+            role: Mutex::new(member.role),
+            join_time: now(),
+            group,
+        })
+    }
+}
+
+impl BotMember for MemberData {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn nickname(&self) -> &str {
+        &self.nickname
+    }
+
+    fn card_name(&self) -> &str {
+        self.card_name.get()
+    }
+
+    fn group(&self) -> Arc<dyn BotGroup> {
+        self.group.upgrade().expect("Group has been dropped")
+    }
+
+    fn role(&self) -> MemberRole {
+        *self.role.lock().unwrap()
+    }
+
+    fn join_time(&self) -> i64 {
+        self.join_time
+    }
+
+    fn send_message(self: Arc<Self>, chain: MessageChain) -> BoxFuture<HostResult<MessageReceipt>> {
+        let result = match self.group.upgrade() {
+            Some(group) => group.client_data().send(Outgoing::Member {
+                group: group.id,
+                member: self.id,
+                message: chain,
+            }),
+            None => Err(HostError::ClientError(String::from(
+                "Group has been dropped",
+            ))),
+        };
+
+        Box::pin(ready(result))
+    }
+
+    fn upload_image(self: Arc<Self>, data: Vec<u8>) -> BoxFuture<HostResult<Image>> {
+        Box::pin(ready(Ok(StubImage::from_bytes(&data))))
+    }
+
+    fn change_card_name(self: Arc<Self>, card: String) -> BoxFuture<HostResult<()>> {
+        self.card_name.set(card);
+        Box::pin(ready(Ok(())))
+    }
+
+    fn mute(self: Arc<Self>, duration: Duration) -> BoxFuture<HostResult<()>> {
+        println!("[stub] Member({}) muted for {:?}", self.id, duration);
+        Box::pin(ready(Ok(())))
+    }
+
+    fn kick(self: Arc<Self>, reason: String, _block: bool) -> BoxFuture<HostResult<()>> {
+        let result = match self.group.upgrade() {
+            Some(group) => group
+                .remove_member(self.id)
+                .map(|_| println!("[stub] Member({}) kicked: {}", self.id, reason))
+                .ok_or_else(|| {
+                    HostError::ClientError(format!("Member({}) is not in the group", self.id))
+                }),
+            None => Err(HostError::ClientError(String::from(
+                "Group has been dropped",
+            ))),
+        };
+
+        Box::pin(ready(result))
+    }
+
+    fn set_admin(self: Arc<Self>, admin: bool) -> BoxFuture<HostResult<()>> {
+        let mut role = self.role.lock().unwrap();
+        let result = if *role == MemberRole::Owner {
+            Err(HostError::ClientError(String::from(
+                "Cannot change the role of the owner",
+            )))
+        } else {
+            *role = if admin {
+                MemberRole::Administrator
+            } else {
+                MemberRole::Member
+            };
+            Ok(())
+        };
+
+        Box::pin(ready(result))
+    }
+
+    fn set_special_title(self: Arc<Self>, title: String) -> BoxFuture<HostResult<()>> {
+        println!("[stub] Member({}) special title: {}", self.id, title);
+        Box::pin(ready(Ok(())))
+    }
+}
+
+struct StubImage {
+    id: String,
+    url: String,
+}
+
+impl StubImage {
+    fn from_bytes(data: &[u8]) -> Image {
+        let hash = data.iter().fold(0x811c9dc5u32, |hash, b| {
+            (hash ^ *b as u32).wrapping_mul(0x01000193)
+        });
+        let id = format!("{{{:08X}-{:04X}}}.image", hash, data.len() & 0xFFFF);
+        let url = format!("https://stub.atri/image/{}", id);
+
+        Arc::new(Self { id, url })
+    }
+}
+
+impl BotImage for StubImage {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,1417 @@
+use crate::backend::{
+    BotClient, BotFriend, BotGroup, BotMember, BotRequest, BotStranger, GroupFile, GroupFileEntry,
+    LogLevel,
+};
+use crate::event::{
+    AnonymousMemberData, DeleteFriendData, EventData, FriendMessageData, FriendRecallData,
+    FriendRequestData, GroupInvitedData, GroupJoinRequestData, GroupMemberData, GroupMessageData,
+    GroupRecallData, GroupTempMessageData, Member,
+};
+use crate::handle::{
+    clone_handle, drop_handle, handle_ref, into_handle, opt_into_handle, OwnedHandle,
+};
+use crate::manager::{default_manager, ManagerState};
+use crate::message::ffi::forward_from_ffi;
+use crate::message::meta::MessageReceipt;
+use crate::message::{File, Image, MessageChain, MessageElement, Video, Voice};
+use atri_ffi::closure::FFIFn;
+use atri_ffi::contact::FFIMember;
+use atri_ffi::contact::{FFIAnnouncement, FFIGroupFile, FFIGroupSettings};
+use atri_ffi::error::FFIResult;
+use atri_ffi::ffi::{FFIEvent, ForFFI};
+use atri_ffi::future::FFIFuture;
+use atri_ffi::message::forward::FFIForwardNode;
+use atri_ffi::message::{FFIMessageChain, FFIMessageElement, FFIMessageReceipt};
+use atri_ffi::{
+    FFIOption, Handle, Managed, ManagedCloneable, PHandle, RustStr, RustString, RustVec,
+};
+use std::future::ready;
+use std::ptr::null;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle as RuntimeHandle;
+
+macro_rules! resolve {
+    ($($sig:literal => $fun:ident: $t:ty),* $(,)?) => {
+        /// 宿主提供的函数签名, 用于与插件进行ABI协商
+        pub(crate) const SIGNATURES: &[u16] = &[$($sig),*];
+
+        /// 传递给插件的函数获取器, 签名需与插件侧的`AtriVTable`保持一致
+        pub(crate) extern "C" fn get_fun(sig: u16) -> *const () {
+            match sig {
+                $($sig => $fun as $t as *const (),)*
+                _ => null(),
+            }
+        }
+    };
+}
+
+resolve! {
+    0 => plugin_manager_spawn: extern "C" fn(*const (), FFIFuture<Managed>) -> FFIFuture<FFIResult<Managed>>,
+    1 => plugin_manager_block_on: extern "C" fn(*const (), FFIFuture<Managed>) -> Managed,
+
+    100 => new_listener: extern "C" fn(bool, FFIFn<FFIEvent, FFIFuture<bool>>, u8) -> Managed,
+    101 => listener_next_event_with_priority: extern "C" fn(u64, FFIFn<FFIEvent, bool>, u8) -> FFIFuture<FFIOption<FFIEvent>>,
+    102 => new_listener_with_manager: extern "C" fn(*const (), bool, FFIFn<FFIEvent, FFIFuture<bool>>, u8) -> Managed,
+    103 => listener_next_event_with_manager: extern "C" fn(*const (), u64, FFIFn<FFIEvent, bool>, u8) -> FFIFuture<FFIOption<FFIEvent>>,
+
+    200 => event_intercept: extern "C" fn(*const ()),
+    201 => event_is_intercepted: extern "C" fn(*const ()) -> bool,
+
+    300 => client_get_id: extern "C" fn(Handle) -> i64,
+    301 => client_get_nickname: extern "C" fn(Handle) -> RustString,
+    302 => client_get_list: extern "C" fn() -> RustVec<Handle>,
+    303 => find_client: extern "C" fn(i64) -> Handle,
+    304 => client_find_group: extern "C" fn(Handle, i64) -> Handle,
+    305 => client_find_friend: extern "C" fn(Handle, i64) -> Handle,
+    306 => client_get_groups: extern "C" fn(Handle) -> RustVec<Handle>,
+    307 => client_get_friends: extern "C" fn(Handle) -> RustVec<Handle>,
+    308 => client_find_stranger: extern "C" fn(Handle, i64) -> FFIFuture<ManagedCloneable>,
+    309 => client_get_list_with_manager: extern "C" fn(*const ()) -> RustVec<Handle>,
+    310 => find_client_with_manager: extern "C" fn(*const (), i64) -> Handle,
+    320 => client_clone: extern "C" fn(Handle) -> Handle,
+    321 => client_drop: extern "C" fn(Handle),
+
+    400 => group_get_id: extern "C" fn(Handle) -> i64,
+    401 => group_get_name: extern "C" fn(Handle) -> RustStr,
+    402 => group_get_client: extern "C" fn(Handle) -> Handle,
+    403 => group_get_members: extern "C" fn(Handle) -> FFIFuture<RustVec<ManagedCloneable>>,
+    404 => group_find_member: extern "C" fn(Handle, i64) -> FFIFuture<ManagedCloneable>,
+    406 => group_send_message: extern "C" fn(Handle, FFIMessageChain) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
+    407 => group_upload_image: extern "C" fn(Handle, RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+    408 => group_quit: extern "C" fn(Handle) -> FFIFuture<bool>,
+    409 => group_change_name: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<()>>,
+    410 => group_send_forward_message: extern "C" fn(Handle, RustVec<FFIForwardNode>) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
+    411 => group_invite: extern "C" fn(Handle, i64) -> FFIFuture<FFIResult<()>>,
+    412 => group_recall: extern "C" fn(Handle, FFIMessageReceipt) -> FFIFuture<FFIResult<()>>,
+    413 => group_mute_all: extern "C" fn(Handle, bool) -> FFIFuture<FFIResult<()>>,
+    414 => group_get_announcements: extern "C" fn(Handle) -> FFIFuture<FFIResult<RustVec<FFIAnnouncement>>>,
+    415 => group_publish_announcement: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<RustString>>,
+    416 => group_delete_announcement: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<()>>,
+    417 => group_set_anonymous: extern "C" fn(Handle, bool) -> FFIFuture<FFIResult<()>>,
+    418 => group_change_avatar: extern "C" fn(Handle, RustVec<u8>) -> FFIFuture<FFIResult<()>>,
+    419 => group_get_member_count: extern "C" fn(Handle) -> u32,
+    420 => group_clone: extern "C" fn(Handle) -> Handle,
+    421 => group_drop: extern "C" fn(Handle),
+    422 => group_get_owner: extern "C" fn(Handle) -> FFIFuture<ManagedCloneable>,
+    423 => group_get_settings: extern "C" fn(Handle) -> FFIGroupSettings,
+    424 => group_upload_voice: extern "C" fn(Handle, RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+    425 => group_list_files: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<RustVec<FFIGroupFile>>>,
+    426 => group_upload_file: extern "C" fn(Handle, RustStr, RustStr, RustVec<u8>) -> FFIFuture<FFIResult<FFIGroupFile>>,
+    427 => group_download_file: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<RustVec<u8>>>,
+    428 => group_rename_file: extern "C" fn(Handle, RustStr, RustStr) -> FFIFuture<FFIResult<()>>,
+    429 => group_move_file: extern "C" fn(Handle, RustStr, RustStr) -> FFIFuture<FFIResult<()>>,
+    430 => group_delete_file: extern "C" fn(Handle, RustStr) -> FFIFuture<FFIResult<()>>,
+    431 => group_get_message: extern "C" fn(Handle, i32) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>>,
+    432 => group_get_history: extern "C" fn(Handle, i32, u32) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>>,
+
+    500 => friend_get_id: extern "C" fn(Handle) -> i64,
+    501 => friend_get_nickname: extern "C" fn(Handle) -> RustStr,
+    502 => friend_get_client: extern "C" fn(Handle) -> Handle,
+    503 => friend_send_message: extern "C" fn(Handle, FFIMessageChain) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
+    504 => friend_upload_image: extern "C" fn(Handle, RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+    505 => friend_recall: extern "C" fn(Handle, FFIMessageReceipt) -> FFIFuture<FFIResult<()>>,
+    506 => friend_upload_voice: extern "C" fn(Handle, RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+    507 => friend_get_history: extern "C" fn(Handle, i32, u32) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>>,
+    508 => friend_get_message: extern "C" fn(Handle, i32) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>>,
+    520 => friend_clone: extern "C" fn(Handle) -> Handle,
+    521 => friend_drop: extern "C" fn(Handle),
+
+    600 => named_member_get_id: extern "C" fn(*const ()) -> i64,
+    601 => named_member_get_nickname: extern "C" fn(*const ()) -> RustStr,
+    602 => named_member_get_card_name: extern "C" fn(*const ()) -> RustStr,
+    603 => named_member_get_group: extern "C" fn(*const ()) -> Handle,
+    604 => named_member_change_card_name: extern "C" fn(*const (), RustStr) -> FFIFuture<FFIResult<()>>,
+    605 => named_member_mute: extern "C" fn(*const (), u64) -> FFIFuture<FFIResult<()>>,
+    606 => named_member_kick: extern "C" fn(*const (), RustStr, bool) -> FFIFuture<FFIResult<()>>,
+    607 => named_member_set_admin: extern "C" fn(*const (), bool) -> FFIFuture<FFIResult<()>>,
+    608 => named_member_set_special_title: extern "C" fn(*const (), RustStr) -> FFIFuture<FFIResult<()>>,
+    609 => named_member_get_role: extern "C" fn(*const ()) -> u8,
+    610 => named_member_get_join_time: extern "C" fn(*const ()) -> i64,
+    611 => named_member_get_last_speak_time: extern "C" fn(*const ()) -> i64,
+    612 => named_member_get_level: extern "C" fn(*const ()) -> u16,
+    613 => named_member_send_message: extern "C" fn(*const (), FFIMessageChain) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
+    614 => named_member_upload_image: extern "C" fn(*const (), RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+
+    700 => anonymous_member_get_anon_id: extern "C" fn(*const ()) -> RustVec<u8>,
+    701 => anonymous_member_get_nickname: extern "C" fn(*const ()) -> RustStr,
+    702 => anonymous_member_get_portrait_index: extern "C" fn(*const ()) -> i32,
+    703 => anonymous_member_get_bubble_index: extern "C" fn(*const ()) -> i32,
+    704 => anonymous_member_get_expire_time: extern "C" fn(*const ()) -> i32,
+    705 => anonymous_member_get_color: extern "C" fn(*const ()) -> RustStr,
+    706 => anonymous_member_get_group: extern "C" fn(*const ()) -> Handle,
+    707 => anonymous_member_mute: extern "C" fn(*const (), u64) -> FFIFuture<FFIResult<()>>,
+
+    800 => stranger_get_id: extern "C" fn(*const ()) -> i64,
+    801 => stranger_get_nickname: extern "C" fn(*const ()) -> RustStr,
+    802 => stranger_get_client: extern "C" fn(*const ()) -> Handle,
+    803 => stranger_send_message: extern "C" fn(*const (), FFIMessageChain) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
+    804 => stranger_upload_image: extern "C" fn(*const (), RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
+
+    10000 => group_message_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    10001 => group_message_event_get_message: extern "C" fn(*const ()) -> FFIMessageChain,
+    10002 => group_message_event_get_sender: extern "C" fn(*const ()) -> FFIMember,
+
+    10100 => friend_message_event_get_friend: extern "C" fn(*const ()) -> PHandle,
+    10101 => friend_message_event_get_message: extern "C" fn(*const ()) -> FFIMessageChain,
+
+    10200 => group_member_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    10201 => group_member_event_get_member: extern "C" fn(*const ()) -> ManagedCloneable,
+    10202 => group_member_event_get_operator: extern "C" fn(*const ()) -> ManagedCloneable,
+
+    10300 => group_member_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    10301 => group_member_event_get_member: extern "C" fn(*const ()) -> ManagedCloneable,
+    10302 => group_member_event_get_operator: extern "C" fn(*const ()) -> ManagedCloneable,
+    10303 => group_member_leave_event_is_kicked: extern "C" fn(*const ()) -> bool,
+
+    10400 => group_recall_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    10401 => group_recall_event_get_operator: extern "C" fn(*const ()) -> ManagedCloneable,
+    10402 => group_recall_event_get_operator_id: extern "C" fn(*const ()) -> i64,
+    10403 => group_recall_event_get_sender_id: extern "C" fn(*const ()) -> i64,
+    10404 => group_recall_event_get_seqs: extern "C" fn(*const ()) -> RustVec<i32>,
+    10405 => group_recall_event_get_time: extern "C" fn(*const ()) -> i32,
+
+    10500 => friend_recall_event_get_friend: extern "C" fn(*const ()) -> PHandle,
+    10501 => friend_recall_event_get_operator_id: extern "C" fn(*const ()) -> i64,
+    10502 => friend_recall_event_get_seqs: extern "C" fn(*const ()) -> RustVec<i32>,
+    10503 => friend_recall_event_get_time: extern "C" fn(*const ()) -> i32,
+    10504 => friend_recall_event_get_sender_id: extern "C" fn(*const ()) -> i64,
+
+    10600 => friend_request_event_get_client: extern "C" fn(*const ()) -> PHandle,
+    10601 => friend_request_event_get_requester_id: extern "C" fn(*const ()) -> i64,
+    10602 => friend_request_event_get_requester_nickname: extern "C" fn(*const ()) -> RustStr,
+    10603 => friend_request_event_get_message: extern "C" fn(*const ()) -> RustStr,
+    10604 => friend_request_event_accept: extern "C" fn(*const ()) -> FFIFuture<FFIResult<()>>,
+    10605 => friend_request_event_reject: extern "C" fn(*const (), RustStr, bool) -> FFIFuture<FFIResult<()>>,
+
+    10700 => group_invited_event_get_client: extern "C" fn(*const ()) -> PHandle,
+    10701 => group_invited_event_get_group_id: extern "C" fn(*const ()) -> i64,
+    10702 => group_invited_event_get_group_name: extern "C" fn(*const ()) -> RustStr,
+    10703 => group_invited_event_get_inviter_id: extern "C" fn(*const ()) -> i64,
+    10704 => group_invited_event_get_inviter_nickname: extern "C" fn(*const ()) -> RustStr,
+    10705 => group_invited_event_accept: extern "C" fn(*const ()) -> FFIFuture<FFIResult<()>>,
+    10706 => group_invited_event_reject: extern "C" fn(*const (), RustStr, bool) -> FFIFuture<FFIResult<()>>,
+
+    10800 => group_join_request_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    10801 => group_join_request_event_get_requester_id: extern "C" fn(*const ()) -> i64,
+    10802 => group_join_request_event_get_requester_nickname: extern "C" fn(*const ()) -> RustStr,
+    10803 => group_join_request_event_get_message: extern "C" fn(*const ()) -> RustStr,
+    10804 => group_join_request_event_accept: extern "C" fn(*const ()) -> FFIFuture<FFIResult<()>>,
+    10805 => group_join_request_event_reject: extern "C" fn(*const (), RustStr, bool) -> FFIFuture<FFIResult<()>>,
+
+    10900 => client_login_event_get_client: extern "C" fn(*const ()) -> PHandle,
+
+    11000 => new_friend_event_get_friend: extern "C" fn(*const ()) -> PHandle,
+
+    11100 => delete_friend_event_get_client: extern "C" fn(*const ()) -> PHandle,
+    11101 => delete_friend_event_get_friend_id: extern "C" fn(*const ()) -> i64,
+
+    11200 => group_temp_message_event_get_group: extern "C" fn(*const ()) -> PHandle,
+    11201 => group_temp_message_event_get_sender: extern "C" fn(*const ()) -> ManagedCloneable,
+    11202 => group_temp_message_event_get_message: extern "C" fn(*const ()) -> FFIMessageChain,
+
+    2000 => image_get_id: extern "C" fn(*const ()) -> RustStr,
+    2002 => image_get_url: extern "C" fn(*const ()) -> RustString,
+
+    2100 => voice_get_url: extern "C" fn(*const ()) -> RustString,
+    2101 => voice_get_md5: extern "C" fn(*const ()) -> RustVec<u8>,
+    2102 => voice_get_size: extern "C" fn(*const ()) -> u32,
+    2103 => voice_get_duration: extern "C" fn(*const ()) -> u64,
+    2104 => voice_download: extern "C" fn(*const ()) -> FFIFuture<FFIResult<RustVec<u8>>>,
+
+    2200 => video_get_name: extern "C" fn(*const ()) -> RustStr,
+    2201 => video_get_size: extern "C" fn(*const ()) -> u64,
+    2202 => video_get_url: extern "C" fn(*const ()) -> RustString,
+    2203 => video_download: extern "C" fn(*const ()) -> FFIFuture<FFIResult<RustVec<u8>>>,
+
+    2300 => file_get_name: extern "C" fn(*const ()) -> RustStr,
+    2301 => file_get_size: extern "C" fn(*const ()) -> u64,
+    2302 => file_get_url: extern "C" fn(*const ()) -> RustString,
+    2303 => file_download: extern "C" fn(*const ()) -> FFIFuture<FFIResult<RustVec<u8>>>,
+
+    20000 => log: extern "C" fn(usize, *const (), u8, RustStr),
+
+    30000 => env_get_workspace: extern "C" fn(usize, *const ()) -> RustString,
+
+    30100 => message_chain_to_json: extern "C" fn(FFIMessageChain) -> RustString,
+    30101 => message_chain_from_json: extern "C" fn(RustStr) -> FFIResult<FFIMessageChain>,
+    30102 => message_element_wrap: extern "C" fn(FFIMessageElement) -> ManagedCloneable,
+    30103 => message_element_unwrap: extern "C" fn(*const ()) -> FFIMessageElement,
+    30104 => message_element_to_json: extern "C" fn(*const ()) -> RustString,
+}
+
+fn manager<'a>(manager: *const ()) -> &'a ManagerState {
+    unsafe { &*(manager as *const ManagerState) }
+}
+
+fn event<'a, T>(event: *const ()) -> &'a EventData<T> {
+    unsafe { &*(event as *const EventData<T>) }
+}
+
+extern "C" fn plugin_manager_spawn(
+    manager_ptr: *const (),
+    future: FFIFuture<Managed>,
+) -> FFIFuture<FFIResult<Managed>> {
+    let handle = manager(manager_ptr).runtime.spawn(future);
+    FFIFuture::from(async move { FFIResult::from(handle.await) })
+}
+
+extern "C" fn plugin_manager_block_on(
+    manager_ptr: *const (),
+    future: FFIFuture<Managed>,
+) -> Managed {
+    let runtime = &manager(manager_ptr).runtime;
+
+    // 在运行时的工作线程中阻塞需先让出该线程
+    if RuntimeHandle::try_current().is_ok() {
+        tokio::task::block_in_place(|| runtime.block_on(future))
+    } else {
+        runtime.block_on(future)
+    }
+}
+
+extern "C" fn new_listener(
+    concurrent: bool,
+    handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+    priority: u8,
+) -> Managed {
+    match default_manager() {
+        Some(state) => Managed::from_value(state.listeners.add(concurrent, handler, priority)),
+        None => Managed::from_value(handler),
+    }
+}
+
+extern "C" fn listener_next_event_with_priority(
+    millis: u64,
+    filter: FFIFn<FFIEvent, bool>,
+    priority: u8,
+) -> FFIFuture<FFIOption<FFIEvent>> {
+    match default_manager() {
+        Some(state) => next_event(&state, millis, filter, priority),
+        None => FFIFuture::from(ready(FFIOption::from(None))),
+    }
+}
+
+extern "C" fn new_listener_with_manager(
+    manager_ptr: *const (),
+    concurrent: bool,
+    handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+    priority: u8,
+) -> Managed {
+    let guard = manager(manager_ptr)
+        .listeners
+        .add(concurrent, handler, priority);
+    Managed::from_value(guard)
+}
+
+extern "C" fn listener_next_event_with_manager(
+    manager_ptr: *const (),
+    millis: u64,
+    filter: FFIFn<FFIEvent, bool>,
+    priority: u8,
+) -> FFIFuture<FFIOption<FFIEvent>> {
+    next_event(manager(manager_ptr), millis, filter, priority)
+}
+
+fn next_event(
+    state: &ManagerState,
+    millis: u64,
+    filter: FFIFn<FFIEvent, bool>,
+    priority: u8,
+) -> FFIFuture<FFIOption<FFIEvent>> {
+    FFIFuture::from(state.listeners.next_event(
+        &state.runtime,
+        Duration::from_millis(millis),
+        filter,
+        priority,
+    ))
+}
+
+extern "C" fn event_intercept(intercepted: *const ()) {
+    let intercepted = unsafe { &*(intercepted as *const AtomicBool) };
+    intercepted.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn event_is_intercepted(intercepted: *const ()) -> bool {
+    let intercepted = unsafe { &*(intercepted as *const AtomicBool) };
+    intercepted.load(Ordering::SeqCst)
+}
+
+fn client<'a>(client: Handle) -> &'a Arc<dyn BotClient> {
+    unsafe { handle_ref(client) }
+}
+
+extern "C" fn client_get_id(handle: Handle) -> i64 {
+    client(handle).id()
+}
+
+extern "C" fn client_get_nickname(handle: Handle) -> RustString {
+    RustString::from(client(handle).nickname())
+}
+
+extern "C" fn client_get_list() -> RustVec<Handle> {
+    let clients = default_manager()
+        .map(|state| state.backend.clients())
+        .unwrap_or_default();
+
+    clients_into_ffi(clients)
+}
+
+extern "C" fn find_client(id: i64) -> Handle {
+    opt_into_handle(default_manager().and_then(|state| state.backend.find_client(id)))
+}
+
+extern "C" fn client_get_list_with_manager(manager_ptr: *const ()) -> RustVec<Handle> {
+    clients_into_ffi(manager(manager_ptr).backend.clients())
+}
+
+extern "C" fn find_client_with_manager(manager_ptr: *const (), id: i64) -> Handle {
+    opt_into_handle(manager(manager_ptr).backend.find_client(id))
+}
+
+fn clients_into_ffi(clients: Vec<Arc<dyn BotClient>>) -> RustVec<Handle> {
+    clients
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn client_find_group(handle: Handle, id: i64) -> Handle {
+    opt_into_handle(client(handle).find_group(id))
+}
+
+extern "C" fn client_find_friend(handle: Handle, id: i64) -> Handle {
+    opt_into_handle(client(handle).find_friend(id))
+}
+
+extern "C" fn client_get_groups(handle: Handle) -> RustVec<Handle> {
+    client(handle)
+        .groups()
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn client_get_friends(handle: Handle) -> RustVec<Handle> {
+    client(handle)
+        .friends()
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn client_find_stranger(handle: Handle, id: i64) -> FFIFuture<ManagedCloneable> {
+    let stranger = client(handle).find_stranger(id);
+
+    FFIFuture::from(async move {
+        stranger
+            .await
+            .map(ManagedCloneable::from_value)
+            .unwrap_or_else(|| unsafe { ManagedCloneable::null() })
+    })
+}
+
+extern "C" fn client_clone(handle: Handle) -> Handle {
+    unsafe { clone_handle::<dyn BotClient>(handle) }
+}
+
+extern "C" fn client_drop(handle: Handle) {
+    unsafe { drop_handle::<dyn BotClient>(handle) }
+}
+
+extern "C" fn group_message_event_get_group(ptr: *const ()) -> PHandle {
+    event::<GroupMessageData>(ptr).inner.group.as_phandle()
+}
+
+extern "C" fn group_message_event_get_message(ptr: *const ()) -> FFIMessageChain {
+    event::<GroupMessageData>(ptr)
+        .inner
+        .message
+        .clone()
+        .into_ffi()
+}
+
+extern "C" fn group_message_event_get_sender(ptr: *const ()) -> FFIMember {
+    let data = &event::<GroupMessageData>(ptr).inner;
+    match data.sender.clone() {
+        Member::Named(named) => FFIMember {
+            is_named: true,
+            inner: ManagedCloneable::from_value(named),
+        },
+        Member::Anonymous(anonymous) => FFIMember {
+            is_named: false,
+            inner: ManagedCloneable::from_value(AnonymousMemberData {
+                group: data.group.get().clone(),
+                anonymous,
+            }),
+        },
+    }
+}
+
+// 入群与离开事件的数据结构相同, 共用同一组函数
+extern "C" fn group_member_event_get_group(ptr: *const ()) -> PHandle {
+    event::<GroupMemberData>(ptr).inner.group.as_phandle()
+}
+
+extern "C" fn group_member_event_get_member(ptr: *const ()) -> ManagedCloneable {
+    ManagedCloneable::from_value(event::<GroupMemberData>(ptr).inner.member.clone())
+}
+
+extern "C" fn group_member_event_get_operator(ptr: *const ()) -> ManagedCloneable {
+    match event::<GroupMemberData>(ptr).inner.operator.clone() {
+        Some(operator) => ManagedCloneable::from_value(operator),
+        None => unsafe { ManagedCloneable::null() },
+    }
+}
+
+extern "C" fn group_member_leave_event_is_kicked(ptr: *const ()) -> bool {
+    event::<GroupMemberData>(ptr).inner.kicked
+}
+
+extern "C" fn group_recall_event_get_group(ptr: *const ()) -> PHandle {
+    event::<GroupRecallData>(ptr).inner.group.as_phandle()
+}
+
+extern "C" fn group_recall_event_get_operator(ptr: *const ()) -> ManagedCloneable {
+    match event::<GroupRecallData>(ptr).inner.operator.clone() {
+        Some(operator) => ManagedCloneable::from_value(operator),
+        None => unsafe { ManagedCloneable::null() },
+    }
+}
+
+extern "C" fn group_recall_event_get_operator_id(ptr: *const ()) -> i64 {
+    event::<GroupRecallData>(ptr).inner.operator_id
+}
+
+extern "C" fn group_recall_event_get_sender_id(ptr: *const ()) -> i64 {
+    event::<GroupRecallData>(ptr).inner.sender
+}
+
+extern "C" fn group_recall_event_get_seqs(ptr: *const ()) -> RustVec<i32> {
+    event::<GroupRecallData>(ptr).inner.seqs.clone().into()
+}
+
+extern "C" fn group_recall_event_get_time(ptr: *const ()) -> i32 {
+    event::<GroupRecallData>(ptr).inner.time
+}
+
+fn group<'a>(group: Handle) -> &'a Arc<dyn BotGroup> {
+    unsafe { handle_ref(group) }
+}
+
+extern "C" fn group_get_id(handle: Handle) -> i64 {
+    group(handle).id()
+}
+
+extern "C" fn group_get_name(handle: Handle) -> RustStr {
+    RustStr::from(group(handle).name())
+}
+
+extern "C" fn group_get_client(handle: Handle) -> Handle {
+    into_handle(group(handle).client())
+}
+
+extern "C" fn group_get_members(handle: Handle) -> FFIFuture<RustVec<ManagedCloneable>> {
+    let members = group(handle).clone().members();
+
+    FFIFuture::from(async move {
+        members
+            .await
+            .into_iter()
+            .map(ManagedCloneable::from_value)
+            .collect::<Vec<_>>()
+            .into()
+    })
+}
+
+extern "C" fn group_find_member(handle: Handle, id: i64) -> FFIFuture<ManagedCloneable> {
+    let member = group(handle).clone().find_member(id);
+
+    FFIFuture::from(async move {
+        member
+            .await
+            .map(ManagedCloneable::from_value)
+            .unwrap_or_else(|| unsafe { ManagedCloneable::null() })
+    })
+}
+
+extern "C" fn group_send_message(
+    handle: Handle,
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let result = group(handle)
+        .clone()
+        .send_message(MessageChain::from_ffi(chain));
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(ForFFI::into_ffi)) })
+}
+
+extern "C" fn group_upload_image(
+    handle: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = group(handle).clone().upload_image(data.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Image>))
+    })
+}
+
+extern "C" fn group_upload_voice(
+    handle: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = group(handle).clone().upload_voice(data.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Voice>))
+    })
+}
+
+fn group_file_entry_into_ffi(entry: GroupFileEntry) -> FFIGroupFile {
+    match entry {
+        GroupFileEntry::Folder(folder) => FFIGroupFile {
+            is_folder: true,
+            id: folder.id.into(),
+            name: folder.name.into(),
+            folder: folder.parent.into(),
+            size: 0,
+            uploader: 0,
+            time: 0,
+            file_count: folder.file_count,
+        },
+        GroupFileEntry::File(file) => group_file_into_ffi(file),
+    }
+}
+
+fn group_file_into_ffi(file: GroupFile) -> FFIGroupFile {
+    FFIGroupFile {
+        is_folder: false,
+        id: file.id.into(),
+        name: file.name.into(),
+        folder: file.folder.into(),
+        size: file.size,
+        uploader: file.uploader,
+        time: file.upload_time,
+        file_count: 0,
+    }
+}
+
+extern "C" fn group_list_files(
+    handle: Handle,
+    folder: RustStr,
+) -> FFIFuture<FFIResult<RustVec<FFIGroupFile>>> {
+    let result = group(handle).clone().list_files(folder.to_string());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(|entries| {
+            entries
+                .into_iter()
+                .map(group_file_entry_into_ffi)
+                .collect::<Vec<_>>()
+                .into()
+        }))
+    })
+}
+
+extern "C" fn group_upload_file(
+    handle: Handle,
+    folder: RustStr,
+    name: RustStr,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<FFIGroupFile>> {
+    let result =
+        group(handle)
+            .clone()
+            .upload_file(folder.to_string(), name.to_string(), data.into_vec());
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(group_file_into_ffi)) })
+}
+
+extern "C" fn group_download_file(
+    handle: Handle,
+    id: RustStr,
+) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let result = group(handle).clone().download_file(id.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await.map(RustVec::from)) })
+}
+
+extern "C" fn group_rename_file(
+    handle: Handle,
+    id: RustStr,
+    name: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle)
+        .clone()
+        .rename_file(id.to_string(), name.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_move_file(
+    handle: Handle,
+    id: RustStr,
+    folder: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle)
+        .clone()
+        .move_file(id.to_string(), folder.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_delete_file(handle: Handle, id: RustStr) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().delete_file(id.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_get_message(
+    handle: Handle,
+    seq: i32,
+) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>> {
+    let result = group(handle).clone().get_message(seq);
+    FFIFuture::from(async move {
+        FFIResult::from(
+            result
+                .await
+                .map(|chain| FFIOption::from(chain.map(ForFFI::into_ffi))),
+        )
+    })
+}
+
+extern "C" fn group_get_history(
+    handle: Handle,
+    before_seq: i32,
+    count: u32,
+) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>> {
+    let result = group(handle).clone().history(before_seq, count);
+    FFIFuture::from(async move { FFIResult::from(result.await.map(history_into_ffi)) })
+}
+
+fn history_into_ffi(history: Vec<MessageChain>) -> RustVec<FFIMessageChain> {
+    history
+        .into_iter()
+        .map(ForFFI::into_ffi)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn group_quit(handle: Handle) -> FFIFuture<bool> {
+    FFIFuture::from(group(handle).clone().quit())
+}
+
+extern "C" fn group_change_name(handle: Handle, name: RustStr) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().change_name(name.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_send_forward_message(
+    handle: Handle,
+    msg: RustVec<FFIForwardNode>,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let result = group(handle)
+        .clone()
+        .send_forward_message(forward_from_ffi(msg));
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(ForFFI::into_ffi)) })
+}
+
+extern "C" fn group_invite(handle: Handle, id: i64) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().invite(id);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_recall(handle: Handle, receipt: FFIMessageReceipt) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle)
+        .clone()
+        .recall(MessageReceipt::from_ffi(receipt));
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_mute_all(handle: Handle, mute: bool) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().mute_all(mute);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_get_announcements(
+    handle: Handle,
+) -> FFIFuture<FFIResult<RustVec<FFIAnnouncement>>> {
+    let result = group(handle).clone().announcements();
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(|announcements| {
+            announcements
+                .into_iter()
+                .map(|a| FFIAnnouncement {
+                    fid: a.fid.into(),
+                    sender: a.sender,
+                    time: a.time,
+                    content: a.content.into(),
+                })
+                .collect::<Vec<_>>()
+                .into()
+        }))
+    })
+}
+
+extern "C" fn group_publish_announcement(
+    handle: Handle,
+    content: RustStr,
+) -> FFIFuture<FFIResult<RustString>> {
+    let result = group(handle)
+        .clone()
+        .publish_announcement(content.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await.map(RustString::from)) })
+}
+
+extern "C" fn group_delete_announcement(handle: Handle, fid: RustStr) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().delete_announcement(fid.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_set_anonymous(handle: Handle, allow: bool) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().set_anonymous(allow);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_change_avatar(handle: Handle, data: RustVec<u8>) -> FFIFuture<FFIResult<()>> {
+    let result = group(handle).clone().change_avatar(data.into_vec());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn group_get_member_count(handle: Handle) -> u32 {
+    group(handle).member_count()
+}
+
+extern "C" fn group_get_owner(handle: Handle) -> FFIFuture<ManagedCloneable> {
+    let owner = group(handle).clone().owner();
+
+    FFIFuture::from(async move {
+        owner
+            .await
+            .map(ManagedCloneable::from_value)
+            .unwrap_or_else(|| unsafe { ManagedCloneable::null() })
+    })
+}
+
+extern "C" fn group_get_settings(handle: Handle) -> FFIGroupSettings {
+    let settings = group(handle).settings();
+    FFIGroupSettings {
+        mute_all: settings.mute_all,
+        allow_anonymous: settings.allow_anonymous,
+        allow_member_invite: settings.allow_member_invite,
+    }
+}
+
+extern "C" fn group_clone(handle: Handle) -> Handle {
+    unsafe { clone_handle::<dyn BotGroup>(handle) }
+}
+
+extern "C" fn group_drop(handle: Handle) {
+    unsafe { drop_handle::<dyn BotGroup>(handle) }
+}
+
+extern "C" fn friend_message_event_get_friend(ptr: *const ()) -> PHandle {
+    event::<FriendMessageData>(ptr).inner.friend.as_phandle()
+}
+
+extern "C" fn friend_message_event_get_message(ptr: *const ()) -> FFIMessageChain {
+    event::<FriendMessageData>(ptr)
+        .inner
+        .message
+        .clone()
+        .into_ffi()
+}
+
+extern "C" fn friend_recall_event_get_friend(ptr: *const ()) -> PHandle {
+    event::<FriendRecallData>(ptr).inner.friend.as_phandle()
+}
+
+extern "C" fn friend_recall_event_get_operator_id(ptr: *const ()) -> i64 {
+    event::<FriendRecallData>(ptr).inner.operator_id
+}
+
+extern "C" fn friend_recall_event_get_seqs(ptr: *const ()) -> RustVec<i32> {
+    event::<FriendRecallData>(ptr).inner.seqs.clone().into()
+}
+
+extern "C" fn friend_recall_event_get_time(ptr: *const ()) -> i32 {
+    event::<FriendRecallData>(ptr).inner.time
+}
+
+extern "C" fn friend_recall_event_get_sender_id(ptr: *const ()) -> i64 {
+    event::<FriendRecallData>(ptr).inner.sender
+}
+
+extern "C" fn group_temp_message_event_get_group(ptr: *const ()) -> PHandle {
+    event::<GroupTempMessageData>(ptr).inner.group.as_phandle()
+}
+
+extern "C" fn group_temp_message_event_get_sender(ptr: *const ()) -> ManagedCloneable {
+    ManagedCloneable::from_value(event::<GroupTempMessageData>(ptr).inner.sender.clone())
+}
+
+extern "C" fn group_temp_message_event_get_message(ptr: *const ()) -> FFIMessageChain {
+    event::<GroupTempMessageData>(ptr)
+        .inner
+        .message
+        .clone()
+        .into_ffi()
+}
+
+extern "C" fn client_login_event_get_client(ptr: *const ()) -> PHandle {
+    event::<OwnedHandle<dyn BotClient>>(ptr).inner.as_phandle()
+}
+
+extern "C" fn new_friend_event_get_friend(ptr: *const ()) -> PHandle {
+    event::<OwnedHandle<dyn BotFriend>>(ptr).inner.as_phandle()
+}
+
+extern "C" fn delete_friend_event_get_client(ptr: *const ()) -> PHandle {
+    event::<DeleteFriendData>(ptr).inner.client.as_phandle()
+}
+
+extern "C" fn delete_friend_event_get_friend_id(ptr: *const ()) -> i64 {
+    event::<DeleteFriendData>(ptr).inner.friend_id
+}
+
+fn request_accept(request: &Arc<dyn BotRequest>) -> FFIFuture<FFIResult<()>> {
+    let result = request.clone().accept();
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+fn request_reject(
+    request: &Arc<dyn BotRequest>,
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let result = request.clone().reject(reason.as_str().to_owned(), block);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn friend_request_event_get_client(ptr: *const ()) -> PHandle {
+    event::<FriendRequestData>(ptr).inner.client.as_phandle()
+}
+
+extern "C" fn friend_request_event_get_requester_id(ptr: *const ()) -> i64 {
+    event::<FriendRequestData>(ptr).inner.requester_id
+}
+
+extern "C" fn friend_request_event_get_requester_nickname(ptr: *const ()) -> RustStr {
+    RustStr::from(
+        event::<FriendRequestData>(ptr)
+            .inner
+            .requester_nickname
+            .as_str(),
+    )
+}
+
+extern "C" fn friend_request_event_get_message(ptr: *const ()) -> RustStr {
+    RustStr::from(event::<FriendRequestData>(ptr).inner.message.as_str())
+}
+
+extern "C" fn friend_request_event_accept(ptr: *const ()) -> FFIFuture<FFIResult<()>> {
+    request_accept(&event::<FriendRequestData>(ptr).inner.request)
+}
+
+extern "C" fn friend_request_event_reject(
+    ptr: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    request_reject(
+        &event::<FriendRequestData>(ptr).inner.request,
+        reason,
+        block,
+    )
+}
+
+extern "C" fn group_invited_event_get_client(ptr: *const ()) -> PHandle {
+    event::<GroupInvitedData>(ptr).inner.client.as_phandle()
+}
+
+extern "C" fn group_invited_event_get_group_id(ptr: *const ()) -> i64 {
+    event::<GroupInvitedData>(ptr).inner.group_id
+}
+
+extern "C" fn group_invited_event_get_group_name(ptr: *const ()) -> RustStr {
+    RustStr::from(event::<GroupInvitedData>(ptr).inner.group_name.as_str())
+}
+
+extern "C" fn group_invited_event_get_inviter_id(ptr: *const ()) -> i64 {
+    event::<GroupInvitedData>(ptr).inner.inviter_id
+}
+
+extern "C" fn group_invited_event_get_inviter_nickname(ptr: *const ()) -> RustStr {
+    RustStr::from(
+        event::<GroupInvitedData>(ptr)
+            .inner
+            .inviter_nickname
+            .as_str(),
+    )
+}
+
+extern "C" fn group_invited_event_accept(ptr: *const ()) -> FFIFuture<FFIResult<()>> {
+    request_accept(&event::<GroupInvitedData>(ptr).inner.request)
+}
+
+extern "C" fn group_invited_event_reject(
+    ptr: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    request_reject(&event::<GroupInvitedData>(ptr).inner.request, reason, block)
+}
+
+extern "C" fn group_join_request_event_get_group(ptr: *const ()) -> PHandle {
+    event::<GroupJoinRequestData>(ptr).inner.group.as_phandle()
+}
+
+extern "C" fn group_join_request_event_get_requester_id(ptr: *const ()) -> i64 {
+    event::<GroupJoinRequestData>(ptr).inner.requester_id
+}
+
+extern "C" fn group_join_request_event_get_requester_nickname(ptr: *const ()) -> RustStr {
+    RustStr::from(
+        event::<GroupJoinRequestData>(ptr)
+            .inner
+            .requester_nickname
+            .as_str(),
+    )
+}
+
+extern "C" fn group_join_request_event_get_message(ptr: *const ()) -> RustStr {
+    RustStr::from(event::<GroupJoinRequestData>(ptr).inner.message.as_str())
+}
+
+extern "C" fn group_join_request_event_accept(ptr: *const ()) -> FFIFuture<FFIResult<()>> {
+    request_accept(&event::<GroupJoinRequestData>(ptr).inner.request)
+}
+
+extern "C" fn group_join_request_event_reject(
+    ptr: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    request_reject(
+        &event::<GroupJoinRequestData>(ptr).inner.request,
+        reason,
+        block,
+    )
+}
+
+fn friend<'a>(friend: Handle) -> &'a Arc<dyn BotFriend> {
+    unsafe { handle_ref(friend) }
+}
+
+extern "C" fn friend_get_id(handle: Handle) -> i64 {
+    friend(handle).id()
+}
+
+extern "C" fn friend_get_nickname(handle: Handle) -> RustStr {
+    RustStr::from(friend(handle).nickname())
+}
+
+extern "C" fn friend_get_client(handle: Handle) -> Handle {
+    into_handle(friend(handle).client())
+}
+
+extern "C" fn friend_send_message(
+    handle: Handle,
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let result = friend(handle)
+        .clone()
+        .send_message(MessageChain::from_ffi(chain));
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(ForFFI::into_ffi)) })
+}
+
+extern "C" fn friend_upload_image(
+    handle: Handle,
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = friend(handle).clone().upload_image(img.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Image>))
+    })
+}
+
+extern "C" fn friend_recall(
+    handle: Handle,
+    receipt: FFIMessageReceipt,
+) -> FFIFuture<FFIResult<()>> {
+    let result = friend(handle)
+        .clone()
+        .recall(MessageReceipt::from_ffi(receipt));
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn friend_get_history(
+    handle: Handle,
+    before_seq: i32,
+    count: u32,
+) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>> {
+    let result = friend(handle).clone().history(before_seq, count);
+    FFIFuture::from(async move { FFIResult::from(result.await.map(history_into_ffi)) })
+}
+
+extern "C" fn friend_get_message(
+    handle: Handle,
+    seq: i32,
+) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>> {
+    let result = friend(handle).clone().get_message(seq);
+    FFIFuture::from(async move {
+        FFIResult::from(
+            result
+                .await
+                .map(|chain| FFIOption::from(chain.map(ForFFI::into_ffi))),
+        )
+    })
+}
+
+extern "C" fn friend_upload_voice(
+    handle: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = friend(handle).clone().upload_voice(data.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Voice>))
+    })
+}
+
+extern "C" fn friend_clone(handle: Handle) -> Handle {
+    unsafe { clone_handle::<dyn BotFriend>(handle) }
+}
+
+extern "C" fn friend_drop(handle: Handle) {
+    unsafe { drop_handle::<dyn BotFriend>(handle) }
+}
+
+fn named_member<'a>(named: *const ()) -> &'a Arc<dyn BotMember> {
+    unsafe { &*(named as *const Arc<dyn BotMember>) }
+}
+
+extern "C" fn named_member_get_id(named: *const ()) -> i64 {
+    named_member(named).id()
+}
+
+extern "C" fn named_member_get_nickname(named: *const ()) -> RustStr {
+    RustStr::from(named_member(named).nickname())
+}
+
+extern "C" fn named_member_get_card_name(named: *const ()) -> RustStr {
+    RustStr::from(named_member(named).card_name())
+}
+
+extern "C" fn named_member_get_group(named: *const ()) -> Handle {
+    into_handle(named_member(named).group())
+}
+
+extern "C" fn named_member_change_card_name(
+    named: *const (),
+    card: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let result = named_member(named)
+        .clone()
+        .change_card_name(card.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn named_member_mute(named: *const (), seconds: u64) -> FFIFuture<FFIResult<()>> {
+    let result = named_member(named)
+        .clone()
+        .mute(Duration::from_secs(seconds));
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn named_member_kick(
+    named: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let result = named_member(named).clone().kick(reason.to_string(), block);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn named_member_set_admin(named: *const (), admin: bool) -> FFIFuture<FFIResult<()>> {
+    let result = named_member(named).clone().set_admin(admin);
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn named_member_set_special_title(
+    named: *const (),
+    title: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let result = named_member(named)
+        .clone()
+        .set_special_title(title.to_string());
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+extern "C" fn named_member_get_role(named: *const ()) -> u8 {
+    named_member(named).role() as u8
+}
+
+extern "C" fn named_member_get_join_time(named: *const ()) -> i64 {
+    named_member(named).join_time()
+}
+
+extern "C" fn named_member_get_last_speak_time(named: *const ()) -> i64 {
+    named_member(named).last_speak_time()
+}
+
+extern "C" fn named_member_get_level(named: *const ()) -> u16 {
+    named_member(named).level()
+}
+
+extern "C" fn named_member_send_message(
+    named: *const (),
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let result = named_member(named)
+        .clone()
+        .send_message(MessageChain::from_ffi(chain));
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(ForFFI::into_ffi)) })
+}
+
+extern "C" fn named_member_upload_image(
+    named: *const (),
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = named_member(named).clone().upload_image(img.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Image>))
+    })
+}
+
+fn anonymous_member<'a>(ano: *const ()) -> &'a AnonymousMemberData {
+    unsafe { &*(ano as *const AnonymousMemberData) }
+}
+
+extern "C" fn anonymous_member_get_anon_id(ano: *const ()) -> RustVec<u8> {
+    anonymous_member(ano).anonymous.anon_id.clone().into()
+}
+
+extern "C" fn anonymous_member_get_nickname(ano: *const ()) -> RustStr {
+    RustStr::from(anonymous_member(ano).anonymous.nick.as_str())
+}
+
+extern "C" fn anonymous_member_get_portrait_index(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.portrait_index
+}
+
+extern "C" fn anonymous_member_get_bubble_index(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.bubble_index
+}
+
+extern "C" fn anonymous_member_get_expire_time(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.expire_time
+}
+
+extern "C" fn anonymous_member_get_color(ano: *const ()) -> RustStr {
+    RustStr::from(anonymous_member(ano).anonymous.color.as_str())
+}
+
+extern "C" fn anonymous_member_get_group(ano: *const ()) -> Handle {
+    into_handle(anonymous_member(ano).group.clone())
+}
+
+extern "C" fn anonymous_member_mute(ano: *const (), seconds: u64) -> FFIFuture<FFIResult<()>> {
+    let data = anonymous_member(ano);
+    let result = data
+        .group
+        .clone()
+        .mute_anonymous(data.anonymous.clone(), Duration::from_secs(seconds));
+    FFIFuture::from(async move { FFIResult::from(result.await) })
+}
+
+fn stranger<'a>(stranger: *const ()) -> &'a Arc<dyn BotStranger> {
+    unsafe { &*(stranger as *const Arc<dyn BotStranger>) }
+}
+
+extern "C" fn stranger_get_id(ptr: *const ()) -> i64 {
+    stranger(ptr).id()
+}
+
+extern "C" fn stranger_get_nickname(ptr: *const ()) -> RustStr {
+    RustStr::from(stranger(ptr).nickname())
+}
+
+extern "C" fn stranger_get_client(ptr: *const ()) -> Handle {
+    into_handle(stranger(ptr).client())
+}
+
+extern "C" fn stranger_send_message(
+    ptr: *const (),
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let result = stranger(ptr)
+        .clone()
+        .send_message(MessageChain::from_ffi(chain));
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(ForFFI::into_ffi)) })
+}
+
+extern "C" fn stranger_upload_image(
+    ptr: *const (),
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let result = stranger(ptr).clone().upload_image(img.into_vec());
+
+    FFIFuture::from(async move {
+        FFIResult::from(result.await.map(ManagedCloneable::from_value::<Image>))
+    })
+}
+
+fn image<'a>(img: *const ()) -> &'a Image {
+    unsafe { &*(img as *const Image) }
+}
+
+extern "C" fn image_get_id(img: *const ()) -> RustStr {
+    RustStr::from(image(img).id())
+}
+
+extern "C" fn image_get_url(img: *const ()) -> RustString {
+    RustString::from(image(img).url())
+}
+
+fn voice<'a>(voice: *const ()) -> &'a Voice {
+    unsafe { &*(voice as *const Voice) }
+}
+
+extern "C" fn voice_get_url(ptr: *const ()) -> RustString {
+    RustString::from(voice(ptr).url())
+}
+
+extern "C" fn voice_get_md5(ptr: *const ()) -> RustVec<u8> {
+    RustVec::from(voice(ptr).md5().to_vec())
+}
+
+extern "C" fn voice_get_size(ptr: *const ()) -> u32 {
+    voice(ptr).size()
+}
+
+extern "C" fn voice_get_duration(ptr: *const ()) -> u64 {
+    voice(ptr).duration().as_secs()
+}
+
+extern "C" fn voice_download(ptr: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let result = voice(ptr).clone().download();
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(RustVec::from)) })
+}
+
+fn video<'a>(video: *const ()) -> &'a Video {
+    unsafe { &*(video as *const Video) }
+}
+
+extern "C" fn video_get_name(ptr: *const ()) -> RustStr {
+    RustStr::from(video(ptr).name())
+}
+
+extern "C" fn video_get_size(ptr: *const ()) -> u64 {
+    video(ptr).size()
+}
+
+extern "C" fn video_get_url(ptr: *const ()) -> RustString {
+    RustString::from(video(ptr).url())
+}
+
+extern "C" fn video_download(ptr: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let result = video(ptr).clone().download();
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(RustVec::from)) })
+}
+
+fn file<'a>(file: *const ()) -> &'a File {
+    unsafe { &*(file as *const File) }
+}
+
+extern "C" fn file_get_name(ptr: *const ()) -> RustStr {
+    RustStr::from(file(ptr).name())
+}
+
+extern "C" fn file_get_size(ptr: *const ()) -> u64 {
+    file(ptr).size()
+}
+
+extern "C" fn file_get_url(ptr: *const ()) -> RustString {
+    RustString::from(file(ptr).url())
+}
+
+extern "C" fn file_download(ptr: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let result = file(ptr).clone().download();
+
+    FFIFuture::from(async move { FFIResult::from(result.await.map(RustVec::from)) })
+}
+
+extern "C" fn log(handle: usize, manager_ptr: *const (), level: u8, log: RustStr) {
+    let state = manager(manager_ptr);
+    state.backend.log(
+        &state.plugin_name(handle),
+        LogLevel::from_u8(level),
+        log.as_str(),
+    );
+}
+
+extern "C" fn env_get_workspace(handle: usize, manager_ptr: *const ()) -> RustString {
+    let state = manager(manager_ptr);
+    let workspace = state
+        .workspaces
+        .read()
+        .unwrap()
+        .join(state.plugin_name(handle));
+
+    if let Err(e) = std::fs::create_dir_all(&workspace) {
+        state.backend.log(
+            &state.plugin_name(handle),
+            LogLevel::Warn,
+            &format!("无法创建工作目录{}: {}", workspace.display(), e),
+        );
+    }
+
+    RustString::from(workspace.to_string_lossy().into_owned())
+}
+
+extern "C" fn message_chain_to_json(chain: FFIMessageChain) -> RustString {
+    RustString::from(MessageChain::from_ffi(chain).to_json())
+}
+
+extern "C" fn message_chain_from_json(json: RustStr) -> FFIResult<FFIMessageChain> {
+    FFIResult::from(MessageChain::from_json(json.as_str()).map(ForFFI::into_ffi))
+}
+
+/// 插件无法识别的元素交由宿主保管, 以便原样发送
+extern "C" fn message_element_wrap(elem: FFIMessageElement) -> ManagedCloneable {
+    ManagedCloneable::from_value(MessageElement::from_ffi(elem))
+}
+
+extern "C" fn message_element_unwrap(elem: *const ()) -> FFIMessageElement {
+    let elem = unsafe { &*(elem as *const MessageElement) };
+    elem.clone().into_ffi()
+}
+
+extern "C" fn message_element_to_json(elem: *const ()) -> RustString {
+    let elem = unsafe { &*(elem as *const MessageElement) };
+    RustString::from(elem.to_json().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_fun, SIGNATURES};
+
+    #[test]
+    fn signatures() {
+        // 宿主与插件各自维护函数表, 新增或修改签名时须同步
+        assert_eq!(SIGNATURES, atri_plugin::loader::SIGNATURES);
+        assert!(SIGNATURES.iter().all(|&sig| !get_fun(sig).is_null()));
+        assert!(get_fun(u16::MAX).is_null());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::plugin::{str_list, str_lit};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Error, Expr, ExprLit, FnArg, ItemFn, Lit, LitStr, Meta, MetaNameValue, Pat, Result, Token,
+    Visibility,
+};
+
+const KEYS: &[&str] = &["name", "aliases", "description", "permission"];
+
+#[derive(Default)]
+struct CommandAttrs {
+    name: Option<LitStr>,
+    aliases: Option<Vec<LitStr>>,
+    description: Option<LitStr>,
+    permission: Option<LitStr>,
+}
+
+impl CommandAttrs {
+    fn parse(attr: TokenStream) -> Result<Self> {
+        let args = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse2(attr)?;
+
+        let mut attrs = Self::default();
+        for arg in args {
+            let key = arg.path.get_ident().map(ToString::to_string);
+            let value = &arg.value;
+
+            macro_rules! set {
+                ($field:ident, $parse:expr) => {{
+                    if attrs.$field.is_some() {
+                        return Err(Error::new(
+                            arg.path.span(),
+                            concat!("duplicate key `", stringify!($field), "`"),
+                        ));
+                    }
+                    attrs.$field = Some($parse(value)?);
+                }};
+            }
+
+            match key.as_deref() {
+                Some("name") => set!(name, str_lit),
+                Some("aliases") => set!(aliases, str_list),
+                Some("description") => set!(description, str_lit),
+                Some("permission") => set!(permission, str_lit),
+                _ => {
+                    return Err(Error::new(
+                        arg.path.span(),
+                        format!("unknown key, expected one of: {}", KEYS.join(", ")),
+                    ))
+                }
+            }
+        }
+
+        for lit in attrs.name.iter().chain(attrs.aliases.iter().flatten()) {
+            let value = lit.value();
+            if value.is_empty() || value.contains(char::is_whitespace) {
+                return Err(Error::new(
+                    lit.span(),
+                    "command name cannot be empty or contain whitespace",
+                ));
+            }
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// 取文档注释的第一行作为命令描述
+fn doc_description(func: &ItemFn) -> Option<String> {
+    func.attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(MetaNameValue {
+            path,
+            value: Expr::Lit(ExprLit {
+                lit: Lit::Str(doc), ..
+            }),
+            ..
+        }) if path.is_ident("doc") => {
+            let line = doc.value().trim().to_owned();
+            (!line.is_empty()).then_some(line)
+        }
+        _ => None,
+    })
+}
+
+pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let attrs = CommandAttrs::parse(attr)?;
+    let func: ItemFn = syn::parse2(item)?;
+    let sig = &func.sig;
+
+    if sig.asyncness.is_none() {
+        return Err(Error::new(
+            sig.fn_token.span,
+            "command function must be async",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "command function cannot be generic",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Typed(_)) => {}
+        Some(FnArg::Receiver(r)) => {
+            return Err(Error::new(r.span(), "command function cannot take `self`"))
+        }
+        None => {
+            return Err(Error::new(
+                sig.paren_token.span.join(),
+                "the first parameter must be `CommandContext`",
+            ))
+        }
+    }
+
+    let mut arg_idents = vec![];
+    let mut arg_types = vec![];
+    let mut arg_names = vec![];
+    for (i, input) in inputs.enumerate() {
+        let FnArg::Typed(pat) = input else {
+            unreachable!("receiver can only be the first parameter");
+        };
+
+        let name = match &*pat.pat {
+            Pat::Ident(ident) => ident.ident.to_string().trim_start_matches('_').to_owned(),
+            _ => format!("arg{}", i),
+        };
+
+        arg_idents.push(format_ident!("__arg{}", i));
+        arg_types.push(&pat.ty);
+        arg_names.push(name);
+    }
+
+    let ident = &sig.ident;
+    let name = attrs
+        .name
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let aliases = attrs.aliases.unwrap_or_default();
+    let description = attrs
+        .description
+        .map(|d| d.value())
+        .or_else(|| doc_description(&func))
+        .unwrap_or_default();
+    let permission = attrs.permission.map(|p| quote!(.permission(#p)));
+
+    // 文档注释留在生成的函数上, 其余属性留在命令的处理函数上
+    let (docs, others): (Vec<_>, Vec<_>) = func
+        .attrs
+        .iter()
+        .cloned()
+        .partition(|attr| attr.path().is_ident("doc"));
+
+    let vis = &func.vis;
+    let mut handler = func.clone();
+    handler.attrs = others;
+    handler.vis = Visibility::Inherited;
+    handler.sig.ident = format_ident!("__command");
+
+    let args = if arg_idents.is_empty() {
+        quote!(args)
+    } else {
+        quote!(mut args)
+    };
+
+    Ok(quote! {
+        #(#docs)*
+        #vis fn #ident() -> atri_plugin::command::Command {
+            #handler
+
+            atri_plugin::command::Command::new(#name, |ctx, #args| async move {
+                #(let #arg_idents: #arg_types = args.parse()?;)*
+                args.finish()?;
+                atri_plugin::command::IntoCommandResult::into_command_result(
+                    __command(ctx, #(#arg_idents),*).await,
+                )
+            })
+            #(.alias(#aliases))*
+            .description(#description)
+            #permission
+            #(.arg::<#arg_types>(#arg_names))*
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+    use quote::quote;
+
+    #[test]
+    fn accepted() {
+        let out = expand(
+            quote!(aliases = ["mute"], permission = "admin"),
+            quote! {
+                /// 禁言成员
+                pub async fn ban(ctx: CommandContext, target: At, _minutes: u32) {}
+            },
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(out.contains("pub fn ban () -> atri_plugin :: command :: Command"));
+        assert!(out.contains("Command :: new (\"ban\""));
+        assert!(out.contains("let __arg1 : u32 = args . parse () ?"));
+        assert!(out.contains("args . finish () ?"));
+        assert!(out.contains(". alias (\"mute\")"));
+        assert!(out.contains(". description (\"禁言成员\")"));
+        assert!(out.contains(". permission (\"admin\")"));
+        assert!(out.contains(". arg :: < u32 > (\"minutes\")"));
+    }
+
+    #[test]
+    fn rejected() {
+        let error = |attr, item| expand(attr, item).unwrap_err().to_string();
+
+        assert!(error(
+            quote!(),
+            quote!(
+                fn a(ctx: C) {}
+            )
+        )
+        .contains("async"));
+        assert!(error(
+            quote!(),
+            quote!(
+                async fn a() {}
+            )
+        )
+        .contains("CommandContext"));
+        assert!(error(
+            quote!(),
+            quote!(
+                async fn a<T>(ctx: T) {}
+            )
+        )
+        .contains("generic"));
+        assert!(error(
+            quote!(name = "a b"),
+            quote!(
+                async fn a(c: C) {}
+            )
+        )
+        .contains("whitespace"));
+        assert!(error(
+            quote!(perm = "x"),
+            quote!(
+                async fn a(c: C) {}
+            )
+        )
+        .contains("unknown key"));
+    }
+}
@@ -0,0 +1,289 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Error, Expr, ExprArray, Item, Lit, LitStr, MetaNameValue, Path, Result, Token};
+
+const KEYS: &[&str] = &[
+    "name",
+    "version",
+    "authors",
+    "description",
+    "homepage",
+    "depends",
+    "commands",
+];
+
+#[derive(Default)]
+struct PluginAttrs {
+    name: Option<LitStr>,
+    version: Option<LitStr>,
+    authors: Option<Vec<LitStr>>,
+    description: Option<LitStr>,
+    homepage: Option<LitStr>,
+    depends: Option<Vec<LitStr>>,
+    commands: Option<Vec<Path>>,
+}
+
+impl PluginAttrs {
+    fn parse(attr: TokenStream) -> Result<Self> {
+        let args = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse2(attr)?;
+
+        let mut attrs = Self::default();
+        for arg in args {
+            let key = arg.path.get_ident().map(ToString::to_string);
+            let value = &arg.value;
+
+            macro_rules! set {
+                ($field:ident, $parse:expr) => {{
+                    if attrs.$field.is_some() {
+                        return Err(Error::new(
+                            arg.path.span(),
+                            concat!("duplicate key `", stringify!($field), "`"),
+                        ));
+                    }
+                    attrs.$field = Some($parse(value)?);
+                }};
+            }
+
+            match key.as_deref() {
+                Some("name") => set!(name, str_lit),
+                Some("version") => set!(version, str_lit),
+                Some("authors") => set!(authors, str_list),
+                Some("description") => set!(description, str_lit),
+                Some("homepage") => set!(homepage, str_lit),
+                Some("depends") => set!(depends, str_list),
+                Some("commands") => set!(commands, path_list),
+                _ => {
+                    return Err(Error::new(
+                        arg.path.span(),
+                        format!("unknown key, expected one of: {}", KEYS.join(", ")),
+                    ))
+                }
+            }
+        }
+
+        if let Some(name) = &attrs.name {
+            if name.value().trim().is_empty() {
+                return Err(Error::new(name.span(), "plugin name cannot be empty"));
+            }
+        }
+
+        for dep in attrs.depends.iter().flatten() {
+            let value = dep.value();
+            let name = value.split([' ', '=', '<', '>', '^']).next().unwrap_or("");
+            if name.is_empty() {
+                return Err(Error::new(
+                    dep.span(),
+                    "expected a dependency like `other_plugin >= 1.2`",
+                ));
+            }
+        }
+
+        Ok(attrs)
+    }
+}
+
+pub(crate) fn str_lit(expr: &Expr) -> Result<LitStr> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => Ok(s.clone()),
+            _ => Err(Error::new(lit.span(), "expected a string literal")),
+        },
+        _ => Err(Error::new(expr.span(), "expected a string literal")),
+    }
+}
+
+/// 接受单个字符串或字符串数组
+pub(crate) fn str_list(expr: &Expr) -> Result<Vec<LitStr>> {
+    match expr {
+        Expr::Array(ExprArray { elems, .. }) => elems.iter().map(str_lit).collect(),
+        _ => str_lit(expr).map(|s| vec![s]),
+    }
+}
+
+/// 接受单个路径或路径数组
+fn path_list(expr: &Expr) -> Result<Vec<Path>> {
+    let path = |expr: &Expr| match expr {
+        Expr::Path(p) => Ok(p.path.clone()),
+        _ => Err(Error::new(
+            expr.span(),
+            "expected a function marked with `#[command]`",
+        )),
+    };
+
+    match expr {
+        Expr::Array(ExprArray { elems, .. }) => elems.iter().map(path).collect(),
+        _ => path(expr).map(|p| vec![p]),
+    }
+}
+
+pub fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let attrs = PluginAttrs::parse(attr)?;
+    let item: Item = syn::parse2(item)?;
+
+    let (ident, generics) = match &item {
+        Item::Struct(s) => (&s.ident, &s.generics),
+        Item::Enum(e) => (&e.ident, &e.generics),
+        Item::Union(u) => {
+            return Err(Error::new(
+                u.union_token.span,
+                "union is not supported, use a struct or an enum instead",
+            ))
+        }
+        other => {
+            return Err(Error::new(
+                other.span(),
+                "`#[plugin]` can only be applied to a struct or an enum",
+            ))
+        }
+    };
+
+    if !generics.params.is_empty() {
+        return Err(Error::new(generics.span(), "plugin type cannot be generic"));
+    }
+
+    let name = attrs
+        .name
+        .unwrap_or_else(|| LitStr::new(&format!("{}_plugin", ident), ident.span()));
+
+    let or_env = |lit: Option<LitStr>, env: &str| match lit {
+        Some(lit) => quote!(#lit),
+        None => quote!(env!(#env)),
+    };
+    let version = or_env(attrs.version, "CARGO_PKG_VERSION");
+    let description = or_env(attrs.description, "CARGO_PKG_DESCRIPTION");
+    let homepage = or_env(attrs.homepage, "CARGO_PKG_HOMEPAGE");
+    let authors = match attrs.authors {
+        Some(authors) => quote!(&[#(#authors),*]),
+        None => quote!(&[env!("CARGO_PKG_AUTHORS")]),
+    };
+    let depends = attrs.depends.unwrap_or_default();
+    let commands = attrs.commands.unwrap_or_default();
+
+    let span = ident.span();
+    let assert_plugin = quote_spanned! {span=>
+        const _: () = {
+            fn assert_plugin<P: atri_plugin::Plugin>() {}
+            let _ = assert_plugin::<#ident>;
+        };
+    };
+
+    // `#[macro_export]`的宏总是位于crate根, 无论插件位于哪个模块,
+    // 出现多个插件时该宏都会重复定义, 错误指向第二个插件
+    let guard = quote_spanned! {span=>
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! only_one_type_can_be_marked_as_plugin_in_a_crate {
+            () => {};
+        }
+    };
+
+    Ok(quote! {
+        #item
+
+        #assert_plugin
+
+        #guard
+
+        #[no_mangle]
+        extern "C" fn on_init() -> atri_plugin::PluginInstance {
+            atri_plugin::__get_instance::<#ident>(#name, || ::std::vec![#(#commands()),*])
+        }
+
+        #[no_mangle]
+        extern "C" fn plugin_info() -> atri_plugin::PluginInfo {
+            atri_plugin::__get_info(&atri_plugin::__PluginMeta {
+                version: #version,
+                authors: #authors,
+                description: #description,
+                homepage: #homepage,
+                depends: &[#(#depends),*],
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+    use quote::quote;
+
+    fn error(attr: proc_macro2::TokenStream, item: proc_macro2::TokenStream) -> String {
+        expand(attr, item).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn accepted() {
+        let out = expand(
+            quote!(
+                name = "my",
+                authors = "A",
+                depends = ["other >= 1.2"],
+                commands = [ban, admin::kick]
+            ),
+            quote! {
+                /// a struct in docs
+                pub(crate) struct MyPlugin {
+                    field: u8,
+                }
+            },
+        )
+        .unwrap()
+        .to_string();
+
+        assert!(out.contains("__get_instance :: < MyPlugin > (\"my\""));
+        assert!(out.contains("authors : & [\"A\"]"));
+        assert!(out.contains("env ! (\"CARGO_PKG_VERSION\")"));
+        assert!(out.contains("vec ! [ban () , admin :: kick ()]"));
+        assert!(out.contains("macro_rules ! only_one_type_can_be_marked_as_plugin_in_a_crate"));
+    }
+
+    #[test]
+    fn rejected() {
+        assert!(error(
+            quote!(nmae = "x"),
+            quote!(
+                struct A;
+            )
+        )
+        .starts_with("unknown key"));
+        assert!(error(
+            quote!(name = 1),
+            quote!(
+                struct A;
+            )
+        )
+        .contains("string literal"));
+        assert!(error(
+            quote!(name = "a", name = "b"),
+            quote!(
+                struct A;
+            )
+        )
+        .contains("duplicate"));
+        assert!(error(
+            quote!(),
+            quote!(
+                struct A<T>(T);
+            )
+        )
+        .contains("generic"));
+        assert!(error(quote!(), quote!(union A { a: u8 })).contains("union"));
+        assert!(error(
+            quote!(),
+            quote!(
+                fn a() {}
+            )
+        )
+        .contains("struct or an enum"));
+        assert!(error(
+            quote!(depends = [">= 1"]),
+            quote!(
+                struct A;
+            )
+        )
+        .contains("dependency"));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::contact::friend::Friend;
 use crate::contact::group::Group;
-use crate::loader::get_vtb;
+use crate::contact::stranger::Stranger;
+use crate::loader::{capabilities, get_plugin_manager, get_vtb};
 use atri_ffi::Handle;
 use std::fmt::{Display, Formatter};
 
@@ -18,13 +19,21 @@ impl Client {
     }
 
     pub fn list() -> Vec<Client> {
-        let raw = (get_vtb().client_get_list)();
+        let raw = if capabilities().client_get_list_with_manager {
+            (get_vtb().client_get_list_with_manager)(get_plugin_manager())
+        } else {
+            (get_vtb().client_get_list)()
+        };
 
         raw.into_vec().into_iter().map(Client).collect()
     }
 
     pub fn find(id: i64) -> Option<Self> {
-        let handle = (get_vtb().find_client)(id);
+        let handle = if capabilities().find_client_with_manager {
+            (get_vtb().find_client_with_manager)(get_plugin_manager(), id)
+        } else {
+            (get_vtb().find_client)(id)
+        };
 
         if handle.is_null() {
             None
@@ -53,6 +62,14 @@ impl Client {
         }
     }
 
+    /// 查找陌生人, 需要向服务器查询
+    pub async fn find_stranger(&self, id: i64) -> Option<Stranger> {
+        let fu = { (get_vtb().client_find_stranger)(self.0, id) };
+        let ma = crate::runtime::spawn(fu).await.unwrap();
+
+        Stranger::from_nullable(ma)
+    }
+
     pub fn groups(&self) -> Vec<Group> {
         let ma = (get_vtb().client_get_groups)(self.0);
         ma.into_vec().into_iter().map(Group).collect()
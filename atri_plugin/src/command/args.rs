@@ -0,0 +1,342 @@
+use crate::message::at::At;
+use crate::message::{MessageChain, MessageElement};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+/// 命令参数, 按顺序依次读取
+///
+/// 文本以空白分隔, 可使用双引号包含空白, 引号内可用`\"`与`\\`转义;
+/// 非文本的消息元素(如`At`)各自作为一个参数
+pub struct Args {
+    elements: VecDeque<MessageElement>,
+    index: usize,
+}
+
+/// 未解析的单个参数
+pub enum RawArg {
+    Text(String),
+    Element(MessageElement),
+}
+
+impl Display for RawArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(s) => f.write_str(s),
+            Self::Element(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl Args {
+    pub fn new<I: IntoIterator<Item = MessageElement>>(elements: I) -> Self {
+        Self {
+            elements: elements.into_iter().collect(),
+            index: 0,
+        }
+    }
+
+    /// 读取下一个参数
+    pub fn parse<T: FromArg>(&mut self) -> Result<T, ArgError> {
+        let arg = T::from_args(self);
+        self.index += 1;
+        arg
+    }
+
+    /// 已读取的参数数量
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 是否没有剩余的参数
+    pub fn is_empty(&self) -> bool {
+        self.elements
+            .iter()
+            .all(|e| matches!(e, MessageElement::Text(text) if text.trim().is_empty()))
+    }
+
+    /// 确认所有参数均已读取, 否则返回[`ArgError::Unexpected`]
+    pub fn finish(&self) -> Result<(), ArgError> {
+        let found = self.elements.iter().find_map(|e| match e {
+            MessageElement::Text(text) => {
+                let text = text.trim_start();
+                (!text.is_empty()).then(|| split_word(text).0)
+            }
+            e => Some(e.to_string()),
+        });
+
+        match found {
+            Some(found) => Err(ArgError::Unexpected {
+                index: self.index,
+                found,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 取出下一个未解析的参数
+    pub fn next_raw(&mut self) -> Option<RawArg> {
+        self.trim_start();
+
+        match self.elements.pop_front()? {
+            MessageElement::Text(text) => {
+                let (word, rest) = split_word(&text);
+                if !rest.is_empty() {
+                    self.elements
+                        .push_front(MessageElement::Text(rest.to_owned()));
+                }
+                Some(RawArg::Text(word))
+            }
+            e => Some(RawArg::Element(e)),
+        }
+    }
+
+    /// 取出剩余的所有消息元素
+    pub fn rest(&mut self) -> Vec<MessageElement> {
+        self.trim_start();
+        let mut rest: Vec<_> = self.elements.drain(..).collect();
+        if let Some(MessageElement::Text(text)) = rest.last_mut() {
+            let len = text.trim_end().len();
+            text.truncate(len);
+        }
+        rest
+    }
+
+    /// 参数缺失
+    pub fn missing<T: FromArg>(&self) -> ArgError {
+        ArgError::Missing {
+            index: self.index,
+            expected: T::KIND,
+        }
+    }
+
+    /// 参数无法解析
+    pub fn invalid<T: FromArg>(&self, found: &RawArg) -> ArgError {
+        ArgError::Invalid {
+            index: self.index,
+            expected: T::KIND,
+            found: found.to_string(),
+        }
+    }
+
+    /// 读取单个参数并转换
+    pub fn parse_with<T, F>(&mut self, f: F) -> Result<T, ArgError>
+    where
+        T: FromArg,
+        F: FnOnce(&RawArg) -> Option<T>,
+    {
+        let raw = self.next_raw().ok_or_else(|| self.missing::<T>())?;
+        f(&raw).ok_or_else(|| self.invalid::<T>(&raw))
+    }
+
+    fn trim_start(&mut self) {
+        while let Some(MessageElement::Text(text)) = self.elements.front_mut() {
+            let trimmed = text.trim_start();
+            if trimmed.is_empty() {
+                self.elements.pop_front();
+            } else {
+                if trimmed.len() != text.len() {
+                    *text = trimmed.to_owned();
+                }
+                break;
+            }
+        }
+    }
+}
+
+/// 分出第一个参数, 文本需已去除前导空白
+fn split_word(text: &str) -> (String, &str) {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let mut word = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return (word, &quoted[i + 1..]),
+                '\\' => match chars.next() {
+                    Some((_, c @ ('"' | '\\'))) => word.push(c),
+                    Some((_, c)) => {
+                        word.push('\\');
+                        word.push(c);
+                    }
+                    None => word.push('\\'),
+                },
+                c => word.push(c),
+            }
+        }
+
+        // 引号未闭合时取至末尾
+        return (word, "");
+    }
+
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    (text[..end].to_owned(), &text[end..])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    Missing {
+        index: usize,
+        expected: &'static str,
+    },
+    Invalid {
+        index: usize,
+        expected: &'static str,
+        found: String,
+    },
+    /// 读取所有参数后仍有剩余
+    Unexpected { index: usize, found: String },
+}
+
+impl ArgError {
+    /// 出错的参数位置
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Missing { index, .. }
+            | Self::Invalid { index, .. }
+            | Self::Unexpected { index, .. } => *index,
+        }
+    }
+}
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { expected, .. } => write!(f, "缺少参数, 需要{}", expected),
+            Self::Invalid {
+                expected, found, ..
+            } => write!(f, "无效的参数`{}`, 需要{}", found, expected),
+            Self::Unexpected { found, .. } => write!(f, "多余的参数`{}`", found),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// 可从命令参数中解析的类型
+pub trait FromArg: Sized {
+    /// 显示在帮助信息中的类型名
+    const KIND: &'static str;
+
+    /// 是否可以省略
+    const OPTIONAL: bool = false;
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError>;
+}
+
+macro_rules! from_str_impl {
+    ($kind:literal: $($t:ty)*) => {
+        $(
+        impl FromArg for $t {
+            const KIND: &'static str = $kind;
+
+            fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+                args.parse_with(|raw| match raw {
+                    RawArg::Text(s) => s.parse().ok(),
+                    RawArg::Element(_) => None,
+                })
+            }
+        }
+        )*
+    };
+}
+
+from_str_impl! {"整数": i8 i16 i32 i64 isize u8 u16 u32 u64 usize}
+from_str_impl! {"数字": f32 f64}
+
+impl FromArg for bool {
+    const KIND: &'static str = "开关(on/off)";
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        args.parse_with(|raw| match raw {
+            RawArg::Text(s) => match &*s.to_lowercase() {
+                "true" | "on" | "yes" | "开" | "是" => Some(true),
+                "false" | "off" | "no" | "关" | "否" => Some(false),
+                _ => None,
+            },
+            RawArg::Element(_) => None,
+        })
+    }
+}
+
+impl FromArg for String {
+    const KIND: &'static str = "文本";
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        args.parse_with(|raw| match raw {
+            RawArg::Text(s) => Some(s.clone()),
+            RawArg::Element(_) => None,
+        })
+    }
+}
+
+/// 接受`At`消息元素, 或以文本给出的QQ号(可带`@`前缀)
+impl FromArg for At {
+    const KIND: &'static str = "@成员";
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        args.parse_with(|raw| match raw {
+            RawArg::Element(MessageElement::At(at)) => Some(at.clone()),
+            RawArg::Text(s) => s
+                .strip_prefix('@')
+                .unwrap_or(s)
+                .parse()
+                .ok()
+                .map(|target| At {
+                    target,
+                    display: String::new(),
+                }),
+            RawArg::Element(_) => None,
+        })
+    }
+}
+
+/// 剩余的所有参数, 以文本形式给出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rest(pub String);
+
+impl FromArg for Rest {
+    const KIND: &'static str = "文本...";
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        let rest = args.rest();
+        if rest.is_empty() {
+            return Err(args.missing::<Self>());
+        }
+
+        Ok(Self(rest.iter().map(ToString::to_string).collect()))
+    }
+}
+
+/// 剩余的所有参数, 保留图片等消息元素
+impl FromArg for MessageChain {
+    const KIND: &'static str = "消息";
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        let rest = args.rest();
+        if rest.is_empty() {
+            return Err(args.missing::<Self>());
+        }
+
+        Ok(rest.into_iter().collect())
+    }
+}
+
+/// 参数缺失或无法解析时为`None`, 无法解析的参数将留给下一个参数读取
+impl<T: FromArg> FromArg for Option<T> {
+    const KIND: &'static str = T::KIND;
+    const OPTIONAL: bool = true;
+
+    fn from_args(args: &mut Args) -> Result<Self, ArgError> {
+        if args.is_empty() {
+            return Ok(None);
+        }
+
+        let elements = args.elements.clone();
+        match T::from_args(args) {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => {
+                args.elements = elements;
+                Ok(None)
+            }
+        }
+    }
+}
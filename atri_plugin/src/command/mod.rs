@@ -0,0 +1,595 @@
+mod args;
+
+pub use args::*;
+
+use crate::contact::HasSubject;
+use crate::error::{AtriError, AtriResult};
+use crate::event::MessageEvent;
+use crate::listener::{ListenerBuilder, ListenerGuard};
+use crate::message::meta::MessageReceipt;
+use crate::message::{MessageChain, MessageElement};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Write};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+type Handler = Box<
+    dyn Fn(CommandContext, Args) -> Pin<Box<dyn Future<Output = CommandResult> + Send>>
+        + Send
+        + Sync,
+>;
+
+type Checker = Box<dyn Fn(&CommandContext) -> bool + Send + Sync>;
+
+pub type CommandResult = Result<(), CommandError>;
+
+/// 命令处理函数的返回值, 用于`#[command]`
+pub trait IntoCommandResult {
+    fn into_command_result(self) -> CommandResult;
+}
+
+impl IntoCommandResult for () {
+    fn into_command_result(self) -> CommandResult {
+        Ok(())
+    }
+}
+
+impl<E: Into<CommandError>> IntoCommandResult for Result<(), E> {
+    fn into_command_result(self) -> CommandResult {
+        self.map_err(Into::into)
+    }
+}
+
+/// 命令执行失败的原因
+#[derive(Debug)]
+pub enum CommandError {
+    /// 参数错误, 将回复错误信息与命令用法
+    Arg(ArgError),
+    /// 调用接口失败, 将记录日志
+    Atri(AtriError),
+    /// 直接回复给发送者的错误信息
+    Message(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Arg(e) => Display::fmt(e, f),
+            Self::Atri(e) => Display::fmt(e, f),
+            Self::Message(s) => f.write_str(s),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<ArgError> for CommandError {
+    fn from(e: ArgError) -> Self {
+        Self::Arg(e)
+    }
+}
+
+impl From<AtriError> for CommandError {
+    fn from(e: AtriError) -> Self {
+        Self::Atri(e)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(s: String) -> Self {
+        Self::Message(s)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(s: &str) -> Self {
+        Self::Message(s.to_owned())
+    }
+}
+
+/// 参数的帮助信息
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub kind: &'static str,
+    pub optional: bool,
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.optional {
+            write!(f, "[{}: {}]", self.name, self.kind)
+        } else {
+            write!(f, "<{}: {}>", self.name, self.kind)
+        }
+    }
+}
+
+pub struct Command {
+    name: String,
+    aliases: Vec<String>,
+    description: String,
+    permission: Option<String>,
+    params: Vec<Param>,
+    handler: Handler,
+}
+
+impl Command {
+    /// 处理器按顺序调用[`Args::parse`]读取参数, 参数需同时以[`Command::arg`]声明以生成帮助信息;
+    /// 读取完毕后可调用[`Args::finish`]拒绝多余的参数
+    ///
+    /// ```rust,ignore
+    /// Command::new("ban", |ctx, mut args| async move {
+    ///     let target: At = args.parse()?;
+    ///     let minutes: u32 = args.parse()?;
+    ///     args.finish()?;
+    ///     ctx.reply(format!("已禁言{}分钟", minutes)).await?;
+    ///     Ok(())
+    /// })
+    /// .alias("mute")
+    /// .arg::<At>("target")
+    /// .arg::<u32>("minutes");
+    /// ```
+    pub fn new<S, F, Fu>(name: S, handler: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(CommandContext, Args) -> Fu,
+        F: Send + Sync + 'static,
+        Fu: Future<Output = CommandResult>,
+        Fu: Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            aliases: vec![],
+            description: String::new(),
+            permission: None,
+            params: vec![],
+            handler: Box::new(move |ctx, args| Box::pin(handler(ctx, args))),
+        }
+    }
+
+    pub fn alias<S: Into<String>>(mut self, alias: S) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// 执行命令所需的权限, 由[`CommandRegistry::permission`]定义
+    pub fn permission<S: Into<String>>(mut self, permission: S) -> Self {
+        self.permission = Some(permission.into());
+        self
+    }
+
+    /// 声明一个参数
+    pub fn arg<T: FromArg>(self, name: &str) -> Self {
+        self.param(Param {
+            name: name.to_owned(),
+            kind: T::KIND,
+            optional: T::OPTIONAL,
+        })
+    }
+
+    pub fn param(mut self, param: Param) -> Self {
+        self.params.push(param);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    fn matches(&self, label: &str) -> bool {
+        self.name == label || self.aliases.iter().any(|a| a == label)
+    }
+
+    /// 命令的用法, 如`/ban <target: @成员> <minutes: 整数>`
+    pub fn usage(&self, prefix: &str) -> String {
+        let mut s = format!("{}{}", prefix, self.name);
+        for param in &self.params {
+            let _ = write!(s, " {}", param);
+        }
+        s
+    }
+}
+
+/// 命令的执行环境
+#[derive(Clone)]
+pub struct CommandContext {
+    event: MessageEvent,
+    prefix: String,
+    label: String,
+}
+
+impl CommandContext {
+    pub fn event(&self) -> &MessageEvent {
+        &self.event
+    }
+
+    /// 触发命令的前缀
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 触发命令的名称或别名
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn sender_id(&self) -> i64 {
+        self.event.sender_id()
+    }
+
+    /// 向命令来源(群, 好友或临时会话)发送消息
+    pub async fn reply<M: Into<MessageChain>>(&self, msg: M) -> AtriResult<MessageReceipt> {
+        self.event.subject().send_message(msg).await
+    }
+}
+
+/// 命令注册表, 可同时处理群消息与好友消息
+///
+/// 未注册名为`help`的命令时, 将自动响应`help`与`help <命令>`
+pub struct CommandRegistry {
+    prefixes: Vec<String>,
+    commands: Vec<Command>,
+    permissions: HashMap<String, Checker>,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandRegistry {
+    /// 默认前缀为`/`
+    pub fn new() -> Self {
+        Self {
+            prefixes: vec![],
+            commands: vec![],
+            permissions: HashMap::new(),
+        }
+    }
+
+    /// 添加前缀, 添加后不再使用默认前缀
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    /// 定义权限, 需要未定义权限的命令将拒绝所有人执行
+    pub fn permission<S, F>(mut self, name: S, checker: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&CommandContext) -> bool,
+        F: Send + Sync + 'static,
+    {
+        self.permissions.insert(name.into(), Box::new(checker));
+        self
+    }
+
+    pub fn command(mut self, command: Command) -> Self {
+        self.register(command);
+        self
+    }
+
+    pub fn register(&mut self, command: Command) -> &mut Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn find(&self, label: &str) -> Option<&Command> {
+        self.commands.iter().find(|c| c.matches(label))
+    }
+
+    fn default_prefix(&self) -> &str {
+        self.prefixes.first().map(String::as_str).unwrap_or("/")
+    }
+
+    /// 所有命令的帮助信息
+    pub fn help(&self) -> String {
+        let prefix = self.default_prefix();
+
+        let mut s = String::from("可用命令:");
+        for command in &self.commands {
+            let _ = write!(s, "\n{}", command.usage(prefix));
+            if !command.description.is_empty() {
+                let _ = write!(s, " - {}", command.description);
+            }
+            if !command.aliases.is_empty() {
+                let _ = write!(s, " (别名: {})", command.aliases.join(", "));
+            }
+        }
+        s
+    }
+
+    /// 解析前缀与命令名, 返回前缀, 命令名与剩余的消息元素
+    fn parse(&self, message: MessageChain) -> Option<(String, String, Vec<MessageElement>)> {
+        let mut elements = message.into_iter();
+        let MessageElement::Text(first) = elements.next()? else {
+            return None;
+        };
+
+        let text = first.trim_start();
+        let prefix = if self.prefixes.is_empty() {
+            text.starts_with('/').then_some("/")?
+        } else {
+            // 优先匹配较长的前缀
+            self.prefixes
+                .iter()
+                .filter(|p| text.starts_with(p.as_str()))
+                .max_by_key(|p| p.len())?
+        };
+
+        let text = &text[prefix.len()..];
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        let (label, rest) = text.split_at(end);
+        if label.is_empty() {
+            return None;
+        }
+
+        let mut rest_elements = vec![MessageElement::Text(rest.to_owned())];
+        rest_elements.extend(elements);
+        Some((prefix.to_owned(), label.to_owned(), rest_elements))
+    }
+
+    /// 尝试将消息作为命令执行, 返回消息是否匹配了命令
+    pub async fn execute(&self, event: MessageEvent) -> bool {
+        let message = event.message();
+        let Some((prefix, label, rest)) = self.parse(message) else {
+            return false;
+        };
+
+        let mut args = Args::new(rest);
+        let ctx = CommandContext {
+            event,
+            prefix,
+            label,
+        };
+
+        let Some(command) = self.find(&ctx.label) else {
+            if ctx.label != "help" {
+                return false;
+            }
+
+            let help = match args.parse::<String>() {
+                Ok(label) => match self.find(&label) {
+                    Some(command) => command.usage(&ctx.prefix),
+                    None => format!("未知命令: {}", label),
+                },
+                Err(_) => self.help(),
+            };
+            if let Err(e) = ctx.reply(help).await {
+                crate::error!("发送帮助信息失败: {}", e);
+            }
+            return true;
+        };
+
+        if let Some(permission) = &command.permission {
+            let permitted = match self.permissions.get(permission) {
+                Some(checker) => checker(&ctx),
+                None => {
+                    crate::warn!("命令{}需要未定义的权限: {}", command.name, permission);
+                    false
+                }
+            };
+
+            if !permitted {
+                if let Err(e) = ctx.reply("没有执行该命令的权限").await {
+                    crate::error!("回复命令{}失败: {}", command.name, e);
+                }
+                return true;
+            }
+        }
+
+        let result = (command.handler)(ctx.clone(), args).await;
+        let reply = match result {
+            Ok(()) => return true,
+            Err(CommandError::Arg(e)) => {
+                let param = command
+                    .params
+                    .get(e.index())
+                    .map(|p| format!("参数`{}`: ", p.name))
+                    .unwrap_or_default();
+                format!("{}{}\n用法: {}", param, e, command.usage(&ctx.prefix))
+            }
+            Err(CommandError::Atri(e)) => {
+                crate::error!("执行命令{}时发生错误: {}", command.name, e);
+                return true;
+            }
+            Err(CommandError::Message(s)) => s,
+        };
+
+        if let Err(e) = ctx.reply(reply).await {
+            crate::error!("回复命令{}失败: {}", command.name, e);
+        }
+        true
+    }
+
+    /// 构造处理命令的监听器, 可在启动前设置优先级等
+    pub fn listener(self) -> ListenerBuilder {
+        let registry = Arc::new(self);
+        ListenerBuilder::listening_on_always(move |e: MessageEvent| {
+            let registry = registry.clone();
+            async move {
+                registry.execute(e).await;
+            }
+        })
+    }
+
+    /// 开始监听群消息与好友消息
+    pub fn start(self) -> ListenerGuard {
+        self.listener().start()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgError, Args, Command, CommandContext, CommandRegistry, CommandResult, Rest};
+    use crate::message::at::At;
+    use crate::message::{MessageChain, MessageElement};
+    use crate::testing::{MockClient, MockFriend, MockGroup, MockHost, MockMember};
+
+    #[test]
+    fn args() {
+        let at = At {
+            target: 2,
+            display: "@Bob".into(),
+        };
+        let mut args = Args::new([
+            MessageElement::Text(r#" 10 "hello \"world\"" "#.into()),
+            MessageElement::At(at),
+            MessageElement::Text(" @3 on rest of  line ".into()),
+        ]);
+
+        assert_eq!(args.parse::<u32>(), Ok(10));
+        assert_eq!(args.parse::<String>().unwrap(), r#"hello "world""#);
+        assert_eq!(args.parse::<At>().unwrap().target, 2);
+        assert_eq!(args.parse::<At>().unwrap().target, 3);
+        assert_eq!(args.parse::<Option<bool>>(), Ok(Some(true)));
+        assert_eq!(args.parse::<Rest>().unwrap().0, "rest of  line");
+        assert_eq!(args.parse::<Option<i64>>(), Ok(None));
+        assert_eq!(
+            args.parse::<i64>(),
+            Err(ArgError::Missing {
+                index: 7,
+                expected: "整数"
+            })
+        );
+
+        let mut args = Args::new([MessageElement::Text("abc 5".into())]);
+        assert_eq!(args.parse::<Option<u32>>(), Ok(None));
+        assert_eq!(args.parse::<String>().unwrap(), "abc");
+        assert_eq!(args.parse::<u32>(), Ok(5));
+        assert!(args.is_empty());
+        assert_eq!(args.finish(), Ok(()));
+
+        let mut args = Args::new([
+            MessageElement::Text("5 ".into()),
+            MessageElement::Text(" extra words".into()),
+        ]);
+        assert_eq!(args.parse::<u32>(), Ok(5));
+        assert!(!args.is_empty());
+        assert_eq!(
+            args.finish(),
+            Err(ArgError::Unexpected {
+                index: 1,
+                found: "extra".into()
+            })
+        );
+
+        let mut args = Args::new([MessageElement::Text("abc".into())]);
+        assert!(matches!(
+            args.parse::<u8>(),
+            Err(ArgError::Invalid { index: 0, found, .. }) if found == "abc"
+        ));
+    }
+
+    #[test]
+    fn dispatch() {
+        let host = MockHost::install();
+        let client = host.add_client(
+            MockClient::new(10000, "Atri")
+                .group(
+                    MockGroup::new(123, "测试群")
+                        .member(MockMember::new(1, "Alice"))
+                        .member(MockMember::new(2, "Bob")),
+                )
+                .friend(MockFriend::new(3, "Carol")),
+        );
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+
+        /// 复读
+        #[crate::command]
+        async fn echo(ctx: CommandContext, text: Rest) -> CommandResult {
+            ctx.reply(text.0).await?;
+            Ok(())
+        }
+
+        /// 翻页
+        #[crate::command]
+        async fn page(ctx: CommandContext, page: Option<u32>) -> CommandResult {
+            ctx.reply(format!("第{}页", page.unwrap_or(1))).await?;
+            Ok(())
+        }
+
+        let _guard = CommandRegistry::new()
+            .prefix("/")
+            .prefix("#")
+            .permission("admin", |ctx| ctx.sender_id() == 1)
+            .command(
+                Command::new("ban", |ctx, mut args| async move {
+                    let target: At = args.parse()?;
+                    let minutes: u32 = args.parse()?;
+                    ctx.reply(format!("{} {} by {}", target.target, minutes, ctx.label()))
+                        .await?;
+                    Ok(())
+                })
+                .alias("mute")
+                .description("禁言")
+                .permission("admin")
+                .arg::<At>("target")
+                .arg::<u32>("minutes"),
+            )
+            .command(echo())
+            .command(page())
+            .start();
+
+        let send = |msg: &str| {
+            host.dispatch(host.group_message_event(&group, 1, msg).unwrap());
+            host.group_messages(123).last().map(MessageChain::to_string)
+        };
+
+        assert_eq!(send("#mute @2 10").unwrap(), "2 10 by mute");
+        assert_eq!(
+            send("/ban 2 x").unwrap(),
+            "参数`minutes`: 无效的参数`x`, 需要整数\n用法: /ban <target: @成员> <minutes: 整数>"
+        );
+        assert_eq!(
+            send("/help ban").unwrap(),
+            "/ban <target: @成员> <minutes: 整数>"
+        );
+        assert!(send("/help").unwrap().contains("(别名: mute)"));
+        assert_eq!(send("/help echo").unwrap(), "/echo <text: 文本...>");
+        assert_eq!(send("/page").unwrap(), "第1页");
+        assert_eq!(send("/page 3").unwrap(), "第3页");
+        assert_eq!(
+            send("/page abc").unwrap(),
+            "多余的参数`abc`\n用法: /page [page: 整数]"
+        );
+
+        host.clear_transcript();
+        assert_eq!(send("ban 2 10"), None);
+        assert_eq!(send("/unknown"), None);
+
+        host.dispatch(host.group_message_event(&group, 2, "/ban 1 10").unwrap());
+        assert_eq!(
+            host.group_messages(123)[0].to_string(),
+            "没有执行该命令的权限"
+        );
+
+        host.dispatch(host.friend_message_event(&friend, "/echo  hi there"));
+        assert_eq!(host.friend_messages(3)[0].to_string(), "hi there");
+    }
+}
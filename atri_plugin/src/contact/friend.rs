@@ -1,10 +1,12 @@
 use crate::client::Client;
 use crate::error::AtriError;
-use crate::loader::get_vtb;
+use crate::loader::{ensure_supported, get_vtb};
 use crate::message::image::Image;
 use crate::message::meta::MessageReceipt;
+use crate::message::voice::Voice;
 use crate::message::MessageChain;
 use atri_ffi::ffi::ForFFI;
+use atri_ffi::message::FFIMessageChain;
 use atri_ffi::{Handle, RustVec};
 use std::fmt::{Display, Formatter};
 
@@ -30,6 +32,8 @@ impl Friend {
         &self,
         chain: M,
     ) -> Result<MessageReceipt, AtriError> {
+        ensure_supported!(friend_send_message);
+
         let fu = {
             let ffi = chain.into().into_ffi();
             (get_vtb().friend_send_message)(self.0, ffi)
@@ -42,7 +46,54 @@ impl Friend {
         }
     }
 
+    pub async fn recall(&self, receipt: &MessageReceipt) -> Result<(), AtriError> {
+        ensure_supported!(friend_recall);
+
+        let fu = { (get_vtb().friend_recall)(self.0, receipt.clone().into_ffi()) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 从宿主的消息缓存中获取序号为`seq`的消息, 消息不在缓存中时为`None`
+    pub async fn get_message(&self, seq: i32) -> Result<Option<MessageChain>, AtriError> {
+        ensure_supported!(friend_get_message);
+
+        let fu = { (get_vtb().friend_get_message)(self.0, seq) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|chain| Option::from(chain).map(MessageChain::from_ffi))
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 获取序号小于`before_seq`的最近`count`条消息, 按时间顺序排列
+    ///
+    /// 以`i32::MAX`为`before_seq`可获取最新的消息
+    pub async fn history(
+        &self,
+        before_seq: i32,
+        count: u32,
+    ) -> Result<Vec<MessageChain>, AtriError> {
+        ensure_supported!(friend_get_history);
+
+        let fu = { (get_vtb().friend_get_history)(self.0, before_seq, count) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|history: RustVec<FFIMessageChain>| {
+                history
+                    .into_vec()
+                    .into_iter()
+                    .map(MessageChain::from_ffi)
+                    .collect()
+            })
+            .map_err(AtriError::ClientError)
+    }
+
     pub async fn upload_image(&self, img: Vec<u8>) -> Result<Image, AtriError> {
+        ensure_supported!(friend_upload_image);
+
         let fu = { (get_vtb().friend_upload_image)(self.0, RustVec::from(img)) };
         let result = crate::runtime::spawn(fu).await.unwrap();
 
@@ -51,6 +102,17 @@ impl Friend {
             Err(s) => Err(AtriError::ClientError(s)),
         }
     }
+
+    pub async fn upload_voice(&self, voice: Vec<u8>) -> Result<Voice, AtriError> {
+        ensure_supported!(friend_upload_voice);
+
+        let fu = { (get_vtb().friend_upload_voice)(self.0, RustVec::from(voice)) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(Voice)
+            .map_err(AtriError::ClientError)
+    }
 }
 
 impl Clone for Friend {
@@ -1,15 +1,18 @@
 use crate::client::Client;
+use crate::contact::group_file::GroupFiles;
 use crate::contact::member::NamedMember;
 use crate::error::{AtriError, AtriResult};
-use crate::loader::get_vtb;
+use crate::loader::{ensure_supported, get_vtb};
 use crate::message::forward::ForwardMessage;
 use crate::message::image::Image;
 use crate::message::meta::MessageReceipt;
+use crate::message::voice::Voice;
 use crate::message::MessageChain;
+use atri_ffi::contact::{FFIAnnouncement, FFIGroupSettings};
 use atri_ffi::error::FFIResult;
 use atri_ffi::ffi::ForFFI;
 use atri_ffi::message::FFIMessageChain;
-use atri_ffi::{Handle, RustStr};
+use atri_ffi::{Handle, RustStr, RustVec};
 use std::fmt::{Display, Formatter};
 
 pub struct Group(pub(crate) Handle);
@@ -46,18 +49,15 @@ impl Group {
         let fu = { (get_vtb().group_find_member)(self.0, id) };
 
         let ma = crate::runtime::spawn(fu).await.unwrap();
-
-        if ma.pointer.is_null() {
-            None
-        } else {
-            Some(NamedMember(ma))
-        }
+        NamedMember::from_nullable(ma)
     }
 
     pub async fn send_message<M: Into<MessageChain>>(
         &self,
         chain: M,
     ) -> AtriResult<MessageReceipt> {
+        ensure_supported!(group_send_message);
+
         let fu = {
             let ffi: FFIMessageChain = chain.into().into_ffi();
             (get_vtb().group_send_message)(self.0, ffi)
@@ -73,6 +73,8 @@ impl Group {
         &self,
         msg: M,
     ) -> AtriResult<MessageReceipt> {
+        ensure_supported!(group_send_forward_message);
+
         let fu = {
             let ffi = msg.into().into_ffi();
             (get_vtb().group_send_forward_message)(self.0, ffi)
@@ -85,6 +87,8 @@ impl Group {
     }
 
     pub async fn upload_image(&self, image: Vec<u8>) -> AtriResult<Image> {
+        ensure_supported!(group_upload_image);
+
         let fu = { (get_vtb().group_upload_image)(self.0, image.into()) };
 
         let result = crate::runtime::spawn(fu).await.unwrap();
@@ -93,12 +97,168 @@ impl Group {
             .map_err(AtriError::ClientError)
     }
 
+    pub async fn upload_voice(&self, voice: Vec<u8>) -> AtriResult<Voice> {
+        ensure_supported!(group_upload_voice);
+
+        let fu = { (get_vtb().group_upload_voice)(self.0, voice.into()) };
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(Voice)
+            .map_err(AtriError::ClientError)
+    }
+
     pub async fn change_name(&self, name: &str) -> AtriResult<()> {
+        ensure_supported!(group_change_name);
+
         let rs = RustStr::from(name);
         let fu = { (get_vtb().group_change_name)(self.0, rs) };
         let result: FFIResult<()> = crate::runtime::spawn(fu).await.unwrap();
 
-        Result::from(result).map_err(|s| AtriError::ClientError(s))
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 邀请好友入群
+    pub async fn invite(&self, id: i64) -> AtriResult<()> {
+        ensure_supported!(group_invite);
+
+        let fu = { (get_vtb().group_invite)(self.0, id) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 撤回消息, 撤回其他成员的消息需要管理员权限
+    pub async fn recall(&self, receipt: &MessageReceipt) -> AtriResult<()> {
+        ensure_supported!(group_recall);
+
+        let fu = { (get_vtb().group_recall)(self.0, receipt.clone().into_ffi()) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 从宿主的消息缓存中获取序号为`seq`的消息, 消息不在缓存中时为`None`
+    pub async fn get_message(&self, seq: i32) -> AtriResult<Option<MessageChain>> {
+        ensure_supported!(group_get_message);
+
+        let fu = { (get_vtb().group_get_message)(self.0, seq) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|chain| Option::from(chain).map(MessageChain::from_ffi))
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 获取序号小于`before_seq`的最近`count`条消息, 按时间顺序排列
+    ///
+    /// 以`i32::MAX`为`before_seq`可获取最新的消息, 以返回的第一条消息的序号继续获取更早的消息
+    pub async fn history(&self, before_seq: i32, count: u32) -> AtriResult<Vec<MessageChain>> {
+        ensure_supported!(group_get_history);
+
+        let fu = { (get_vtb().group_get_history)(self.0, before_seq, count) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|history: RustVec<FFIMessageChain>| {
+                history
+                    .into_vec()
+                    .into_iter()
+                    .map(MessageChain::from_ffi)
+                    .collect()
+            })
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 群成员数量
+    pub fn member_count(&self) -> u32 {
+        (get_vtb().group_get_member_count)(self.0)
+    }
+
+    /// 群主, 无法获取时为`None`
+    pub async fn owner(&self) -> Option<NamedMember> {
+        let fu = { (get_vtb().group_get_owner)(self.0) };
+
+        let ma = crate::runtime::spawn(fu).await.unwrap();
+        NamedMember::from_nullable(ma)
+    }
+
+    pub fn settings(&self) -> GroupSettings {
+        GroupSettings::from_ffi((get_vtb().group_get_settings)(self.0))
+    }
+
+    /// 开启或关闭全员禁言
+    pub async fn mute_all(&self, mute: bool) -> AtriResult<()> {
+        ensure_supported!(group_mute_all);
+
+        let fu = { (get_vtb().group_mute_all)(self.0, mute) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 允许或禁止匿名聊天
+    pub async fn set_anonymous(&self, allow: bool) -> AtriResult<()> {
+        ensure_supported!(group_set_anonymous);
+
+        let fu = { (get_vtb().group_set_anonymous)(self.0, allow) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 更换群头像
+    pub async fn change_avatar(&self, image: Vec<u8>) -> AtriResult<()> {
+        ensure_supported!(group_change_avatar);
+
+        let fu = { (get_vtb().group_change_avatar)(self.0, image.into()) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    pub async fn announcements(&self) -> AtriResult<Vec<Announcement>> {
+        ensure_supported!(group_get_announcements);
+
+        let fu = { (get_vtb().group_get_announcements)(self.0) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|v| {
+                v.into_vec()
+                    .into_iter()
+                    .map(Announcement::from_ffi)
+                    .collect()
+            })
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 发布群公告, 返回公告的id
+    pub async fn publish_announcement(&self, content: &str) -> AtriResult<String> {
+        ensure_supported!(group_publish_announcement);
+
+        let rs = RustStr::from(content);
+        let fu = { (get_vtb().group_publish_announcement)(self.0, rs) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(String::from)
+            .map_err(AtriError::ClientError)
+    }
+
+    pub async fn delete_announcement(&self, fid: &str) -> AtriResult<()> {
+        ensure_supported!(group_delete_announcement);
+
+        let rs = RustStr::from(fid);
+        let fu = { (get_vtb().group_delete_announcement)(self.0, rs) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 群文件系统
+    pub fn files(&self) -> GroupFiles {
+        GroupFiles(self.clone())
     }
 
     pub async fn quit(&self) -> bool {
@@ -128,3 +288,125 @@ impl Display for Group {
         write!(f, "Group({})", self.id())
     }
 }
+
+/// 群公告
+#[derive(Debug, Clone)]
+pub struct Announcement {
+    pub fid: String,
+    pub sender: i64,
+    /// 发布时间, 单位为秒
+    pub time: i64,
+    pub content: String,
+}
+
+impl Announcement {
+    fn from_ffi(ffi: FFIAnnouncement) -> Self {
+        Self {
+            fid: ffi.fid.into(),
+            sender: ffi.sender,
+            time: ffi.time,
+            content: ffi.content.into(),
+        }
+    }
+}
+
+/// 群设置的快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupSettings {
+    pub mute_all: bool,
+    pub allow_anonymous: bool,
+    pub allow_member_invite: bool,
+}
+
+impl GroupSettings {
+    fn from_ffi(ffi: FFIGroupSettings) -> Self {
+        Self {
+            mute_all: ffi.mute_all,
+            allow_anonymous: ffi.allow_anonymous,
+            allow_member_invite: ffi.allow_member_invite,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GroupSettings;
+    use crate::info;
+    use crate::testing::{block_on, fixture, Action};
+
+    #[test]
+    fn operations() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        block_on(async {
+            let receipt = group.send_message("Hello").await.unwrap();
+            assert_eq!(receipt.seqs.len(), 1);
+
+            group.change_name("新群名").await.unwrap();
+            assert_eq!(group.name(), "新群名");
+
+            let bob = group.find_member(2).await.unwrap();
+            bob.change_card_name("鲍勃").await.unwrap();
+            assert_eq!(bob.card_name(), "鲍勃");
+
+            let img = group.upload_image(vec![1, 1, 4, 5, 1, 4]).await.unwrap();
+            assert!(img.url().ends_with(img.id()));
+
+            assert!(group.quit().await);
+            assert!(!group.quit().await);
+            assert!(group.send_message("Bye").await.is_err());
+        });
+
+        assert!(client.find_group(123).is_none());
+
+        info!("done");
+        assert_eq!(host.logs(), [(2, "done".to_string())]);
+    }
+
+    #[test]
+    fn group_administration() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        block_on(async {
+            assert_eq!(group.member_count(), 2);
+            assert!(group.owner().await.is_none());
+            assert_eq!(group.settings(), GroupSettings::default());
+
+            group.mute_all(true).await.unwrap();
+            group.set_anonymous(true).await.unwrap();
+            let settings = group.settings();
+            assert!(settings.mute_all && settings.allow_anonymous);
+
+            let fid = group.publish_announcement("明天放假").await.unwrap();
+            let announcements = group.announcements().await.unwrap();
+            assert_eq!(announcements.len(), 1);
+            assert_eq!(announcements[0].content, "明天放假");
+            assert_eq!(announcements[0].sender, 10000);
+
+            group.delete_announcement(&fid).await.unwrap();
+            assert!(group.announcements().await.unwrap().is_empty());
+            assert!(group.delete_announcement(&fid).await.is_err());
+
+            group.change_avatar(vec![1, 2, 3]).await.unwrap();
+        });
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            transcript[0],
+            Action::GroupMuteAll {
+                group: 123,
+                mute: true
+            }
+        ));
+        assert!(matches!(
+            &transcript[3],
+            Action::GroupDeleteAnnouncement { fid: f, .. } if f.starts_with("mock-announcement-")
+        ));
+        assert!(matches!(
+            &transcript[4],
+            Action::GroupChangeAvatar { data, .. } if *data == [1, 2, 3]
+        ));
+    }
+}
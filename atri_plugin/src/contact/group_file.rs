@@ -0,0 +1,255 @@
+use crate::contact::group::Group;
+use crate::env;
+use crate::error::{AtriError, AtriResult};
+use crate::loader::{ensure_supported, get_vtb};
+use atri_ffi::contact::FFIGroupFile;
+use atri_ffi::{RustStr, RustVec};
+use std::path::Path;
+
+/// 群文件系统, 由[`Group::files`]获得
+///
+/// 文件夹与文件均以id区分, 根目录的id为[`GroupFiles::ROOT`]
+#[derive(Clone)]
+pub struct GroupFiles(pub(crate) Group);
+
+impl GroupFiles {
+    pub const ROOT: &'static str = "/";
+
+    pub fn group(&self) -> &Group {
+        &self.0
+    }
+
+    /// 列出文件夹中的文件与子文件夹
+    pub async fn list(&self, folder: &str) -> AtriResult<Vec<GroupFileEntry>> {
+        ensure_supported!(group_list_files);
+
+        let rs = RustStr::from(folder);
+        let fu = { (get_vtb().group_list_files)(self.0 .0, rs) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(|v| {
+                v.into_vec()
+                    .into_iter()
+                    .map(GroupFileEntry::from_ffi)
+                    .collect()
+            })
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 上传文件到指定文件夹
+    pub async fn upload(&self, folder: &str, name: &str, data: Vec<u8>) -> AtriResult<GroupFile> {
+        ensure_supported!(group_upload_file);
+
+        let folder = RustStr::from(folder);
+        let name = RustStr::from(name);
+        let fu = { (get_vtb().group_upload_file)(self.0 .0, folder, name, data.into()) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(GroupFile::from_ffi)
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 上传本地文件, 相对路径以插件的[工作目录](env::workspace)为基准, 文件名不变
+    pub async fn upload_path<P: AsRef<Path>>(
+        &self,
+        folder: &str,
+        path: P,
+    ) -> AtriResult<GroupFile> {
+        let path = env::workspace().join(path);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let data = std::fs::read(&path).map_err(AtriError::IOError)?;
+
+        self.upload(folder, &name, data).await
+    }
+
+    pub async fn download(&self, file: &GroupFile) -> AtriResult<Vec<u8>> {
+        ensure_supported!(group_download_file);
+
+        let rs = RustStr::from(&*file.id);
+        let fu = { (get_vtb().group_download_file)(self.0 .0, rs) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(RustVec::into_vec)
+            .map_err(AtriError::ClientError)
+    }
+
+    pub async fn rename(&self, id: &str, name: &str) -> AtriResult<()> {
+        ensure_supported!(group_rename_file);
+
+        let id = RustStr::from(id);
+        let name = RustStr::from(name);
+        let fu = { (get_vtb().group_rename_file)(self.0 .0, id, name) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 将文件移动到指定文件夹
+    pub async fn move_to(&self, id: &str, folder: &str) -> AtriResult<()> {
+        ensure_supported!(group_move_file);
+
+        let id = RustStr::from(id);
+        let folder = RustStr::from(folder);
+        let fu = { (get_vtb().group_move_file)(self.0 .0, id, folder) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    pub async fn delete(&self, id: &str) -> AtriResult<()> {
+        ensure_supported!(group_delete_file);
+
+        let rs = RustStr::from(id);
+        let fu = { (get_vtb().group_delete_file)(self.0 .0, rs) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+/// 群文件系统中的一项
+#[derive(Debug, Clone)]
+pub enum GroupFileEntry {
+    Folder(GroupFolder),
+    File(GroupFile),
+}
+
+impl GroupFileEntry {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Folder(folder) => &folder.id,
+            Self::File(file) => &file.id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Folder(folder) => &folder.name,
+            Self::File(file) => &file.name,
+        }
+    }
+
+    fn from_ffi(ffi: FFIGroupFile) -> Self {
+        if ffi.is_folder {
+            Self::Folder(GroupFolder {
+                id: ffi.id.into(),
+                name: ffi.name.into(),
+                parent: ffi.folder.into(),
+                file_count: ffi.file_count,
+            })
+        } else {
+            Self::File(GroupFile::from_ffi(ffi))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupFolder {
+    pub id: String,
+    pub name: String,
+    /// 上级文件夹的id
+    pub parent: String,
+    pub file_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupFile {
+    pub id: String,
+    pub name: String,
+    /// 所在文件夹的id
+    pub folder: String,
+    /// 文件大小, 单位为字节
+    pub size: u64,
+    pub uploader: i64,
+    /// 上传时间, 单位为秒
+    pub upload_time: i64,
+}
+
+impl GroupFile {
+    fn from_ffi(ffi: FFIGroupFile) -> Self {
+        Self {
+            id: ffi.id.into(),
+            name: ffi.name.into(),
+            folder: ffi.folder.into(),
+            size: ffi.size,
+            uploader: ffi.uploader,
+            upload_time: ffi.time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GroupFileEntry, GroupFiles};
+    use crate::error::AtriError;
+    use crate::testing::{block_on, Action, MockClient, MockGroup, MockHost};
+
+    #[test]
+    fn group_files() {
+        let host = MockHost::install();
+        let client = host.add_client(
+            MockClient::new(10000, "Atri")
+                .group(MockGroup::new(123, "测试群").folder("备份").folder("归档")),
+        );
+        let files = client.find_group(123).unwrap().files();
+
+        let dir = std::env::temp_dir().join(format!("atri-mock-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("log.txt"), b"hello").unwrap();
+        host.set_workspace(&dir);
+
+        block_on(async {
+            let root = files.list(GroupFiles::ROOT).await.unwrap();
+            let names: Vec<_> = root.iter().map(GroupFileEntry::name).collect();
+            assert_eq!(names, ["备份", "归档"]);
+
+            let file = files.upload("/备份", "a.bin", vec![1, 2, 3]).await.unwrap();
+            assert_eq!((file.size, file.uploader), (3, 10000));
+            assert_eq!(files.download(&file).await.unwrap(), [1, 2, 3]);
+
+            let log = files
+                .upload_path(GroupFiles::ROOT, "log.txt")
+                .await
+                .unwrap();
+            assert_eq!((log.name.as_str(), log.size), ("log.txt", 5));
+            assert!(matches!(
+                files.upload_path("/", "missing.txt").await,
+                Err(AtriError::IOError(_))
+            ));
+
+            files.rename(&file.id, "b.bin").await.unwrap();
+            files.move_to(&file.id, "/归档").await.unwrap();
+            assert!(files.move_to(&file.id, "/不存在").await.is_err());
+
+            let root = files.list("/").await.unwrap();
+            assert!(matches!(
+                &root[1],
+                GroupFileEntry::Folder(f) if f.id == "/归档" && f.file_count == 1
+            ));
+            let archived = files.list("/归档").await.unwrap();
+            assert!(matches!(&archived[..], [GroupFileEntry::File(f)] if f.name == "b.bin"));
+
+            files.delete(&file.id).await.unwrap();
+            assert!(files.delete(&file.id).await.is_err());
+            assert!(files.list("/归档").await.unwrap().is_empty());
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            &host.transcript()[..],
+            [
+                Action::GroupUploadFile { folder, .. },
+                Action::GroupUploadFile { .. },
+                Action::GroupRenameFile { name, .. },
+                Action::GroupMoveFile { .. },
+                Action::GroupDeleteFile { .. },
+            ] if folder == "/备份" && name == "b.bin"
+        ));
+    }
+}
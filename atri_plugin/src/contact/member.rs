@@ -1,10 +1,15 @@
 use crate::client::Client;
 use crate::contact::group::Group;
 use crate::error::AtriError;
-use crate::loader::get_vtb;
+use crate::loader::{ensure_supported, get_vtb};
+use crate::message::image::Image;
+use crate::message::meta::{Anonymous, MessageReceipt};
+use crate::message::MessageChain;
 use atri_ffi::contact::FFIMember;
-use atri_ffi::{ManagedCloneable, RustStr};
+use atri_ffi::ffi::ForFFI;
+use atri_ffi::{ManagedCloneable, RustStr, RustVec};
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 #[derive(Clone)]
 pub enum Member {
@@ -16,7 +21,27 @@ impl Member {
     pub fn id(&self) -> i64 {
         match self {
             Self::Named(named) => named.id(),
-            Self::Anonymous(_ano) => 80000000,
+            Self::Anonymous(ano) => ano.id(),
+        }
+    }
+}
+
+impl Member {
+    /// 发送临时会话消息, 匿名成员不支持
+    pub async fn send_message<M: Into<MessageChain>>(
+        &self,
+        chain: M,
+    ) -> Result<MessageReceipt, AtriError> {
+        match self {
+            Self::Named(named) => named.send_message(chain).await,
+            Self::Anonymous(_) => Err(AtriError::NotSupported),
+        }
+    }
+
+    pub async fn upload_image(&self, img: Vec<u8>) -> Result<Image, AtriError> {
+        match self {
+            Self::Named(named) => named.upload_image(img).await,
+            Self::Anonymous(_) => Err(AtriError::NotSupported),
         }
     }
 }
@@ -35,6 +60,14 @@ impl Member {
 pub struct NamedMember(pub(crate) ManagedCloneable);
 
 impl NamedMember {
+    pub(crate) fn from_nullable(ma: ManagedCloneable) -> Option<Self> {
+        if ma.pointer.is_null() {
+            None
+        } else {
+            Some(Self(ma))
+        }
+    }
+
     pub fn id(&self) -> i64 {
         (get_vtb().named_member_get_id)(self.0.pointer)
     }
@@ -56,11 +89,62 @@ impl NamedMember {
         Group(handle)
     }
 
+    pub fn role(&self) -> MemberRole {
+        MemberRole::from_u8((get_vtb().named_member_get_role)(self.0.pointer))
+    }
+
+    /// 入群时间, 单位为秒
+    pub fn join_time(&self) -> i64 {
+        (get_vtb().named_member_get_join_time)(self.0.pointer)
+    }
+
+    /// 最后发言时间, 单位为秒
+    pub fn last_speak_time(&self) -> i64 {
+        (get_vtb().named_member_get_last_speak_time)(self.0.pointer)
+    }
+
+    /// 群等级
+    pub fn level(&self) -> u16 {
+        (get_vtb().named_member_get_level)(self.0.pointer)
+    }
+
     pub fn client(&self) -> Client {
         self.group().client()
     }
 
+    /// 通过临时会话发送消息
+    pub async fn send_message<M: Into<MessageChain>>(
+        &self,
+        chain: M,
+    ) -> Result<MessageReceipt, AtriError> {
+        ensure_supported!(named_member_send_message);
+
+        let fu = {
+            let ffi = chain.into().into_ffi();
+            (get_vtb().named_member_send_message)(self.0.pointer, ffi)
+        };
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(MessageReceipt::from_ffi)
+            .map_err(AtriError::ClientError)
+    }
+
+    /// 上传用于临时会话的图片
+    pub async fn upload_image(&self, img: Vec<u8>) -> Result<Image, AtriError> {
+        ensure_supported!(named_member_upload_image);
+
+        let fu = (get_vtb().named_member_upload_image)(self.0.pointer, RustVec::from(img));
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(Image)
+            .map_err(AtriError::ClientError)
+    }
+
     pub async fn change_card_name(&self, card_name: &str) -> Result<(), AtriError> {
+        ensure_supported!(named_member_change_card_name);
+
         let rs = RustStr::from(card_name);
 
         let fu = (get_vtb().named_member_change_card_name)(self.0.pointer, rs);
@@ -68,6 +152,89 @@ impl NamedMember {
         let result = crate::runtime::spawn(fu).await.unwrap();
         Result::from(result).map_err(AtriError::ClientError)
     }
+
+    /// 禁言, 不足一秒的部分向上取整, 为0时解除禁言
+    pub async fn mute(&self, duration: Duration) -> Result<(), AtriError> {
+        ensure_supported!(named_member_mute);
+
+        let fu = (get_vtb().named_member_mute)(self.0.pointer, mute_seconds(duration));
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    pub async fn unmute(&self) -> Result<(), AtriError> {
+        self.mute(Duration::ZERO).await
+    }
+
+    /// 移出群, `block`为是否拒绝此人再次加群
+    pub async fn kick(&self, reason: &str, block: bool) -> Result<(), AtriError> {
+        ensure_supported!(named_member_kick);
+
+        let rs = RustStr::from(reason);
+        let fu = (get_vtb().named_member_kick)(self.0.pointer, rs, block);
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 设置或取消管理员, 需要群主权限
+    pub async fn set_admin(&self, admin: bool) -> Result<(), AtriError> {
+        ensure_supported!(named_member_set_admin);
+
+        let fu = (get_vtb().named_member_set_admin)(self.0.pointer, admin);
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 设置专属头衔, 需要群主权限
+    pub async fn set_special_title(&self, title: &str) -> Result<(), AtriError> {
+        ensure_supported!(named_member_set_special_title);
+
+        let rs = RustStr::from(title);
+        let fu = (get_vtb().named_member_set_special_title)(self.0.pointer, rs);
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+/// 群成员的身份
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemberRole {
+    Member,
+    Administrator,
+    Owner,
+}
+
+impl MemberRole {
+    pub fn from_u8(role: u8) -> Self {
+        match role {
+            1 => Self::Administrator,
+            2 => Self::Owner,
+            _ => Self::Member,
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// 是否为管理员或群主
+    pub fn is_operator(self) -> bool {
+        self >= Self::Administrator
+    }
+}
+
+/// 禁言时长, 不足一秒的部分向上取整, 以免短时禁言被当作解除禁言
+fn mute_seconds(duration: Duration) -> u64 {
+    let secs = duration.as_secs();
+    if duration.subsec_nanos() > 0 {
+        secs.saturating_add(1)
+    } else {
+        secs
+    }
 }
 
 impl Display for NamedMember {
@@ -76,5 +243,223 @@ impl Display for NamedMember {
     }
 }
 
+impl Display for Member {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Named(named) => Display::fmt(named, f),
+            Self::Anonymous(ano) => Display::fmt(ano, f),
+        }
+    }
+}
+
+/// 匿名群成员, 仅在其发送的消息事件中可获得
 #[derive(Clone)]
-pub struct AnonymousMember(ManagedCloneable);
+pub struct AnonymousMember(pub(crate) ManagedCloneable);
+
+impl AnonymousMember {
+    /// 所有匿名成员共用的id
+    pub const ID: i64 = 80000000;
+
+    pub fn id(&self) -> i64 {
+        Self::ID
+    }
+
+    /// 匿名身份的标识, 禁言时用于区分不同的匿名成员
+    pub fn anon_id(&self) -> Vec<u8> {
+        (get_vtb().anonymous_member_get_anon_id)(self.0.pointer).into_vec()
+    }
+
+    pub fn nickname(&self) -> &str {
+        let rs = (get_vtb().anonymous_member_get_nickname)(self.0.pointer);
+
+        rs.as_str()
+    }
+
+    pub fn portrait_index(&self) -> i32 {
+        (get_vtb().anonymous_member_get_portrait_index)(self.0.pointer)
+    }
+
+    pub fn bubble_index(&self) -> i32 {
+        (get_vtb().anonymous_member_get_bubble_index)(self.0.pointer)
+    }
+
+    /// 匿名身份的过期时间, 单位为秒
+    pub fn expire_time(&self) -> i32 {
+        (get_vtb().anonymous_member_get_expire_time)(self.0.pointer)
+    }
+
+    pub fn color(&self) -> &str {
+        let rs = (get_vtb().anonymous_member_get_color)(self.0.pointer);
+
+        rs.as_str()
+    }
+
+    pub fn group(&self) -> Group {
+        let handle = (get_vtb().anonymous_member_get_group)(self.0.pointer);
+        Group(handle)
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    /// 转换为消息元数据中的匿名信息, 可用于[`MessageChain::with_anonymous`](crate::message::MessageChain::with_anonymous)
+    pub fn to_anonymous(&self) -> Anonymous {
+        Anonymous {
+            anon_id: self.anon_id(),
+            nick: self.nickname().to_owned(),
+            portrait_index: self.portrait_index(),
+            bubble_index: self.bubble_index(),
+            expire_time: self.expire_time(),
+            color: self.color().to_owned(),
+        }
+    }
+
+    /// 禁言该匿名身份, 不足一秒的部分向上取整, 需要管理员权限
+    pub async fn mute(&self, duration: Duration) -> Result<(), AtriError> {
+        ensure_supported!(anonymous_member_mute);
+
+        let fu = (get_vtb().anonymous_member_mute)(self.0.pointer, mute_seconds(duration));
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+impl Display for AnonymousMember {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AnonymousMember({})", self.nickname())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnonymousMember, Member, MemberRole};
+    use crate::event::GroupMessageEvent;
+    use crate::listener::Listener;
+    use crate::message::meta::Anonymous;
+    use crate::testing::{block_on, fixture, Action, MockClient, MockGroup, MockHost, MockMember};
+    use std::time::Duration;
+
+    #[test]
+    fn anonymous_member() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _guard = Listener::listening_on_always(|e: GroupMessageEvent| async move {
+            if let Member::Anonymous(ano) = e.sender() {
+                ano.mute(Duration::from_secs(600)).await.unwrap();
+                let _ = e.group().send_message(format!("{} 已被禁言", ano)).await;
+            }
+        });
+
+        let anonymous = Anonymous {
+            anon_id: vec![1, 2, 3],
+            nick: String::from("匿名者"),
+            portrait_index: 4,
+            bubble_index: 5,
+            expire_time: 1000,
+            color: String::from("#ffffff"),
+        };
+        let e = host.anonymous_message_event(&group, anonymous, "广告");
+        let Member::Anonymous(ano) = e.sender() else {
+            panic!("expected an anonymous sender");
+        };
+        assert_eq!(e.sender().id(), AnonymousMember::ID);
+        assert_eq!(ano.nickname(), "匿名者");
+        assert_eq!(ano.group().id(), 123);
+        assert_eq!(
+            (ano.portrait_index(), ano.bubble_index(), ano.expire_time()),
+            (4, 5, 1000)
+        );
+        assert_eq!(ano.color(), "#ffffff");
+        assert_eq!(ano.to_anonymous().anon_id, [1, 2, 3]);
+        assert_eq!(
+            e.message().metadata().anonymous.as_ref().unwrap().nick,
+            "匿名者"
+        );
+
+        host.dispatch(e);
+        host.dispatch(host.group_message_event(&group, 1, "hi").unwrap());
+        block_on(ano.mute(Duration::from_millis(1500))).unwrap();
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            &transcript[0],
+            Action::AnonymousMute { group: 123, anon_id, duration }
+                if *anon_id == [1, 2, 3] && *duration == Duration::from_secs(600)
+        ));
+        assert_eq!(
+            host.group_messages(123)[0].to_string(),
+            "AnonymousMember(匿名者) 已被禁言"
+        );
+        assert!(matches!(
+            &transcript[2],
+            Action::AnonymousMute { duration, .. } if *duration == Duration::from_secs(2)
+        ));
+        assert_eq!(transcript.len(), 3);
+    }
+
+    #[test]
+    fn moderation() {
+        let host = MockHost::install();
+        let client = host.add_client(
+            MockClient::new(10000, "Atri").group(
+                MockGroup::new(123, "测试群")
+                    .member(MockMember::new(1, "Alice").role(MemberRole::Owner))
+                    .member(MockMember::new(2, "Bob").level(42)),
+            ),
+        );
+        let group = client.find_group(123).unwrap();
+        host.group_message_event(&group, 2, "hi").unwrap();
+
+        block_on(async {
+            let alice = group.find_member(1).await.unwrap();
+            let bob = group.find_member(2).await.unwrap();
+            assert_eq!(alice.role(), MemberRole::Owner);
+            assert_eq!(bob.level(), 42);
+            assert_eq!(group.owner().await.unwrap().id(), 1);
+            assert!(bob.last_speak_time() >= bob.join_time());
+
+            bob.mute(Duration::from_secs(600)).await.unwrap();
+            bob.unmute().await.unwrap();
+            bob.mute(Duration::from_millis(500)).await.unwrap();
+            assert!(alice.mute(Duration::from_secs(60)).await.is_err());
+
+            bob.set_admin(true).await.unwrap();
+            assert!(bob.role().is_operator());
+            assert!(alice.set_admin(false).await.is_err());
+
+            bob.set_special_title("群宠").await.unwrap();
+            bob.kick("刷屏", true).await.unwrap();
+            assert!(group.find_member(2).await.is_none());
+            assert!(bob.kick("刷屏", true).await.is_err());
+        });
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            transcript[0],
+            Action::MemberMute { group: 123, member: 2, duration } if duration.as_secs() == 600
+        ));
+        assert!(matches!(
+            transcript[1],
+            Action::MemberMute { duration, .. } if duration.is_zero()
+        ));
+        assert!(matches!(
+            transcript[2],
+            Action::MemberMute { duration, .. } if duration == Duration::from_secs(1)
+        ));
+        assert!(matches!(
+            transcript[3],
+            Action::MemberSetAdmin {
+                member: 2,
+                admin: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &transcript[5],
+            Action::MemberKick { member: 2, reason, block: true, .. } if reason == "刷屏"
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::contact::friend::Friend;
 use crate::contact::group::Group;
 use crate::contact::member::Member;
+use crate::contact::stranger::Stranger;
 use crate::error::AtriError;
 use crate::message::image::Image;
 use crate::message::meta::MessageReceipt;
@@ -8,14 +9,16 @@ use crate::message::MessageChain;
 
 pub mod friend;
 pub mod group;
+pub mod group_file;
 pub mod member;
+pub mod stranger;
 
 #[derive(Clone)]
 pub enum Contact {
     Friend(Friend),
     Group(Group),
     Member(Member),
-    Stranger,
+    Stranger(Stranger),
 }
 
 impl Contact {
@@ -23,8 +26,8 @@ impl Contact {
         match self {
             Self::Friend(f) => f.upload_image(img).await,
             Self::Group(g) => g.upload_image(img).await,
-            Self::Member(_) => Err(AtriError::NotSupported), // fixme
-            Self::Stranger => Err(AtriError::NotSupported),
+            Self::Member(m) => m.upload_image(img).await,
+            Self::Stranger(s) => s.upload_image(img).await,
         }
     }
 
@@ -35,7 +38,25 @@ impl Contact {
         match self {
             Self::Friend(f) => f.send_message(chain).await,
             Self::Group(g) => g.send_message(chain).await,
-            Self::Member(_) | Self::Stranger => Err(AtriError::NotSupported), // fixme
+            Self::Member(m) => m.send_message(chain).await,
+            Self::Stranger(s) => s.send_message(chain).await,
+        }
+    }
+
+    pub async fn recall(&self, receipt: &MessageReceipt) -> Result<(), AtriError> {
+        match self {
+            Self::Friend(f) => f.recall(receipt).await,
+            Self::Group(g) => g.recall(receipt).await,
+            Self::Member(_) | Self::Stranger(_) => Err(AtriError::NotSupported),
+        }
+    }
+
+    /// 从宿主的消息缓存中获取序号为`seq`的消息, 消息不在缓存中时为`None`
+    pub async fn get_message(&self, seq: i32) -> Result<Option<MessageChain>, AtriError> {
+        match self {
+            Self::Friend(f) => f.get_message(seq).await,
+            Self::Group(g) => g.get_message(seq).await,
+            Self::Member(_) | Self::Stranger(_) => Err(AtriError::NotSupported),
         }
     }
 }
@@ -43,3 +64,49 @@ impl Contact {
 pub trait HasSubject {
     fn subject(&self) -> Contact;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{block_on, fixture};
+
+    #[test]
+    fn message_history() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+
+        host.group_message_event(&group, 1, "早").unwrap();
+        host.group_message_event(&group, 2, "早上好").unwrap();
+        host.friend_message_event(&friend, "在吗");
+
+        block_on(async {
+            group.send_message("大家早").await.unwrap();
+            friend.send_message("在").await.unwrap();
+
+            let latest = group.history(i32::MAX, 2).await.unwrap();
+            let summary: Vec<_> = latest
+                .iter()
+                .map(|m| (m.metadata().sender, m.to_string()))
+                .collect();
+            assert_eq!(
+                summary,
+                [(2, "早上好".to_owned()), (10000, "大家早".to_owned())]
+            );
+
+            let before = latest[0].metadata().seqs[0];
+            let earlier = group.history(before, 10).await.unwrap();
+            assert_eq!(earlier.len(), 1);
+            assert_eq!(earlier[0].to_string(), "早");
+            assert!(group.history(i32::MIN, 10).await.unwrap().is_empty());
+
+            let chat: Vec<_> = friend
+                .history(i32::MAX, 10)
+                .await
+                .unwrap()
+                .iter()
+                .map(|m| (m.metadata().sender, m.to_string()))
+                .collect();
+            assert_eq!(chat, [(3, "在吗".to_owned()), (10000, "在".to_owned())]);
+        });
+    }
+}
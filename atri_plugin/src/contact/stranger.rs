@@ -0,0 +1,137 @@
+use crate::client::Client;
+use crate::error::AtriError;
+use crate::loader::{ensure_supported, get_vtb};
+use crate::message::image::Image;
+use crate::message::meta::MessageReceipt;
+use crate::message::MessageChain;
+use atri_ffi::ffi::ForFFI;
+use atri_ffi::{ManagedCloneable, RustVec};
+use std::fmt::{Display, Formatter};
+
+/// 陌生人, 即既非好友也无共同群的用户
+#[derive(Clone)]
+pub struct Stranger(pub(crate) ManagedCloneable);
+
+impl Stranger {
+    pub(crate) fn from_nullable(ma: ManagedCloneable) -> Option<Self> {
+        if ma.pointer.is_null() {
+            None
+        } else {
+            Some(Self(ma))
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        (get_vtb().stranger_get_id)(self.0.pointer)
+    }
+
+    pub fn nickname(&self) -> &str {
+        let rs = (get_vtb().stranger_get_nickname)(self.0.pointer);
+
+        rs.as_str()
+    }
+
+    pub fn client(&self) -> Client {
+        let ma = (get_vtb().stranger_get_client)(self.0.pointer);
+        Client(ma)
+    }
+
+    pub async fn send_message<M: Into<MessageChain>>(
+        &self,
+        chain: M,
+    ) -> Result<MessageReceipt, AtriError> {
+        ensure_supported!(stranger_send_message);
+
+        let fu = {
+            let ffi = chain.into().into_ffi();
+            (get_vtb().stranger_send_message)(self.0.pointer, ffi)
+        };
+
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(MessageReceipt::from_ffi)
+            .map_err(AtriError::ClientError)
+    }
+
+    pub async fn upload_image(&self, img: Vec<u8>) -> Result<Image, AtriError> {
+        ensure_supported!(stranger_upload_image);
+
+        let fu = { (get_vtb().stranger_upload_image)(self.0.pointer, RustVec::from(img)) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result)
+            .map(Image)
+            .map_err(AtriError::ClientError)
+    }
+}
+
+impl Display for Stranger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stranger({})", self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact::member::Member;
+    use crate::contact::{Contact, HasSubject};
+    use crate::event::GroupTempMessageEvent;
+    use crate::listener::Listener;
+    use crate::testing::{
+        block_on, Action, MockClient, MockGroup, MockHost, MockMember, MockStranger,
+    };
+
+    #[test]
+    fn temp_message_and_stranger() {
+        let host = MockHost::install();
+        let client = host.add_client(
+            MockClient::new(10000, "Atri")
+                .group(MockGroup::new(123, "测试群").member(MockMember::new(1, "Alice")))
+                .stranger(MockStranger::new(6, "Frank")),
+        );
+        let group = client.find_group(123).unwrap();
+
+        let _echo = Listener::listening_on_always(|e: GroupTempMessageEvent| async move {
+            let _ = e.subject().send_message(e.message()).await;
+        });
+
+        let e = host.group_temp_message_event(&group, 1, "在吗").unwrap();
+        assert_eq!(e.group().id(), 123);
+        assert_eq!(e.sender().nickname(), "Alice");
+        host.dispatch(e);
+        assert!(host.group_temp_message_event(&group, 9, "?").is_none());
+        assert_eq!(host.member_messages(123, 1)[0].to_string(), "在吗");
+
+        block_on(async {
+            assert!(client.find_stranger(7).await.is_none());
+            let frank = client.find_stranger(6).await.unwrap();
+            assert_eq!(frank.nickname(), "Frank");
+            assert_eq!(frank.client().id(), 10000);
+
+            let contact = Contact::Stranger(frank);
+            contact.upload_image(vec![0; 4]).await.unwrap();
+            contact.send_message("你好").await.unwrap();
+
+            let alice = Contact::Member(Member::Named(group.find_member(1).await.unwrap()));
+            alice.upload_image(vec![1; 4]).await.unwrap();
+        });
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            &transcript[1],
+            Action::StrangerUploadImage { stranger: 6, data } if data.len() == 4
+        ));
+        assert!(matches!(
+            &transcript[2],
+            Action::StrangerMessage { stranger: 6, message } if message.to_string() == "你好"
+        ));
+        assert!(matches!(
+            transcript[3],
+            Action::MemberUploadImage {
+                group: 123,
+                member: 1,
+                ..
+            }
+        ));
+    }
+}
@@ -7,6 +7,7 @@ pub enum AtriError {
     JoinError(String),
     ClientError(String),
     SerializationError(String),
+    IOError(std::io::Error),
     NotSupported,
 }
 
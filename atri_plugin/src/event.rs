@@ -1,23 +1,36 @@
 use crate::client::Client;
 use crate::contact::friend::Friend;
 use crate::contact::group::Group;
-use crate::contact::member::Member;
+use crate::contact::member::{Member, NamedMember};
+use crate::contact::{Contact, HasSubject};
+use crate::error::{AtriError, AtriResult};
 use crate::listener::Listener;
-use crate::loader::get_vtb;
-use crate::message::MessageChain;
+use crate::loader::{capabilities, ensure_supported, get_vtb};
+use crate::message::at::At;
+use crate::message::meta::MessageReceipt;
+use crate::message::{MessageChain, MessageElement};
 use crate::warn;
 use atri_ffi::ffi::{FFIEvent, ForFFI};
-use atri_ffi::ManagedCloneable;
+use atri_ffi::{ManagedCloneable, RustStr};
 use std::ops::Deref;
 use std::time::Duration;
 
 #[derive(Clone)]
+#[non_exhaustive]
 pub enum Event {
     ClientLogin(ClientLoginEvent),
     GroupMessage(GroupMessageEvent),
     FriendMessage(FriendMessageEvent),
     NewFriend(NewFriendEvent),
     DeleteFriend(DeleteFriendEvent),
+    GroupMemberJoin(GroupMemberJoinEvent),
+    GroupMemberLeave(GroupMemberLeaveEvent),
+    GroupRecall(GroupRecallEvent),
+    FriendRecall(FriendRecallEvent),
+    FriendRequest(FriendRequestEvent),
+    GroupInvited(GroupInvitedEvent),
+    GroupJoinRequest(GroupJoinRequestEvent),
+    GroupTempMessage(GroupTempMessageEvent),
     Unknown { raw_tag: u8, inner: EventInner },
 }
 
@@ -51,8 +64,39 @@ impl Event {
             2 => (FriendMessage, FriendMessageEvent);
             3 => (NewFriend, NewFriendEvent);
             4 => (DeleteFriend, DeleteFriendEvent);
+            5 => (GroupMemberJoin, GroupMemberJoinEvent);
+            6 => (GroupMemberLeave, GroupMemberLeaveEvent);
+            7 => (GroupRecall, GroupRecallEvent);
+            8 => (FriendRecall, FriendRecallEvent);
+            9 => (FriendRequest, FriendRequestEvent);
+            10 => (GroupInvited, GroupInvitedEvent);
+            11 => (GroupJoinRequest, GroupJoinRequestEvent);
+            12 => (GroupTempMessage, GroupTempMessageEvent);
         }
     }
+
+    /// 模拟宿主分发事件时使用
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn into_ffi(self) -> FFIEvent {
+        let (t, inner) = match self {
+            Self::ClientLogin(e) => (0, e.0),
+            Self::GroupMessage(e) => (1, e.0),
+            Self::FriendMessage(e) => (2, e.0),
+            Self::NewFriend(e) => (3, e.0),
+            Self::DeleteFriend(e) => (4, e.0),
+            Self::GroupMemberJoin(e) => (5, e.0),
+            Self::GroupMemberLeave(e) => (6, e.0),
+            Self::GroupRecall(e) => (7, e.0),
+            Self::FriendRecall(e) => (8, e.0),
+            Self::FriendRequest(e) => (9, e.0),
+            Self::GroupInvited(e) => (10, e.0),
+            Self::GroupJoinRequest(e) => (11, e.0),
+            Self::GroupTempMessage(e) => (12, e.0),
+            Self::Unknown { raw_tag, inner } => (raw_tag, inner),
+        };
+
+        FFIEvent::from(t, inner.intercepted, inner.event)
+    }
 }
 
 impl FromEvent for Event {
@@ -81,16 +125,24 @@ unsafe impl Send for EventInner {}
 
 unsafe impl Sync for EventInner {}
 
+/// 机器人登录成功
 #[derive(Clone)]
 pub struct ClientLoginEvent(EventInner);
 
+impl ClientLoginEvent {
+    pub fn client(&self) -> &Client {
+        let phandle = (get_vtb().client_login_event_get_client)(self.event.pointer);
+        unsafe { &*(phandle as *const Client) }
+    }
+}
+
 #[derive(Clone)]
 pub struct GroupMessageEvent(EventInner);
 
 impl GroupMessageEvent {
     pub fn group(&self) -> &Group {
         let phandle = (get_vtb().group_message_event_get_group)(self.0.event.pointer);
-        unsafe { std::mem::transmute(phandle) }
+        unsafe { &*(phandle as *const Group) }
     }
 
     pub fn client(&self) -> Client {
@@ -142,7 +194,7 @@ pub struct FriendMessageEvent(EventInner);
 impl FriendMessageEvent {
     pub fn friend(&self) -> &Friend {
         let phandle = (get_vtb().friend_message_event_get_friend)(self.event.pointer);
-        unsafe { std::mem::transmute(phandle) }
+        unsafe { &*(phandle as *const Friend) }
     }
 
     pub fn client(&self) -> Client {
@@ -171,15 +223,430 @@ impl FriendMessageEvent {
     }
 }
 
+/// 群成员通过临时会话发送的消息
+#[derive(Clone)]
+pub struct GroupTempMessageEvent(EventInner);
+
+impl GroupTempMessageEvent {
+    /// 临时会话所属的群
+    pub fn group(&self) -> &Group {
+        let phandle = (get_vtb().group_temp_message_event_get_group)(self.event.pointer);
+        unsafe { &*(phandle as *const Group) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    pub fn sender(&self) -> NamedMember {
+        let ma = (get_vtb().group_temp_message_event_get_sender)(self.event.pointer);
+        NamedMember(ma)
+    }
+
+    pub fn message(&self) -> MessageChain {
+        let ffi = (get_vtb().group_temp_message_event_get_message)(self.event.pointer);
+        MessageChain::from_ffi(ffi)
+    }
+
+    pub async fn next<F>(&self, timeout: Duration, filter: F) -> Option<Self>
+    where
+        F: Fn(&Self) -> bool,
+        F: Send + 'static,
+    {
+        let group_id = self.group().id();
+        let sender_id = self.sender().id();
+        Listener::next_event(timeout, move |e: &Self| {
+            if e.group().id() != group_id || e.sender().id() != sender_id {
+                return false;
+            }
+
+            filter(e)
+        })
+        .await
+    }
+}
+
+/// 新增好友
 #[derive(Clone)]
 pub struct NewFriendEvent(EventInner);
 
-impl NewFriendEvent {}
+impl NewFriendEvent {
+    pub fn friend(&self) -> &Friend {
+        let phandle = (get_vtb().new_friend_event_get_friend)(self.event.pointer);
+        unsafe { &*(phandle as *const Friend) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.friend().client()
+    }
+}
 
+/// 好友被删除, 此时已无法通过好友发送消息
 #[derive(Clone)]
 pub struct DeleteFriendEvent(EventInner);
 
-impl DeleteFriendEvent {}
+impl DeleteFriendEvent {
+    pub fn client(&self) -> &Client {
+        let phandle = (get_vtb().delete_friend_event_get_client)(self.event.pointer);
+        unsafe { &*(phandle as *const Client) }
+    }
+
+    pub fn friend_id(&self) -> i64 {
+        (get_vtb().delete_friend_event_get_friend_id)(self.event.pointer)
+    }
+}
+
+/// 新成员入群
+#[derive(Clone)]
+pub struct GroupMemberJoinEvent(EventInner);
+
+impl GroupMemberJoinEvent {
+    pub fn group(&self) -> &Group {
+        let phandle = (get_vtb().group_member_join_event_get_group)(self.event.pointer);
+        unsafe { &*(phandle as *const Group) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    pub fn member(&self) -> NamedMember {
+        let ma = (get_vtb().group_member_join_event_get_member)(self.event.pointer);
+        NamedMember(ma)
+    }
+
+    /// 邀请或同意该成员入群的成员, 主动加入或无法获知时为`None`
+    pub fn operator(&self) -> Option<NamedMember> {
+        let ma = (get_vtb().group_member_join_event_get_operator)(self.event.pointer);
+        NamedMember::from_nullable(ma)
+    }
+}
+
+/// 成员退群或被移出群
+#[derive(Clone)]
+pub struct GroupMemberLeaveEvent(EventInner);
+
+impl GroupMemberLeaveEvent {
+    pub fn group(&self) -> &Group {
+        let phandle = (get_vtb().group_member_leave_event_get_group)(self.event.pointer);
+        unsafe { &*(phandle as *const Group) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    /// 离开的成员, 其信息为离开前的状态
+    pub fn member(&self) -> NamedMember {
+        let ma = (get_vtb().group_member_leave_event_get_member)(self.event.pointer);
+        NamedMember(ma)
+    }
+
+    /// 将该成员移出群的管理员, 主动退群或管理员已不在群中时为`None`
+    pub fn operator(&self) -> Option<NamedMember> {
+        let ma = (get_vtb().group_member_leave_event_get_operator)(self.event.pointer);
+        NamedMember::from_nullable(ma)
+    }
+
+    /// 是否被移出群, 与能否获取[`operator`](Self::operator)无关
+    ///
+    /// 宿主未提供时以能否获取操作者判断
+    pub fn is_kicked(&self) -> bool {
+        if capabilities().group_member_leave_event_is_kicked {
+            (get_vtb().group_member_leave_event_is_kicked)(self.event.pointer)
+        } else {
+            self.operator().is_some()
+        }
+    }
+}
+
+/// 群消息被撤回
+#[derive(Clone)]
+pub struct GroupRecallEvent(EventInner);
+
+impl GroupRecallEvent {
+    pub fn group(&self) -> &Group {
+        let phandle = (get_vtb().group_recall_event_get_group)(self.event.pointer);
+        unsafe { &*(phandle as *const Group) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    /// 撤回消息的成员, 若其已不在群中则为`None`
+    pub fn operator(&self) -> Option<NamedMember> {
+        let ma = (get_vtb().group_recall_event_get_operator)(self.event.pointer);
+        NamedMember::from_nullable(ma)
+    }
+
+    pub fn operator_id(&self) -> i64 {
+        (get_vtb().group_recall_event_get_operator_id)(self.event.pointer)
+    }
+
+    /// 被撤回消息的发送者
+    pub fn sender_id(&self) -> i64 {
+        (get_vtb().group_recall_event_get_sender_id)(self.event.pointer)
+    }
+
+    /// 被撤回消息的序号, 与[`MessageMetadata::seqs`](crate::message::meta::MessageMetadata::seqs)对应
+    pub fn seqs(&self) -> Vec<i32> {
+        (get_vtb().group_recall_event_get_seqs)(self.event.pointer).into_vec()
+    }
+
+    /// 被撤回消息的发送时间
+    pub fn time(&self) -> i32 {
+        (get_vtb().group_recall_event_get_time)(self.event.pointer)
+    }
+
+    /// 是否由发送者自己撤回
+    pub fn is_self_recall(&self) -> bool {
+        self.operator_id() == self.sender_id()
+    }
+}
+
+/// 好友消息被撤回
+#[derive(Clone)]
+pub struct FriendRecallEvent(EventInner);
+
+impl FriendRecallEvent {
+    pub fn friend(&self) -> &Friend {
+        let phandle = (get_vtb().friend_recall_event_get_friend)(self.event.pointer);
+        unsafe { &*(phandle as *const Friend) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.friend().client()
+    }
+
+    /// 撤回消息的用户, 为好友或机器人自身
+    pub fn operator_id(&self) -> i64 {
+        (get_vtb().friend_recall_event_get_operator_id)(self.event.pointer)
+    }
+
+    /// 被撤回消息的发送者
+    pub fn sender_id(&self) -> i64 {
+        (get_vtb().friend_recall_event_get_sender_id)(self.event.pointer)
+    }
+
+    pub fn seqs(&self) -> Vec<i32> {
+        (get_vtb().friend_recall_event_get_seqs)(self.event.pointer).into_vec()
+    }
+
+    pub fn time(&self) -> i32 {
+        (get_vtb().friend_recall_event_get_time)(self.event.pointer)
+    }
+}
+
+/// 收到好友申请
+#[derive(Clone)]
+pub struct FriendRequestEvent(EventInner);
+
+impl FriendRequestEvent {
+    pub fn client(&self) -> &Client {
+        let phandle = (get_vtb().friend_request_event_get_client)(self.event.pointer);
+        unsafe { &*(phandle as *const Client) }
+    }
+
+    pub fn requester_id(&self) -> i64 {
+        (get_vtb().friend_request_event_get_requester_id)(self.event.pointer)
+    }
+
+    pub fn requester_nickname(&self) -> &str {
+        let rs = (get_vtb().friend_request_event_get_requester_nickname)(self.event.pointer);
+        rs.as_str()
+    }
+
+    /// 验证消息
+    pub fn message(&self) -> &str {
+        let rs = (get_vtb().friend_request_event_get_message)(self.event.pointer);
+        rs.as_str()
+    }
+
+    pub async fn accept(&self) -> AtriResult<()> {
+        ensure_supported!(friend_request_event_accept);
+
+        let fu = { (get_vtb().friend_request_event_accept)(self.event.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 拒绝申请, `block`为`true`时不再接受该用户的申请
+    pub async fn reject(&self, reason: &str, block: bool) -> AtriResult<()> {
+        ensure_supported!(friend_request_event_reject);
+
+        let fu = {
+            (get_vtb().friend_request_event_reject)(
+                self.event.pointer,
+                RustStr::from(reason),
+                block,
+            )
+        };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+/// 机器人被邀请入群
+#[derive(Clone)]
+pub struct GroupInvitedEvent(EventInner);
+
+impl GroupInvitedEvent {
+    pub fn client(&self) -> &Client {
+        let phandle = (get_vtb().group_invited_event_get_client)(self.event.pointer);
+        unsafe { &*(phandle as *const Client) }
+    }
+
+    pub fn group_id(&self) -> i64 {
+        (get_vtb().group_invited_event_get_group_id)(self.event.pointer)
+    }
+
+    pub fn group_name(&self) -> &str {
+        let rs = (get_vtb().group_invited_event_get_group_name)(self.event.pointer);
+        rs.as_str()
+    }
+
+    pub fn inviter_id(&self) -> i64 {
+        (get_vtb().group_invited_event_get_inviter_id)(self.event.pointer)
+    }
+
+    pub fn inviter_nickname(&self) -> &str {
+        let rs = (get_vtb().group_invited_event_get_inviter_nickname)(self.event.pointer);
+        rs.as_str()
+    }
+
+    pub async fn accept(&self) -> AtriResult<()> {
+        ensure_supported!(group_invited_event_accept);
+
+        let fu = { (get_vtb().group_invited_event_accept)(self.event.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 拒绝邀请, `block`为`true`时不再接受该群的邀请
+    pub async fn reject(&self, reason: &str, block: bool) -> AtriResult<()> {
+        ensure_supported!(group_invited_event_reject);
+
+        let fu = {
+            (get_vtb().group_invited_event_reject)(self.event.pointer, RustStr::from(reason), block)
+        };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+/// 有用户申请加入机器人管理的群
+#[derive(Clone)]
+pub struct GroupJoinRequestEvent(EventInner);
+
+impl GroupJoinRequestEvent {
+    pub fn group(&self) -> &Group {
+        let phandle = (get_vtb().group_join_request_event_get_group)(self.event.pointer);
+        unsafe { &*(phandle as *const Group) }
+    }
+
+    pub fn client(&self) -> Client {
+        self.group().client()
+    }
+
+    pub fn requester_id(&self) -> i64 {
+        (get_vtb().group_join_request_event_get_requester_id)(self.event.pointer)
+    }
+
+    pub fn requester_nickname(&self) -> &str {
+        let rs = (get_vtb().group_join_request_event_get_requester_nickname)(self.event.pointer);
+        rs.as_str()
+    }
+
+    /// 验证消息
+    pub fn message(&self) -> &str {
+        let rs = (get_vtb().group_join_request_event_get_message)(self.event.pointer);
+        rs.as_str()
+    }
+
+    pub async fn accept(&self) -> AtriResult<()> {
+        ensure_supported!(group_join_request_event_accept);
+
+        let fu = { (get_vtb().group_join_request_event_accept)(self.event.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+
+    /// 拒绝申请, `block`为`true`时不再接受该用户的申请
+    pub async fn reject(&self, reason: &str, block: bool) -> AtriResult<()> {
+        ensure_supported!(group_join_request_event_reject);
+
+        let fu = {
+            (get_vtb().group_join_request_event_reject)(
+                self.event.pointer,
+                RustStr::from(reason),
+                block,
+            )
+        };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+
+        Result::from(result).map_err(AtriError::ClientError)
+    }
+}
+
+impl HasSubject for GroupMessageEvent {
+    fn subject(&self) -> Contact {
+        Contact::Group(self.group().clone())
+    }
+}
+
+impl HasSubject for FriendMessageEvent {
+    fn subject(&self) -> Contact {
+        Contact::Friend(self.friend().clone())
+    }
+}
+
+impl HasSubject for GroupTempMessageEvent {
+    fn subject(&self) -> Contact {
+        Contact::Member(Member::Named(self.sender()))
+    }
+}
+
+impl HasSubject for NewFriendEvent {
+    fn subject(&self) -> Contact {
+        Contact::Friend(self.friend().clone())
+    }
+}
+
+impl HasSubject for GroupJoinRequestEvent {
+    fn subject(&self) -> Contact {
+        Contact::Group(self.group().clone())
+    }
+}
+
+impl HasSubject for GroupRecallEvent {
+    fn subject(&self) -> Contact {
+        Contact::Group(self.group().clone())
+    }
+}
+
+impl HasSubject for FriendRecallEvent {
+    fn subject(&self) -> Contact {
+        Contact::Friend(self.friend().clone())
+    }
+}
+
+impl HasSubject for GroupMemberJoinEvent {
+    fn subject(&self) -> Contact {
+        Contact::Group(self.group().clone())
+    }
+}
+
+impl HasSubject for GroupMemberLeaveEvent {
+    fn subject(&self) -> Contact {
+        Contact::Group(self.group().clone())
+    }
+}
 
 impl FromEvent for FriendMessageEvent {
     fn from_event(e: Event) -> Option<Self> {
@@ -195,6 +662,36 @@ pub trait FromEvent: Sized {
     fn from_event(e: Event) -> Option<Self>;
 }
 
+macro_rules! from_event_impl {
+    ($($t:ty => $e:ident)*) => {
+        $(
+        impl FromEvent for $t {
+            fn from_event(e: Event) -> Option<Self> {
+                if let Event::$e(e) = e {
+                    Some(e)
+                } else {
+                    None
+                }
+            }
+        }
+        )*
+    };
+}
+
+from_event_impl! {
+    ClientLoginEvent => ClientLogin
+    NewFriendEvent => NewFriend
+    DeleteFriendEvent => DeleteFriend
+    GroupMemberJoinEvent => GroupMemberJoin
+    GroupMemberLeaveEvent => GroupMemberLeave
+    GroupRecallEvent => GroupRecall
+    FriendRecallEvent => FriendRecall
+    FriendRequestEvent => FriendRequest
+    GroupInvitedEvent => GroupInvited
+    GroupJoinRequestEvent => GroupJoinRequest
+    GroupTempMessageEvent => GroupTempMessage
+}
+
 macro_rules! event_inner_impl {
     ($($t:ty)*) => {
         $(
@@ -209,9 +706,91 @@ macro_rules! event_inner_impl {
     };
 }
 
+/// 群, 好友与临时会话的消息事件, 用于统一处理各类聊天
+#[derive(Clone)]
+#[non_exhaustive]
 pub enum MessageEvent {
     Friend(FriendMessageEvent),
     Group(GroupMessageEvent),
+    GroupTemp(GroupTempMessageEvent),
+}
+
+impl MessageEvent {
+    pub fn client(&self) -> Client {
+        match self {
+            Self::Friend(e) => e.client(),
+            Self::Group(e) => e.client(),
+            Self::GroupTemp(e) => e.client(),
+        }
+    }
+
+    pub fn sender_id(&self) -> i64 {
+        match self {
+            Self::Friend(e) => e.friend().id(),
+            Self::Group(e) => e.sender().id(),
+            Self::GroupTemp(e) => e.sender().id(),
+        }
+    }
+
+    pub fn message(&self) -> MessageChain {
+        match self {
+            Self::Friend(e) => e.message(),
+            Self::Group(e) => e.message(),
+            Self::GroupTemp(e) => e.message(),
+        }
+    }
+
+    /// 引用本条消息进行回复, 消息没有序号时不引用
+    pub async fn reply<M: Into<MessageChain>>(&self, msg: M) -> AtriResult<MessageReceipt> {
+        let mut chain = msg.into();
+        if let Some(reply) = self.message().into_reply() {
+            chain.with_reply(reply);
+        }
+
+        self.subject().send_message(chain).await
+    }
+
+    /// 在消息前`@`发送者进行回复, 仅在群聊中有效, 发送者为匿名成员时不`@`
+    pub async fn reply_at<M: Into<MessageChain>>(&self, msg: M) -> AtriResult<MessageReceipt> {
+        let msg = msg.into();
+        let chain = match self {
+            Self::Group(e) => match e.sender() {
+                Member::Named(named) => {
+                    let name = match named.card_name() {
+                        "" => named.nickname(),
+                        card => card,
+                    };
+                    let at = At {
+                        target: named.id(),
+                        display: format!("@{}", name),
+                    };
+
+                    let meta = msg.metadata().clone();
+                    let mut chain: MessageChain =
+                        [MessageElement::At(at), MessageElement::Text(" ".into())]
+                            .into_iter()
+                            .chain(msg)
+                            .collect();
+                    *chain.metadata_mut() = meta;
+                    chain
+                }
+                Member::Anonymous(_) => msg,
+            },
+            _ => msg,
+        };
+
+        self.subject().send_message(chain).await
+    }
+}
+
+impl HasSubject for MessageEvent {
+    fn subject(&self) -> Contact {
+        match self {
+            Self::Friend(e) => e.subject(),
+            Self::Group(e) => e.subject(),
+            Self::GroupTemp(e) => e.subject(),
+        }
+    }
 }
 
 impl FromEvent for MessageEvent {
@@ -219,6 +798,7 @@ impl FromEvent for MessageEvent {
         let e = match e {
             Event::FriendMessage(e) => Self::Friend(e),
             Event::GroupMessage(e) => Self::Group(e),
+            Event::GroupTempMessage(e) => Self::GroupTemp(e),
             _ => return None,
         };
 
@@ -226,8 +806,361 @@ impl FromEvent for MessageEvent {
     }
 }
 
+impl From<FriendMessageEvent> for MessageEvent {
+    fn from(e: FriendMessageEvent) -> Self {
+        Self::Friend(e)
+    }
+}
+
+impl From<GroupMessageEvent> for MessageEvent {
+    fn from(e: GroupMessageEvent) -> Self {
+        Self::Group(e)
+    }
+}
+
+impl From<GroupTempMessageEvent> for MessageEvent {
+    fn from(e: GroupTempMessageEvent) -> Self {
+        Self::GroupTemp(e)
+    }
+}
+
 event_inner_impl! {
     ClientLoginEvent
     GroupMessageEvent
     FriendMessageEvent
+    NewFriendEvent
+    DeleteFriendEvent
+    GroupMemberJoinEvent
+    GroupMemberLeaveEvent
+    GroupRecallEvent
+    FriendRecallEvent
+    FriendRequestEvent
+    GroupInvitedEvent
+    GroupJoinRequestEvent
+    GroupTempMessageEvent
+}
+
+macro_rules! into_event_impl {
+    ($($t:ty => $e:ident)*) => {
+        $(
+        #[cfg(any(test, feature = "testing"))]
+        impl From<$t> for Event {
+            fn from(e: $t) -> Self {
+                Self::$e(e)
+            }
+        }
+        )*
+    };
+}
+
+into_event_impl! {
+    ClientLoginEvent => ClientLogin
+    GroupMessageEvent => GroupMessage
+    FriendMessageEvent => FriendMessage
+    NewFriendEvent => NewFriend
+    DeleteFriendEvent => DeleteFriend
+    GroupMemberJoinEvent => GroupMemberJoin
+    GroupMemberLeaveEvent => GroupMemberLeave
+    GroupRecallEvent => GroupRecall
+    FriendRecallEvent => FriendRecall
+    FriendRequestEvent => FriendRequest
+    GroupInvitedEvent => GroupInvited
+    GroupJoinRequestEvent => GroupJoinRequest
+    GroupTempMessageEvent => GroupTempMessage
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl From<MessageEvent> for Event {
+    fn from(e: MessageEvent) -> Self {
+        match e {
+            MessageEvent::Friend(e) => Self::FriendMessage(e),
+            MessageEvent::Group(e) => Self::GroupMessage(e),
+            MessageEvent::GroupTemp(e) => Self::GroupTempMessage(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientLoginEvent, DeleteFriendEvent, FriendRequestEvent, GroupInvitedEvent,
+        GroupJoinRequestEvent, GroupMemberJoinEvent, GroupMemberLeaveEvent, GroupRecallEvent,
+        MessageEvent, NewFriendEvent,
+    };
+    use crate::contact::{Contact, HasSubject};
+    use crate::info;
+    use crate::listener::Listener;
+    use crate::testing::{block_on, fixture, Action, MockFriend, MockMember};
+
+    #[test]
+    fn member_events() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _welcome = Listener::listening_on_always(|e: GroupMemberJoinEvent| async move {
+            let msg = match e.operator() {
+                Some(op) => format!("欢迎 {}, 由{}邀请", e.member().nickname(), op.id()),
+                None => format!("欢迎 {}", e.member().nickname()),
+            };
+            let _ = e.group().send_message(msg).await;
+        });
+        let _audit = Listener::listening_on_always(|e: GroupMemberLeaveEvent| async move {
+            let msg = match e.operator() {
+                Some(op) => format!("{} 被 {} 移出", e.member().id(), op.id()),
+                None => format!("{} 退群", e.member().id()),
+            };
+            let _ = e.group().send_message(msg).await;
+        });
+
+        host.dispatch(host.member_join_event(&group, MockMember::new(4, "Dave"), None));
+        host.dispatch(host.member_join_event(&group, MockMember::new(5, "Eve"), Some(1)));
+        block_on(async {
+            let dave = group.find_member(4).await.unwrap();
+            assert_eq!(dave.group().id(), 123);
+            assert_eq!(group.members().await.len(), 4);
+        });
+
+        let e = host.member_leave_event(&group, 4, None).unwrap();
+        assert!(!e.is_kicked());
+        assert!(matches!(e.subject(), Contact::Group(g) if g.id() == 123));
+        host.dispatch(e);
+        host.dispatch(host.member_leave_event(&group, 5, Some(1)).unwrap());
+        assert!(host.member_leave_event(&group, 5, None).is_none());
+        assert!(block_on(group.find_member(5)).is_none());
+
+        let replies: Vec<String> = host
+            .group_messages(123)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            replies,
+            ["欢迎 Dave", "欢迎 Eve, 由1邀请", "4 退群", "5 被 1 移出"]
+        );
+
+        // 操作者已不在群中时仍可判断被移出
+        let e = host.member_leave_event(&group, 2, Some(99)).unwrap();
+        assert!(e.is_kicked());
+        assert!(e.operator().is_none());
+    }
+
+    #[test]
+    fn friend_events() {
+        let (host, client) = fixture();
+
+        let _login = Listener::listening_on_always(|e: ClientLoginEvent| async move {
+            info!("{} 已登录", e.client().id());
+        });
+        let _new = Listener::listening_on_always(|e: NewFriendEvent| async move {
+            let _ = e.friend().send_message("你好").await;
+        });
+        let _delete = Listener::listening_on_always(|e: DeleteFriendEvent| async move {
+            info!("{} 删除了好友 {}", e.client().id(), e.friend_id());
+        });
+
+        host.dispatch(host.client_login_event(&client));
+
+        let e = host.new_friend_event(&client, MockFriend::new(7, "Grace"));
+        assert_eq!(e.client().id(), 10000);
+        assert!(matches!(e.subject(), Contact::Friend(f) if f.id() == 7));
+        host.dispatch(e);
+        assert_eq!(client.find_friend(7).unwrap().nickname(), "Grace");
+        assert_eq!(host.friend_messages(7)[0].to_string(), "你好");
+
+        host.dispatch(host.delete_friend_event(&client, 7).unwrap());
+        assert!(client.find_friend(7).is_none());
+        assert!(host.delete_friend_event(&client, 7).is_none());
+
+        let logs: Vec<String> = host.logs().into_iter().map(|(_, msg)| msg).collect();
+        assert_eq!(logs, ["10000 已登录", "10000 删除了好友 7"]);
+    }
+
+    #[test]
+    fn requests() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _friend = Listener::listening_on_always(|e: FriendRequestEvent| async move {
+            if e.message().contains("广告") {
+                e.reject("", true).await.unwrap();
+            } else {
+                e.accept().await.unwrap();
+            }
+        });
+        let _invited = Listener::listening_on_always(|e: GroupInvitedEvent| async move {
+            assert_eq!(e.client().id(), 10000);
+            e.reject(&format!("不接受来自{}的邀请", e.inviter_nickname()), false)
+                .await
+                .unwrap();
+        });
+        let _join = Listener::listening_on_always(|e: GroupJoinRequestEvent| async move {
+            if e.message() == "atri" {
+                e.accept().await.unwrap();
+            } else {
+                e.reject("答案错误", false).await.unwrap();
+            }
+        });
+
+        host.dispatch(host.friend_request_event(&client, 7, "Grace", "你好"));
+        host.dispatch(host.friend_request_event(&client, 8, "Spam", "广告"));
+        host.dispatch(host.group_invited_event(&client, 456, "新群", 7, "Grace"));
+        host.dispatch(host.group_join_request_event(&group, 9, "Ivan", "atri"));
+        host.dispatch(host.group_join_request_event(&group, 10, "Judy", "?"));
+
+        block_on(async {
+            assert_eq!(group.find_member(9).await.unwrap().nickname(), "Ivan");
+            assert!(group.find_member(10).await.is_none());
+
+            group.invite(7).await.unwrap();
+            assert!(group.invite(9).await.is_err());
+        });
+
+        let e = host.friend_request_event(&client, 11, "Kate", "");
+        block_on(async {
+            e.accept().await.unwrap();
+            assert!(e.reject("", false).await.is_err());
+        });
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            transcript[0],
+            Action::FriendRequestAccept { requester: 7 }
+        ));
+        assert!(matches!(
+            transcript[1],
+            Action::FriendRequestReject {
+                requester: 8,
+                block: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &transcript[2],
+            Action::GroupInvitedReject { group: 456, reason, block: false } if reason == "不接受来自Grace的邀请"
+        ));
+        assert!(matches!(
+            transcript[3],
+            Action::GroupJoinRequestAccept {
+                group: 123,
+                requester: 9
+            }
+        ));
+        assert!(matches!(
+            transcript[4],
+            Action::GroupJoinRequestReject { requester: 10, .. }
+        ));
+        assert!(matches!(
+            transcript[5],
+            Action::GroupInvite {
+                group: 123,
+                target: 7
+            }
+        ));
+        assert!(matches!(
+            transcript[6],
+            Action::FriendRequestAccept { requester: 11 }
+        ));
+        assert_eq!(transcript.len(), 7);
+    }
+
+    #[test]
+    fn message_event_reply() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+
+        let _ping = Listener::listening_on_always(|e: MessageEvent| async move {
+            match e.message().to_string().as_str() {
+                "ping" => e.reply("pong").await.unwrap(),
+                "hi" => e.reply_at("hello").await.unwrap(),
+                _ => return,
+            };
+        });
+
+        let e = host.group_message_event(&group, 1, "ping").unwrap();
+        let seq = e.message().metadata().seqs[0];
+        assert_eq!(MessageEvent::from(e.clone()).sender_id(), 1);
+        host.dispatch(e);
+        host.dispatch(host.group_message_event(&group, 1, "hi").unwrap());
+        host.dispatch(host.friend_message_event(&friend, "hi"));
+        host.dispatch(host.group_temp_message_event(&group, 2, "ping").unwrap());
+
+        let replies = host.group_messages(123);
+        let reply = replies[0].metadata().reply.as_ref().unwrap();
+        assert_eq!((reply.reply_seq, reply.sender), (seq, 1));
+        assert_eq!(replies[0].to_string(), "pong");
+        assert_eq!(replies[1].to_string(), "$[At:@爱丽丝(1)] hello");
+        assert!(replies[1].metadata().reply.is_none());
+
+        assert_eq!(host.friend_messages(3)[0].to_string(), "hello");
+        let temp = host.member_messages(123, 2);
+        assert_eq!(temp[0].metadata().reply.as_ref().unwrap().sender, 2);
+    }
+
+    #[test]
+    fn recall() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+
+        let _anti_recall = Listener::listening_on_always(|e: GroupRecallEvent| async move {
+            if !e.is_self_recall() {
+                return;
+            }
+            let msg = format!("{} 撤回了消息 {:?}", e.operator_id(), e.seqs());
+            let _ = e.group().send_message(msg).await;
+        });
+
+        let message = host.group_message_event(&group, 2, "hi").unwrap().message();
+        let seqs = message.metadata().seqs.clone();
+        host.dispatch(host.group_recall_event(&group, 2, &message));
+        host.dispatch(host.group_recall_event(&group, 1, &message));
+        assert_eq!(
+            host.group_messages(123)[0].to_string(),
+            format!("2 撤回了消息 {:?}", seqs)
+        );
+
+        let e = host.group_recall_event(&group, 1, &message);
+        assert_eq!(e.sender_id(), 2);
+        assert_eq!(e.operator().unwrap().nickname(), "Alice");
+
+        let friend_message = host.friend_message_event(&friend, "oops").message();
+        let e = host.friend_recall_event(&friend, 3, &friend_message);
+        assert_eq!((e.operator_id(), e.sender_id()), (3, 3));
+        assert_eq!(e.seqs(), friend_message.metadata().seqs);
+
+        block_on(friend.send_message("sent")).unwrap();
+        let sent = block_on(friend.history(i32::MAX, 1)).unwrap().remove(0);
+        let e = host.friend_recall_event(&friend, 10000, &sent);
+        assert_eq!((e.operator_id(), e.sender_id()), (10000, 10000));
+
+        host.clear_transcript();
+        block_on(async {
+            let receipt = group.send_message("Hello").await.unwrap();
+            group.recall(&receipt).await.unwrap();
+            message
+                .recall(&Contact::Group(group.clone()))
+                .await
+                .unwrap();
+            friend_message
+                .recall(&Contact::Friend(friend.clone()))
+                .await
+                .unwrap();
+            assert!(group.recall(&Default::default()).await.is_err());
+
+            let transcript = host.transcript();
+            assert!(matches!(
+                &transcript[1],
+                Action::GroupRecall { group: 123, seqs: s } if *s == receipt.seqs
+            ));
+            assert!(matches!(
+                &transcript[2],
+                Action::GroupRecall { group: 123, seqs: s } if *s == seqs
+            ));
+            assert!(matches!(
+                transcript[3],
+                Action::FriendRecall { friend: 3, .. }
+            ));
+        });
+    }
 }
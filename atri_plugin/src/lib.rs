@@ -1,6 +1,10 @@
-pub use atri_macros::plugin;
+pub use atri_macros::{command, plugin};
+
+// 使宏生成的代码在本crate内可用
+extern crate self as atri_plugin;
 
 pub mod client;
+pub mod command;
 pub mod contact;
 pub mod env;
 pub mod error;
@@ -10,6 +14,8 @@ pub mod loader;
 pub mod log;
 pub mod message;
 pub mod runtime;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 mod plugin;
 pub use plugin::*;
@@ -1,6 +1,6 @@
 use crate::error;
 use crate::event::{Event, FromEvent};
-use crate::loader::get_vtb;
+use crate::loader::{capabilities, get_plugin_manager, get_vtb};
 use atri_ffi::closure::FFIFn;
 use atri_ffi::ffi::FFIEvent;
 use atri_ffi::future::FFIFuture;
@@ -66,17 +66,23 @@ impl Listener {
         E: Send + 'static,
         F: Fn(&E) -> bool,
     {
-        let ffi = crate::runtime::spawn((get_vtb().listener_next_event_with_priority)(
-            timeout.as_millis() as u64,
-            FFIFn::from(|ffi| {
-                let event = Event::from_ffi(ffi);
-
-                E::from_event(event).as_ref().map(&filter).unwrap_or(false)
-            }),
-            priority as u8,
-        ))
-        .await
-        .unwrap();
+        let millis = timeout.as_millis() as u64;
+        let filter = FFIFn::from(|ffi| {
+            let event = Event::from_ffi(ffi);
+
+            E::from_event(event).as_ref().map(&filter).unwrap_or(false)
+        });
+        let future = if capabilities().listener_next_event_with_manager {
+            (get_vtb().listener_next_event_with_manager)(
+                get_plugin_manager(),
+                millis,
+                filter,
+                priority as u8,
+            )
+        } else {
+            (get_vtb().listener_next_event_with_priority)(millis, filter, priority as u8)
+        };
+        let ffi = crate::runtime::spawn(future).await.unwrap();
 
         Option::<FFIEvent>::from(ffi).and_then(|ffi| {
             let event = Event::from_ffi(ffi);
@@ -144,7 +150,7 @@ impl ListenerBuilder {
         E: FromEvent,
     {
         Self::new(move |e: Event| {
-            let fu = E::from_event(e).and_then(|e| Some(handler(e)));
+            let fu = E::from_event(e).map(&handler);
 
             async move {
                 if let Some(fu) = fu {
@@ -166,7 +172,7 @@ impl ListenerBuilder {
         E: FromEvent,
     {
         Self::new_always(move |e: Event| {
-            let fu = E::from_event(e).and_then(|e| Some(handler(e)));
+            let fu = E::from_event(e).map(&handler);
 
             async move {
                 if let Some(fu) = fu {
@@ -189,16 +195,127 @@ impl ListenerBuilder {
     }
 
     pub fn start(self) -> ListenerGuard {
-        let ma = (get_vtb().new_listener)(self.concurrent, self.handler, self.priority as u8);
+        let priority = self.priority as u8;
+        let ma = if capabilities().new_listener_with_manager {
+            (get_vtb().new_listener_with_manager)(
+                get_plugin_manager(),
+                self.concurrent,
+                self.handler,
+                priority,
+            )
+        } else {
+            (get_vtb().new_listener)(self.concurrent, self.handler, priority)
+        };
         ListenerGuard(ma)
     }
 }
 
 #[must_use = "if unused the Listener will immediately close"]
-pub struct ListenerGuard(Managed);
+pub struct ListenerGuard(#[allow(dead_code)] Managed);
 
 impl ListenerGuard {
     pub fn close(self) {
         drop(self);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Listener, ListenerBuilder, Priority};
+    use crate::event::GroupMessageEvent;
+    use crate::testing::{block_on, fixture};
+    use std::time::Duration;
+
+    #[test]
+    fn priority_and_intercept() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _low = ListenerBuilder::listening_on_always(|e: GroupMessageEvent| async move {
+            let _ = e.group().send_message("low").await;
+        })
+        .priority(Priority::Low)
+        .start();
+        let _top = ListenerBuilder::listening_on_always(|e: GroupMessageEvent| async move {
+            let _ = e.group().send_message("top").await;
+            if e.message().to_string() == "stop" {
+                e.intercept();
+            }
+        })
+        .priority(Priority::Top)
+        .start();
+
+        let e = host.group_message_event(&group, 1, "go").unwrap();
+        assert!(!host.dispatch(e));
+        let e = host.group_message_event(&group, 1, "stop").unwrap();
+        assert!(host.dispatch(e));
+
+        let replies: Vec<String> = host
+            .group_messages(123)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(replies, ["top", "low", "top"]);
+    }
+
+    #[test]
+    fn close_listener() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let once = Listener::listening_on(|e: GroupMessageEvent| async move {
+            let _ = e.group().send_message("once").await;
+            false
+        });
+        let guard = Listener::listening_on_always(|_: GroupMessageEvent| async {});
+        assert_eq!(host.listener_count(), 2);
+
+        host.dispatch(host.group_message_event(&group, 1, "1").unwrap());
+        host.dispatch(host.group_message_event(&group, 1, "2").unwrap());
+        assert_eq!(host.group_messages(123).len(), 1);
+        assert_eq!(host.listener_count(), 1);
+
+        drop(once);
+        guard.close();
+        assert_eq!(host.listener_count(), 0);
+    }
+
+    #[test]
+    fn next_event() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _guard = ListenerBuilder::listening_on_always(|e: GroupMessageEvent| async move {
+            if e.message().to_string() != "/ask" {
+                return;
+            }
+
+            let _ = e.group().send_message("你的名字是?").await;
+            let reply = match e.next(Duration::from_secs(60), |_| true).await {
+                Some(next) => format!("你好, {}", next.message()),
+                None => "超时".to_string(),
+            };
+            let _ = e.group().send_message(reply).await;
+        })
+        .concurrent(false)
+        .start();
+
+        host.dispatch(host.group_message_event(&group, 1, "/ask").unwrap());
+        assert_eq!(host.group_messages(123).len(), 1);
+
+        host.dispatch(host.group_message_event(&group, 2, "Bob").unwrap());
+        assert_eq!(host.group_messages(123).len(), 1);
+
+        host.dispatch(host.group_message_event(&group, 1, "Alice").unwrap());
+        let replies = host.group_messages(123);
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[1].to_string(), "你好, Alice");
+
+        let timeout = block_on(Listener::next_event(
+            Duration::from_millis(10),
+            |_: &GroupMessageEvent| true,
+        ));
+        assert!(timeout.is_none());
+        assert_eq!(host.listener_count(), 1);
+    }
+}
@@ -1,195 +1,463 @@
 use atri_ffi::closure::FFIFn;
-use atri_ffi::contact::FFIMember;
+use atri_ffi::contact::{FFIAnnouncement, FFIGroupFile, FFIGroupSettings, FFIMember};
 use atri_ffi::error::FFIResult;
 use atri_ffi::ffi::{AtriManager, FFIEvent};
 use atri_ffi::future::FFIFuture;
 use atri_ffi::message::forward::FFIForwardNode;
-use atri_ffi::message::{FFIMessageChain, FFIMessageReceipt};
+use atri_ffi::message::{FFIMessageChain, FFIMessageElement, FFIMessageReceipt};
+use atri_ffi::plugin::{check_compatibility, AbiInfo};
 use atri_ffi::{
     FFIOption, Handle, Managed, ManagedCloneable, PHandle, RustStr, RustString, RustVec,
 };
 use std::mem::MaybeUninit;
+use std::ptr::{addr_of, addr_of_mut};
+use std::sync::OnceLock;
+
+mod neutral;
+
+macro_rules! atri_vtable {
+    ($(
+        $sig:literal => $field:ident: fn($($arg:ident: $t:ty),* $(,)?) $(-> $ret:ty)?;
+    )*) => {
+        pub struct AtriVTable {
+            $(pub $field: extern "C" fn($($arg: $t),*) $(-> $ret)?,)*
+        }
+
+        /// 宿主是否提供了对应的函数
+        ///
+        /// 返回[`AtriResult`](crate::error::AtriResult)的接口会在调用前检查, 宿主未提供时返回[`AtriError::NotSupported`](crate::error::AtriError::NotSupported).
+        ///
+        /// 其余接口在宿主未提供时会得到空值而不会报错, 使用前应检查对应的项:
+        /// - 数值为`0`, 布尔值为`false`, 字符串与列表为空, 如[`Group::id`](crate::contact::group::Group::id)与[`MessageChain::to_json`](crate::message::MessageChain::to_json)
+        /// - 可选值为`None`, 如[`Group::owner`](crate::contact::group::Group::owner)
+        /// - 事件的getter返回空句柄, 如[`GroupMessageEvent::group`](crate::event::GroupMessageEvent::group), 对其继续调用接口的结果由宿主决定
+        /// - 运行时(`plugin_manager_spawn`与`plugin_manager_block_on`)在当前线程执行
+        #[derive(Debug, Clone)]
+        pub struct Capabilities {
+            $(pub $field: bool,)*
+        }
+
+        impl Capabilities {
+            /// 宿主未提供的函数
+            pub fn missing(&self) -> Vec<&'static str> {
+                let mut missing = vec![];
+                $(
+                    if !self.$field {
+                        missing.push(stringify!($field));
+                    }
+                )*
+                missing
+            }
+        }
+
+        /// 编译时函数表包含的签名, 宿主可据此检查其提供的函数
+        #[doc(hidden)]
+        pub const SIGNATURES: &[u16] = &[$($sig),*];
+
+        /// 函数表中签名对应的函数, 模拟宿主以此提供函数
+        #[cfg(any(test, feature = "testing"))]
+        pub(crate) fn vtable_fun(vtable: &AtriVTable, sig: u16) -> *const () {
+            match sig {
+                $($sig => vtable.$field as *const (),)*
+                _ => std::ptr::null(),
+            }
+        }
+
+        mod fallback {
+            use super::*;
+
+            $(
+                pub(super) extern "C" fn $field($(_: $t),*) $(-> $ret)? {
+                    super::neutral::Neutral::neutral()
+                }
+            )*
+        }
+
+        #[allow(clippy::missing_transmute_annotations)]
+        fn load_vtable(get_fun: extern "C" fn(sig: u16) -> *const ()) -> (AtriVTable, Capabilities) {
+            $(let $field = get_fun($sig);)*
+
+            let capabilities = Capabilities {
+                $($field: !$field.is_null(),)*
+            };
+
+            let mut vtable = AtriVTable {
+                $(
+                    $field: if $field.is_null() {
+                        fallback::$field
+                    } else {
+                        unsafe { std::mem::transmute($field) }
+                    },
+                )*
+            };
+
+            if !capabilities.plugin_manager_spawn {
+                vtable.plugin_manager_spawn = neutral::spawn;
+            }
+            if !capabilities.plugin_manager_block_on {
+                vtable.plugin_manager_block_on = neutral::block_on;
+            }
+
+            (vtable, capabilities)
+        }
+    };
+}
 
-pub struct AtriVTable {
-    pub plugin_manager_spawn:
-        extern "C" fn(manager: *const (), FFIFuture<Managed>) -> FFIFuture<FFIResult<Managed>>,
-    pub plugin_manager_block_on: extern "C" fn(manager: *const (), FFIFuture<Managed>) -> Managed,
+atri_vtable! {
+    0 => plugin_manager_spawn: fn(
+        manager: *const (),
+        future: FFIFuture<Managed>,
+    ) -> FFIFuture<FFIResult<Managed>>;
+    1 => plugin_manager_block_on: fn(manager: *const (), future: FFIFuture<Managed>) -> Managed;
 
-    pub new_listener: extern "C" fn(bool, FFIFn<FFIEvent, FFIFuture<bool>>, u8) -> Managed,
-    pub listener_next_event_with_priority: extern "C" fn(
+    100 => new_listener: fn(
+        concurrent: bool,
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+        priority: u8,
+    ) -> Managed;
+    101 => listener_next_event_with_priority: fn(
+        millis: u64,
+        filter: FFIFn<FFIEvent, bool>,
+        priority: u8,
+    ) -> FFIFuture<FFIOption<FFIEvent>>;
+    102 => new_listener_with_manager: fn(
+        manager: *const (),
+        concurrent: bool,
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+        priority: u8,
+    ) -> Managed;
+    103 => listener_next_event_with_manager: fn(
+        manager: *const (),
         millis: u64,
         filter: FFIFn<FFIEvent, bool>,
         priority: u8,
-    ) -> FFIFuture<FFIOption<FFIEvent>>,
-
-    pub event_intercept: extern "C" fn(*const ()),
-    pub event_is_intercepted: extern "C" fn(*const ()) -> bool,
-
-    pub client_get_id: extern "C" fn(Handle) -> i64,
-    pub client_get_nickname: extern "C" fn(Handle) -> RustString,
-    pub client_get_list: extern "C" fn() -> RustVec<Handle>,
-    pub find_client: extern "C" fn(i64) -> Handle,
-    pub client_find_group: extern "C" fn(Handle, i64) -> Handle,
-    pub client_find_friend: extern "C" fn(Handle, i64) -> Handle,
-    pub client_get_groups: extern "C" fn(Handle) -> RustVec<Handle>,
-    pub client_get_friends: extern "C" fn(Handle) -> RustVec<Handle>,
-    pub client_clone: extern "C" fn(Handle) -> Handle,
-    pub client_drop: extern "C" fn(Handle),
-
-    pub group_message_event_get_group: extern "C" fn(event: *const ()) -> PHandle,
-    pub group_message_event_get_message: extern "C" fn(event: *const ()) -> FFIMessageChain,
-    pub group_message_event_get_sender: extern "C" fn(event: *const ()) -> FFIMember,
-
-    pub group_get_id: extern "C" fn(Handle) -> i64,
-    pub group_get_name: extern "C" fn(Handle) -> RustStr,
-    pub group_get_client: extern "C" fn(Handle) -> Handle,
-    pub group_get_members: extern "C" fn(Handle) -> FFIFuture<RustVec<ManagedCloneable>>,
-    pub group_find_member: extern "C" fn(Handle, i64) -> FFIFuture<ManagedCloneable>,
-    pub group_send_message: extern "C" fn(
+    ) -> FFIFuture<FFIOption<FFIEvent>>;
+
+    200 => event_intercept: fn(intercepted: *const ());
+    201 => event_is_intercepted: fn(intercepted: *const ()) -> bool;
+
+    300 => client_get_id: fn(client: Handle) -> i64;
+    301 => client_get_nickname: fn(client: Handle) -> RustString;
+    302 => client_get_list: fn() -> RustVec<Handle>;
+    303 => find_client: fn(id: i64) -> Handle;
+    304 => client_find_group: fn(client: Handle, id: i64) -> Handle;
+    305 => client_find_friend: fn(client: Handle, id: i64) -> Handle;
+    306 => client_get_groups: fn(client: Handle) -> RustVec<Handle>;
+    307 => client_get_friends: fn(client: Handle) -> RustVec<Handle>;
+    308 => client_find_stranger: fn(client: Handle, id: i64) -> FFIFuture<ManagedCloneable>;
+    309 => client_get_list_with_manager: fn(manager: *const ()) -> RustVec<Handle>;
+    310 => find_client_with_manager: fn(manager: *const (), id: i64) -> Handle;
+    320 => client_clone: fn(client: Handle) -> Handle;
+    321 => client_drop: fn(client: Handle);
+
+    400 => group_get_id: fn(group: Handle) -> i64;
+    401 => group_get_name: fn(group: Handle) -> RustStr;
+    402 => group_get_client: fn(group: Handle) -> Handle;
+    403 => group_get_members: fn(group: Handle) -> FFIFuture<RustVec<ManagedCloneable>>;
+    404 => group_find_member: fn(group: Handle, id: i64) -> FFIFuture<ManagedCloneable>;
+    // 405
+    406 => group_send_message: fn(
         group: Handle,
         chain: FFIMessageChain,
-    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
-    pub group_upload_image:
-        extern "C" fn(group: Handle, data: RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
-    pub group_quit: extern "C" fn(group: Handle) -> FFIFuture<bool>,
-    pub group_change_name: extern "C" fn(group: Handle, name: RustStr) -> FFIFuture<FFIResult<()>>,
-    pub group_send_forward_message: extern "C" fn(
-        group: *const (),
+    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>;
+    407 => group_upload_image: fn(
+        group: Handle,
+        data: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+    408 => group_quit: fn(group: Handle) -> FFIFuture<bool>;
+    409 => group_change_name: fn(group: Handle, name: RustStr) -> FFIFuture<FFIResult<()>>;
+    410 => group_send_forward_message: fn(
+        group: Handle,
         msg: RustVec<FFIForwardNode>,
-    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
-    pub group_invite: extern "C" fn(group: Handle, id: i64) -> FFIFuture<FFIResult<()>>,
-    pub group_clone: extern "C" fn(Handle) -> Handle,
-    pub group_drop: extern "C" fn(Handle),
-
-    pub friend_message_event_get_friend: extern "C" fn(event: *const ()) -> PHandle,
-    pub friend_message_event_get_message: extern "C" fn(event: *const ()) -> FFIMessageChain,
-    pub friend_get_id: extern "C" fn(Handle) -> i64,
-    pub friend_get_nickname: extern "C" fn(Handle) -> RustStr,
-    pub friend_get_client: extern "C" fn(Handle) -> Handle,
-    pub friend_send_message: extern "C" fn(
+    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>;
+    411 => group_invite: fn(group: Handle, id: i64) -> FFIFuture<FFIResult<()>>;
+    412 => group_recall: fn(group: Handle, receipt: FFIMessageReceipt) -> FFIFuture<FFIResult<()>>;
+    413 => group_mute_all: fn(group: Handle, mute: bool) -> FFIFuture<FFIResult<()>>;
+    414 => group_get_announcements: fn(
+        group: Handle,
+    ) -> FFIFuture<FFIResult<RustVec<FFIAnnouncement>>>;
+    415 => group_publish_announcement: fn(
+        group: Handle,
+        content: RustStr,
+    ) -> FFIFuture<FFIResult<RustString>>;
+    416 => group_delete_announcement: fn(group: Handle, fid: RustStr) -> FFIFuture<FFIResult<()>>;
+    417 => group_set_anonymous: fn(group: Handle, allow: bool) -> FFIFuture<FFIResult<()>>;
+    418 => group_change_avatar: fn(group: Handle, data: RustVec<u8>) -> FFIFuture<FFIResult<()>>;
+    419 => group_get_member_count: fn(group: Handle) -> u32;
+    420 => group_clone: fn(group: Handle) -> Handle;
+    421 => group_drop: fn(group: Handle);
+    422 => group_get_owner: fn(group: Handle) -> FFIFuture<ManagedCloneable>;
+    423 => group_get_settings: fn(group: Handle) -> FFIGroupSettings;
+    424 => group_upload_voice: fn(
+        group: Handle,
+        data: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+    425 => group_list_files: fn(
+        group: Handle,
+        folder: RustStr,
+    ) -> FFIFuture<FFIResult<RustVec<FFIGroupFile>>>;
+    426 => group_upload_file: fn(
+        group: Handle,
+        folder: RustStr,
+        name: RustStr,
+        data: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<FFIGroupFile>>;
+    427 => group_download_file: fn(group: Handle, id: RustStr) -> FFIFuture<FFIResult<RustVec<u8>>>;
+    428 => group_rename_file: fn(group: Handle, id: RustStr, name: RustStr) -> FFIFuture<FFIResult<()>>;
+    429 => group_move_file: fn(group: Handle, id: RustStr, folder: RustStr) -> FFIFuture<FFIResult<()>>;
+    430 => group_delete_file: fn(group: Handle, id: RustStr) -> FFIFuture<FFIResult<()>>;
+    431 => group_get_message: fn(
+        group: Handle,
+        seq: i32,
+    ) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>>;
+    432 => group_get_history: fn(
+        group: Handle,
+        before_seq: i32,
+        count: u32,
+    ) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>>;
+
+    500 => friend_get_id: fn(friend: Handle) -> i64;
+    501 => friend_get_nickname: fn(friend: Handle) -> RustStr;
+    502 => friend_get_client: fn(friend: Handle) -> Handle;
+    503 => friend_send_message: fn(
+        friend: Handle,
+        chain: FFIMessageChain,
+    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>;
+    504 => friend_upload_image: fn(
+        friend: Handle,
+        img: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+    505 => friend_recall: fn(
+        friend: Handle,
+        receipt: FFIMessageReceipt,
+    ) -> FFIFuture<FFIResult<()>>;
+    506 => friend_upload_voice: fn(
+        friend: Handle,
+        data: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+    507 => friend_get_history: fn(
         friend: Handle,
+        before_seq: i32,
+        count: u32,
+    ) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>>;
+    508 => friend_get_message: fn(
+        friend: Handle,
+        seq: i32,
+    ) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>>;
+    520 => friend_clone: fn(friend: Handle) -> Handle;
+    521 => friend_drop: fn(friend: Handle);
+
+    600 => named_member_get_id: fn(named: *const ()) -> i64;
+    601 => named_member_get_nickname: fn(named: *const ()) -> RustStr;
+    602 => named_member_get_card_name: fn(named: *const ()) -> RustStr;
+    603 => named_member_get_group: fn(named: *const ()) -> Handle;
+    604 => named_member_change_card_name: fn(
+        named: *const (),
+        card: RustStr,
+    ) -> FFIFuture<FFIResult<()>>;
+    605 => named_member_mute: fn(named: *const (), seconds: u64) -> FFIFuture<FFIResult<()>>;
+    606 => named_member_kick: fn(
+        named: *const (),
+        reason: RustStr,
+        block: bool,
+    ) -> FFIFuture<FFIResult<()>>;
+    607 => named_member_set_admin: fn(named: *const (), admin: bool) -> FFIFuture<FFIResult<()>>;
+    608 => named_member_set_special_title: fn(
+        named: *const (),
+        title: RustStr,
+    ) -> FFIFuture<FFIResult<()>>;
+    609 => named_member_get_role: fn(named: *const ()) -> u8;
+    610 => named_member_get_join_time: fn(named: *const ()) -> i64;
+    611 => named_member_get_last_speak_time: fn(named: *const ()) -> i64;
+    612 => named_member_get_level: fn(named: *const ()) -> u16;
+    613 => named_member_send_message: fn(
+        named: *const (),
+        chain: FFIMessageChain,
+    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>;
+    614 => named_member_upload_image: fn(
+        named: *const (),
+        img: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+
+    700 => anonymous_member_get_anon_id: fn(ano: *const ()) -> RustVec<u8>;
+    701 => anonymous_member_get_nickname: fn(ano: *const ()) -> RustStr;
+    702 => anonymous_member_get_portrait_index: fn(ano: *const ()) -> i32;
+    703 => anonymous_member_get_bubble_index: fn(ano: *const ()) -> i32;
+    704 => anonymous_member_get_expire_time: fn(ano: *const ()) -> i32;
+    705 => anonymous_member_get_color: fn(ano: *const ()) -> RustStr;
+    706 => anonymous_member_get_group: fn(ano: *const ()) -> Handle;
+    707 => anonymous_member_mute: fn(ano: *const (), seconds: u64) -> FFIFuture<FFIResult<()>>;
+
+    800 => stranger_get_id: fn(stranger: *const ()) -> i64;
+    801 => stranger_get_nickname: fn(stranger: *const ()) -> RustStr;
+    802 => stranger_get_client: fn(stranger: *const ()) -> Handle;
+    803 => stranger_send_message: fn(
+        stranger: *const (),
         chain: FFIMessageChain,
-    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>,
-    pub friend_upload_image:
-        extern "C" fn(friend: Handle, img: RustVec<u8>) -> FFIFuture<FFIResult<ManagedCloneable>>,
-    pub friend_clone: extern "C" fn(Handle) -> Handle,
-    pub friend_drop: extern "C" fn(Handle),
+    ) -> FFIFuture<FFIResult<FFIMessageReceipt>>;
+    804 => stranger_upload_image: fn(
+        stranger: *const (),
+        img: RustVec<u8>,
+    ) -> FFIFuture<FFIResult<ManagedCloneable>>;
+
+    10000 => group_message_event_get_group: fn(event: *const ()) -> PHandle;
+    10001 => group_message_event_get_message: fn(event: *const ()) -> FFIMessageChain;
+    10002 => group_message_event_get_sender: fn(event: *const ()) -> FFIMember;
+
+    10100 => friend_message_event_get_friend: fn(event: *const ()) -> PHandle;
+    10101 => friend_message_event_get_message: fn(event: *const ()) -> FFIMessageChain;
+
+    10200 => group_member_join_event_get_group: fn(event: *const ()) -> PHandle;
+    10201 => group_member_join_event_get_member: fn(event: *const ()) -> ManagedCloneable;
+    10202 => group_member_join_event_get_operator: fn(event: *const ()) -> ManagedCloneable;
+
+    10300 => group_member_leave_event_get_group: fn(event: *const ()) -> PHandle;
+    10301 => group_member_leave_event_get_member: fn(event: *const ()) -> ManagedCloneable;
+    10302 => group_member_leave_event_get_operator: fn(event: *const ()) -> ManagedCloneable;
+    10303 => group_member_leave_event_is_kicked: fn(event: *const ()) -> bool;
+
+    10400 => group_recall_event_get_group: fn(event: *const ()) -> PHandle;
+    10401 => group_recall_event_get_operator: fn(event: *const ()) -> ManagedCloneable;
+    10402 => group_recall_event_get_operator_id: fn(event: *const ()) -> i64;
+    10403 => group_recall_event_get_sender_id: fn(event: *const ()) -> i64;
+    10404 => group_recall_event_get_seqs: fn(event: *const ()) -> RustVec<i32>;
+    10405 => group_recall_event_get_time: fn(event: *const ()) -> i32;
+
+    10500 => friend_recall_event_get_friend: fn(event: *const ()) -> PHandle;
+    10501 => friend_recall_event_get_operator_id: fn(event: *const ()) -> i64;
+    10502 => friend_recall_event_get_seqs: fn(event: *const ()) -> RustVec<i32>;
+    10503 => friend_recall_event_get_time: fn(event: *const ()) -> i32;
+    10504 => friend_recall_event_get_sender_id: fn(event: *const ()) -> i64;
+
+    10600 => friend_request_event_get_client: fn(event: *const ()) -> PHandle;
+    10601 => friend_request_event_get_requester_id: fn(event: *const ()) -> i64;
+    10602 => friend_request_event_get_requester_nickname: fn(event: *const ()) -> RustStr;
+    10603 => friend_request_event_get_message: fn(event: *const ()) -> RustStr;
+    10604 => friend_request_event_accept: fn(event: *const ()) -> FFIFuture<FFIResult<()>>;
+    10605 => friend_request_event_reject: fn(
+        event: *const (),
+        reason: RustStr,
+        block: bool,
+    ) -> FFIFuture<FFIResult<()>>;
+
+    10700 => group_invited_event_get_client: fn(event: *const ()) -> PHandle;
+    10701 => group_invited_event_get_group_id: fn(event: *const ()) -> i64;
+    10702 => group_invited_event_get_group_name: fn(event: *const ()) -> RustStr;
+    10703 => group_invited_event_get_inviter_id: fn(event: *const ()) -> i64;
+    10704 => group_invited_event_get_inviter_nickname: fn(event: *const ()) -> RustStr;
+    10705 => group_invited_event_accept: fn(event: *const ()) -> FFIFuture<FFIResult<()>>;
+    10706 => group_invited_event_reject: fn(
+        event: *const (),
+        reason: RustStr,
+        block: bool,
+    ) -> FFIFuture<FFIResult<()>>;
+
+    10800 => group_join_request_event_get_group: fn(event: *const ()) -> PHandle;
+    10801 => group_join_request_event_get_requester_id: fn(event: *const ()) -> i64;
+    10802 => group_join_request_event_get_requester_nickname: fn(event: *const ()) -> RustStr;
+    10803 => group_join_request_event_get_message: fn(event: *const ()) -> RustStr;
+    10804 => group_join_request_event_accept: fn(event: *const ()) -> FFIFuture<FFIResult<()>>;
+    10805 => group_join_request_event_reject: fn(
+        event: *const (),
+        reason: RustStr,
+        block: bool,
+    ) -> FFIFuture<FFIResult<()>>;
+
+    10900 => client_login_event_get_client: fn(event: *const ()) -> PHandle;
+
+    11000 => new_friend_event_get_friend: fn(event: *const ()) -> PHandle;
+
+    11100 => delete_friend_event_get_client: fn(event: *const ()) -> PHandle;
+    11101 => delete_friend_event_get_friend_id: fn(event: *const ()) -> i64;
+
+    11200 => group_temp_message_event_get_group: fn(event: *const ()) -> PHandle;
+    11201 => group_temp_message_event_get_sender: fn(event: *const ()) -> ManagedCloneable;
+    11202 => group_temp_message_event_get_message: fn(event: *const ()) -> FFIMessageChain;
+
+    2000 => image_get_id: fn(img: *const ()) -> RustStr;
+    // flash => 2001
+    2002 => image_get_url: fn(img: *const ()) -> RustString;
+
+    2100 => voice_get_url: fn(voice: *const ()) -> RustString;
+    2101 => voice_get_md5: fn(voice: *const ()) -> RustVec<u8>;
+    2102 => voice_get_size: fn(voice: *const ()) -> u32;
+    2103 => voice_get_duration: fn(voice: *const ()) -> u64;
+    2104 => voice_download: fn(voice: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>>;
+
+    2200 => video_get_name: fn(video: *const ()) -> RustStr;
+    2201 => video_get_size: fn(video: *const ()) -> u64;
+    2202 => video_get_url: fn(video: *const ()) -> RustString;
+    2203 => video_download: fn(video: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>>;
+
+    2300 => file_get_name: fn(file: *const ()) -> RustStr;
+    2301 => file_get_size: fn(file: *const ()) -> u64;
+    2302 => file_get_url: fn(file: *const ()) -> RustString;
+    2303 => file_download: fn(file: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>>;
+
+    20000 => log: fn(handle: usize, manager: *const (), level: u8, log: RustStr);
+
+    30000 => env_get_workspace: fn(handle: usize, manager: *const ()) -> RustString;
+
+    30100 => message_chain_to_json: fn(chain: FFIMessageChain) -> RustString;
+    30101 => message_chain_from_json: fn(json: RustStr) -> FFIResult<FFIMessageChain>;
+    30102 => message_element_wrap: fn(elem: FFIMessageElement) -> ManagedCloneable;
+    30103 => message_element_unwrap: fn(elem: *const ()) -> FFIMessageElement;
+    30104 => message_element_to_json: fn(elem: *const ()) -> RustString;
+}
 
-    pub named_member_get_id: extern "C" fn(named: *const ()) -> i64,
-    pub named_member_get_nickname: extern "C" fn(named: *const ()) -> RustStr,
-    pub named_member_get_card_name: extern "C" fn(named: *const ()) -> RustStr,
-    pub named_member_get_group: extern "C" fn(named: *const ()) -> Handle,
-    pub named_member_change_card_name:
-        extern "C" fn(named: *const (), card: RustStr) -> FFIFuture<FFIResult<()>>,
+/// 可能失败的接口在调用宿主函数前的检查, 宿主未提供时返回[`AtriError::NotSupported`](crate::error::AtriError::NotSupported)
+macro_rules! ensure_supported {
+    ($($field:ident),+ $(,)?) => {
+        if $(!$crate::loader::capabilities().$field)||+ {
+            return Err($crate::error::AtriError::NotSupported);
+        }
+    };
+}
 
-    pub image_get_id: extern "C" fn(img: *const ()) -> RustStr,
-    // flash
-    pub image_get_url: extern "C" fn(img: *const ()) -> RustString,
+pub(crate) use ensure_supported;
 
-    pub log: extern "C" fn(handle: usize, manager: *const (), level: u8, log: RustStr),
+static mut ATRI_MANAGER: MaybeUninit<AtriManager> = MaybeUninit::uninit();
 
-    pub env_get_workspace: extern "C" fn(handle: usize, manager: *const ()) -> RustString,
+static mut ATRI_VTABLE: MaybeUninit<AtriVTable> = MaybeUninit::uninit();
 
-    pub message_chain_to_json: extern "C" fn(chain: FFIMessageChain) -> RustString,
-    pub message_chain_from_json: extern "C" fn(json: RustStr) -> FFIResult<FFIMessageChain>,
-}
+static mut CAPABILITIES: MaybeUninit<Capabilities> = MaybeUninit::uninit();
 
-static mut ATRI_MANAGER: MaybeUninit<AtriManager> = MaybeUninit::uninit();
+/// 本插件编译时的ABI信息
+pub(crate) fn abi_info() -> &'static AbiInfo {
+    static ABI: OnceLock<AbiInfo> = OnceLock::new();
+    ABI.get_or_init(|| AbiInfo::new(env!("CARGO_PKG_VERSION"), SIGNATURES))
+}
 
-static mut ATRI_VTABLE: MaybeUninit<AtriVTable> = MaybeUninit::uninit();
+/// 宿主在调用`on_init`前以此检查ABI兼容性
+#[no_mangle]
+pub(crate) extern "C" fn atri_abi_info() -> *const AbiInfo {
+    abi_info()
+}
 
+/// 宿主的ABI不兼容时返回`false`, 此时不会进行初始化
+///
 /// Safety: This function will be called by the plugin manager once
 #[no_mangle]
-unsafe extern "C" fn atri_manager_init(manager: AtriManager) {
-    let get_fun = manager.get_fun;
-
-    ATRI_MANAGER.write(manager);
-
-    macro_rules! vtb {
-        (get_fun: $fun:expr; $($field:ident => $sig:expr),* $(,)?) => {
-            AtriVTable {
-                $($field: std::mem::transmute(($fun)($sig)),)*
-            }
-        };
+pub(crate) unsafe extern "C" fn atri_manager_init(manager: AtriManager) -> bool {
+    if check_compatibility(&manager.abi, abi_info()).is_err() {
+        return false;
     }
 
-    let vtable = vtb! {
-        get_fun: get_fun;
-        plugin_manager_spawn => 0,
-        plugin_manager_block_on => 1,
-
-        new_listener => 100,
-        listener_next_event_with_priority => 101,
-
-        event_intercept => 200,
-        event_is_intercepted => 201,
-
-        client_get_id => 300,
-        client_get_nickname => 301,
-        client_get_list => 302,
-        find_client => 303,
-        client_find_group => 304,
-        client_find_friend => 305,
-        client_get_groups => 306,
-        client_get_friends => 307,
-        client_clone => 320,
-        client_drop => 321,
-
-        group_get_id => 400,
-        group_get_name => 401,
-        group_get_client => 402,
-        group_get_members => 403,
-        group_find_member => 404,
-        // 405
-        group_send_message => 406,
-        group_upload_image => 407,
-        group_quit => 408,
-        group_change_name => 409,
-        group_send_forward_message => 410,
-        group_invite => 411,
-
-        group_clone => 420,
-        group_drop => 421,
-
-        friend_get_id => 500,
-        friend_get_nickname => 501,
-        friend_get_client => 502,
-        friend_send_message => 503,
-        friend_upload_image => 504,
-
-        friend_clone => 520,
-        friend_drop => 521,
-
-        named_member_get_id => 600,
-        named_member_get_nickname => 601,
-        named_member_get_card_name => 602,
-        named_member_get_group => 603,
-        named_member_change_card_name => 604,
-
-        group_message_event_get_group => 10000,
-        group_message_event_get_message => 10001,
-        group_message_event_get_sender => 10002,
-
-        friend_message_event_get_friend => 10100,
-        friend_message_event_get_message => 10101,
-
-        image_get_id => 2000,
-        // flash => 2001
-        image_get_url => 2002,
-
-        log => 20000,
-
-        env_get_workspace => 30000,
-
-        message_chain_to_json => 30100,
-        message_chain_from_json => 30101,
-    };
+    let (vtable, capabilities) = load_vtable(manager.get_fun);
 
-    ATRI_VTABLE.write(vtable);
+    (*addr_of_mut!(ATRI_MANAGER)).write(manager);
+    (*addr_of_mut!(ATRI_VTABLE)).write(vtable);
+    (*addr_of_mut!(CAPABILITIES)).write(capabilities);
+    true
+}
+
+/// 宿主提供的函数, 可用于在不同版本的宿主间降级处理
+pub fn capabilities() -> &'static Capabilities {
+    unsafe { (*addr_of!(CAPABILITIES)).assume_init_ref() }
 }
 
 fn get_atri_manager() -> &'static AtriManager {
-    unsafe { ATRI_MANAGER.assume_init_ref() }
+    unsafe { (*addr_of!(ATRI_MANAGER)).assume_init_ref() }
 }
 
 pub(crate) fn get_plugin_manager() -> *const () {
@@ -201,5 +469,58 @@ pub(crate) fn get_plugin_handle() -> usize {
 }
 
 pub(crate) fn get_vtb() -> &'static AtriVTable {
-    unsafe { ATRI_VTABLE.assume_init_ref() }
+    unsafe { (*addr_of!(ATRI_VTABLE)).assume_init_ref() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn dummy() {}
+
+    extern "C" fn partial_get_fun(sig: u16) -> *const () {
+        match sig {
+            1 | 300 | 411 | 30101 => std::ptr::null(),
+            _ => dummy as *const (),
+        }
+    }
+
+    #[test]
+    fn missing_functions() {
+        let (vtable, capabilities) = load_vtable(partial_get_fun);
+
+        assert!(capabilities.group_send_message);
+        assert!(!capabilities.group_invite);
+        assert!(!capabilities.message_chain_from_json);
+        assert_eq!(
+            capabilities.missing(),
+            [
+                "plugin_manager_block_on",
+                "client_get_id",
+                "group_invite",
+                "message_chain_from_json"
+            ]
+        );
+
+        assert_eq!(
+            vtable.group_invite as usize,
+            fallback::group_invite as *const () as usize
+        );
+        assert_eq!(
+            vtable.group_send_message as usize,
+            dummy as *const () as usize
+        );
+
+        // 宿主未提供的函数返回空值, 运行时在当前线程执行
+        assert_eq!((vtable.client_get_id)(std::ptr::null()), 0);
+        let invite = (vtable.group_invite)(std::ptr::null(), 1);
+        let managed = (vtable.plugin_manager_block_on)(
+            std::ptr::null(),
+            FFIFuture::from(async move {
+                Managed::from_value(Result::<(), String>::from(invite.await))
+            }),
+        );
+        let result: Result<(), String> = unsafe { managed.into_value() };
+        assert_eq!(result, Err(String::from("not supported by the host")));
+    }
 }
@@ -0,0 +1,184 @@
+//! 宿主未提供的函数的替代实现, 返回空值而不终止宿主进程
+
+use crate::message::MessageChain;
+use atri_ffi::contact::{FFIGroupSettings, FFIMember};
+use atri_ffi::error::FFIResult;
+use atri_ffi::ffi::ForFFI;
+use atri_ffi::future::FFIFuture;
+use atri_ffi::message::{
+    FFIMessageChain, FFIMessageElement, MessageElementFlag, MessageElementUnion,
+};
+use atri_ffi::{
+    FFIOption, Handle, Managed, ManagedCloneable, PHandle, RustStr, RustString, RustVec,
+};
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
+use std::future::{ready, Future};
+use std::mem::ManuallyDrop;
+use std::pin::pin;
+use std::ptr::null;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// 宿主未提供函数时的返回值
+pub(super) trait Neutral {
+    fn neutral() -> Self;
+}
+
+macro_rules! neutral_default {
+    ($($t:ty),*) => {
+        $(
+            impl Neutral for $t {
+                fn neutral() -> Self {
+                    Default::default()
+                }
+            }
+        )*
+    };
+}
+
+neutral_default!((), bool, u8, u16, u32, u64, i32, i64);
+
+impl Neutral for Handle {
+    fn neutral() -> Self {
+        null()
+    }
+}
+
+/// 被借用为空句柄的句柄
+struct NullHandle(Handle);
+
+unsafe impl Sync for NullHandle {}
+
+static NULL_HANDLE: NullHandle = NullHandle(null());
+
+impl Neutral for PHandle {
+    fn neutral() -> Self {
+        &NULL_HANDLE.0
+    }
+}
+
+impl Neutral for RustStr {
+    fn neutral() -> Self {
+        RustStr::from("")
+    }
+}
+
+impl Neutral for RustString {
+    fn neutral() -> Self {
+        RustString::from(String::new())
+    }
+}
+
+impl<T> Neutral for RustVec<T> {
+    fn neutral() -> Self {
+        RustVec::from(Vec::new())
+    }
+}
+
+impl Neutral for Managed {
+    fn neutral() -> Self {
+        Managed::from_value(())
+    }
+}
+
+impl Neutral for ManagedCloneable {
+    fn neutral() -> Self {
+        unsafe { ManagedCloneable::null() }
+    }
+}
+
+impl Neutral for FFIMember {
+    fn neutral() -> Self {
+        FFIMember {
+            is_named: false,
+            inner: ManagedCloneable::neutral(),
+        }
+    }
+}
+
+impl Neutral for FFIGroupSettings {
+    fn neutral() -> Self {
+        FFIGroupSettings {
+            mute_all: false,
+            allow_anonymous: false,
+            allow_member_invite: false,
+        }
+    }
+}
+
+impl Neutral for FFIMessageChain {
+    fn neutral() -> Self {
+        MessageChain::default().into_ffi()
+    }
+}
+
+impl Neutral for FFIMessageElement {
+    fn neutral() -> Self {
+        FFIMessageElement {
+            t: MessageElementFlag::Unknown.value(),
+            union: MessageElementUnion {
+                unknown: ManuallyDrop::new(ManagedCloneable::neutral()),
+            },
+        }
+    }
+}
+
+impl<T> Neutral for FFIOption<T> {
+    fn neutral() -> Self {
+        FFIOption::from(None)
+    }
+}
+
+#[derive(Debug)]
+struct NotSupported;
+
+impl Display for NotSupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("not supported by the host")
+    }
+}
+
+impl std::error::Error for NotSupported {}
+
+impl<T> Neutral for FFIResult<T> {
+    fn neutral() -> Self {
+        FFIResult::from(Err::<T, _>(NotSupported))
+    }
+}
+
+impl<T: Neutral> Neutral for FFIFuture<T> {
+    fn neutral() -> Self {
+        FFIFuture::from(ready(T::neutral()))
+    }
+}
+
+/// 宿主未提供运行时, 在调用者的上下文中执行
+pub(super) extern "C" fn spawn(
+    _manager: *const (),
+    future: FFIFuture<Managed>,
+) -> FFIFuture<FFIResult<Managed>> {
+    FFIFuture::from(async move { FFIResult::from(Ok::<_, Infallible>(future.await)) })
+}
+
+/// 宿主未提供运行时, 在当前线程执行
+pub(super) extern "C" fn block_on(_manager: *const (), future: FFIFuture<Managed>) -> Managed {
+    struct Unparker(Thread);
+
+    impl Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unparker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+            return value;
+        }
+        thread::park();
+    }
+}
@@ -0,0 +1,331 @@
+//! 消息码: [`MessageChain`]可解析的字符串形式, 由[`MessageChain::to_code_string`]生成
+//!
+//! [`Display`](std::fmt::Display)输出的文本不会转义, 表情不含序号, Xml不含服务号, 文本中不含`$[`时可以解析.
+//!
+//! 文本原样输出, 其他元素写作`$[类型:参数]`, 如`$[At:名称(号码)]`, `$[AtAll]`, `$[Face:名称(序号)]`.
+//! 不含序号的`$[Face:名称]`解析为序号为`-1`的表情, 发送前需设置序号.
+//!
+//! 可解析的还有`$[Json:内容]`, `$[Xml:服务号:内容]`(省略服务号时为1), `$[LightApp:内容]`, `$[Poke:名称(类型)]`, `$[Dice:点数]`.
+//!
+//! 文本中的`$[`写作`\$[`, 参数中的`]`写作`\]`, `\`本身在有歧义时写作`\\`.
+
+use crate::error::{AtriError, AtriResult};
+use crate::message::at::At;
+use crate::message::face::Face;
+use crate::message::poke::{Dice, Poke};
+use crate::message::rich::{LightApp, RichCard};
+use crate::message::{MessageChain, MessageElement};
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+impl MessageChain {
+    /// 转为消息码, 文本与参数会被转义, 可由[`MessageChain::from_code_str`]解析
+    pub fn to_code_string(&self) -> String {
+        let mut s = String::new();
+        for elem in self {
+            s.push_str(&elem.to_code_string());
+        }
+        s
+    }
+
+    /// 解析消息码, 与[`MessageChain::to_code_string`]互逆
+    ///
+    /// 图片等需要由服务端构造的元素无法解析, 将返回错误
+    pub fn from_code_str(s: &str) -> AtriResult<Self> {
+        let mut elements = vec![];
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if matches!(chars.peek(), Some('\\' | '$')) => {
+                    text.extend(chars.next());
+                }
+                '$' if chars.peek() == Some(&'[') => {
+                    chars.next();
+                    if !text.is_empty() {
+                        elements.push(MessageElement::Text(std::mem::take(&mut text)));
+                    }
+                    let code = read_code(&mut chars)?;
+                    elements.push(parse_code(&code)?);
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            elements.push(MessageElement::Text(text));
+        }
+
+        Ok(elements.into_iter().collect())
+    }
+}
+
+impl FromStr for MessageChain {
+    type Err = AtriError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code_str(s)
+    }
+}
+
+pub(super) fn push_text(str: &mut String, text: &str) {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('\\' | '$') | None) => str.push_str("\\\\"),
+            ('$', Some('[')) => str.push_str("\\$"),
+            (c, _) => str.push(c),
+        }
+    }
+}
+
+pub(super) fn push_arg(str: &mut String, arg: &str) {
+    for c in arg.chars() {
+        if matches!(c, '\\' | ']') {
+            str.push('\\');
+        }
+        str.push(c);
+    }
+}
+
+fn read_code(chars: &mut Peekable<Chars>) -> AtriResult<String> {
+    let mut code = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            ']' => return Ok(code),
+            '\\' => code.extend(chars.next()),
+            c => code.push(c),
+        }
+    }
+
+    Err(syntax_error(format!("unterminated code: $[{}", code)))
+}
+
+fn parse_code(code: &str) -> AtriResult<MessageElement> {
+    let (kind, arg) = match code.split_once(':') {
+        Some((kind, arg)) => (kind, Some(arg)),
+        None => (code, None),
+    };
+
+    let elem = match (kind, arg) {
+        ("AtAll", None) => MessageElement::AtAll,
+        ("At", Some(arg)) => {
+            let (display, target) = split_id(arg)?;
+            MessageElement::At(At {
+                target,
+                display: display.into(),
+            })
+        }
+        ("Face", Some(arg)) => match split_id(arg) {
+            Ok((name, index)) => MessageElement::Face(Face {
+                index: int(index)?,
+                name: name.into(),
+            }),
+            Err(_) => MessageElement::Face(Face {
+                index: -1,
+                name: arg.into(),
+            }),
+        },
+        ("Json", Some(arg)) => MessageElement::RichCard(RichCard::Json(arg.into())),
+        ("Xml", Some(arg)) => {
+            let service = arg
+                .split_once(':')
+                .and_then(|(id, content)| Some((id.parse().ok()?, content)));
+            let (service_id, content) = service.unwrap_or((1, arg));
+            MessageElement::RichCard(RichCard::Xml {
+                service_id,
+                content: content.into(),
+            })
+        }
+        ("LightApp", Some(arg)) => MessageElement::LightApp(LightApp(arg.into())),
+        ("Poke", Some(arg)) => {
+            let (name, kind) = split_id(arg)?;
+            MessageElement::Poke(Poke {
+                kind: int(kind)?,
+                name: name.into(),
+            })
+        }
+        ("Dice", Some(arg)) => {
+            let value = arg
+                .parse()
+                .map_err(|_| syntax_error(format!("invalid dice: {}", arg)))?;
+            MessageElement::Dice(Dice(value))
+        }
+        _ => return Err(syntax_error(format!("unsupported code: $[{}]", code))),
+    };
+
+    Ok(elem)
+}
+
+/// 拆分`名称(号码)`
+fn split_id(arg: &str) -> AtriResult<(&str, i64)> {
+    arg.strip_suffix(')')
+        .and_then(|s| s.rsplit_once('('))
+        .and_then(|(name, id)| Some((name, id.parse().ok()?)))
+        .ok_or_else(|| syntax_error(format!("expected `name(id)`, found `{}`", arg)))
+}
+
+fn int(n: i64) -> AtriResult<i32> {
+    n.try_into()
+        .map_err(|_| syntax_error(format!("integer out of range: {}", n)))
+}
+
+fn syntax_error(msg: String) -> AtriError {
+    AtriError::SerializationError(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::at::At;
+    use crate::message::face::Face;
+    use crate::message::poke::{Dice, Poke};
+    use crate::message::rich::{LightApp, RichCard};
+    use crate::message::{MessageChain, MessageElement};
+
+    fn round_trip(s: &str) -> String {
+        MessageChain::from_code_str(s).unwrap().to_code_string()
+    }
+
+    #[test]
+    fn parse() {
+        let chain: MessageChain = "hi $[At:@爱丽丝(1)] $[AtAll]$[Face:微笑(14)]!"
+            .parse()
+            .unwrap();
+        let elements: Vec<_> = chain.into_iter().collect();
+
+        assert_eq!(elements.len(), 6);
+        assert!(matches!(&elements[0], MessageElement::Text(s) if s == "hi "));
+        assert!(
+            matches!(&elements[1], MessageElement::At(At { target: 1, display }) if display == "@爱丽丝")
+        );
+        assert!(matches!(elements[3], MessageElement::AtAll));
+        assert!(
+            matches!(&elements[4], MessageElement::Face(Face { index: 14, name }) if name == "微笑")
+        );
+        assert!(matches!(&elements[5], MessageElement::Text(s) if s == "!"));
+    }
+
+    #[test]
+    fn round_trip_chain() {
+        let chain: MessageChain = [
+            MessageElement::Text(r"price: $[10] C:\dir\".into()),
+            MessageElement::At(At {
+                target: 2,
+                display: r"@a]b(c)\".into(),
+            }),
+            MessageElement::Text(r"\$$[".into()),
+            MessageElement::Face(Face {
+                index: 0,
+                name: "惊讶".into(),
+            }),
+            MessageElement::AtAll,
+        ]
+        .into_iter()
+        .collect();
+
+        let s = chain.to_code_string();
+        assert_eq!(
+            s,
+            r"price: \$[10] C:\dir\\$[At:@a\]b(c)\\(2)]\\$\$[$[Face:惊讶(0)]$[AtAll]"
+        );
+        assert_eq!(round_trip(&s), s);
+        assert_eq!(
+            chain.to_string(),
+            r"price: $[10] C:\dir\$[At:@a]b(c)\(2)]\$$[$[Face:惊讶]$[AtAll]"
+        );
+
+        let parsed: Vec<_> = MessageChain::from_code_str(&s)
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert!(matches!(&parsed[0], MessageElement::Text(t) if t == r"price: $[10] C:\dir\"));
+        assert!(
+            matches!(&parsed[1], MessageElement::At(At { target: 2, display }) if display == r"@a]b(c)\")
+        );
+        assert!(matches!(&parsed[2], MessageElement::Text(t) if t == r"\$$["));
+    }
+
+    #[test]
+    fn rich_elements() {
+        let s = r#"$[Json:{"a":[1\]}]$[Xml:60:<msg/>]$[LightApp:{}]$[Poke:戳一戳(1)]$[Dice:6]"#;
+        let elements: Vec<_> = MessageChain::from_code_str(s)
+            .unwrap()
+            .into_iter()
+            .collect();
+
+        assert!(
+            matches!(&elements[0], MessageElement::RichCard(RichCard::Json(j)) if j == r#"{"a":[1]}"#)
+        );
+        assert!(matches!(
+            &elements[1],
+            MessageElement::RichCard(RichCard::Xml { service_id: 60, content }) if content == "<msg/>"
+        ));
+        assert!(matches!(&elements[2], MessageElement::LightApp(LightApp(c)) if c == "{}"));
+        assert!(
+            matches!(&elements[3], MessageElement::Poke(Poke { kind: 1, name }) if name == "戳一戳")
+        );
+        assert!(matches!(elements[4], MessageElement::Dice(Dice(6))));
+        assert_eq!(round_trip(s), s);
+    }
+
+    #[test]
+    fn parse_display() {
+        let chain: MessageChain = [
+            MessageElement::Text("hi ".into()),
+            MessageElement::At(At {
+                target: 1,
+                display: "@爱丽丝".into(),
+            }),
+            MessageElement::Face(Face {
+                index: 14,
+                name: "微笑".into(),
+            }),
+            MessageElement::RichCard(RichCard::Xml {
+                service_id: 60,
+                content: "<msg/>".into(),
+            }),
+            MessageElement::Dice(Dice(3)),
+        ]
+        .into_iter()
+        .collect();
+
+        let s = chain.to_string();
+        assert_eq!(s, "hi $[At:@爱丽丝(1)]$[Face:微笑]$[Xml:<msg/>]$[Dice:3]");
+
+        let parsed = MessageChain::from_code_str(&s).unwrap();
+        assert_eq!(parsed.to_string(), s);
+        let elements: Vec<_> = parsed.into_iter().collect();
+        assert!(
+            matches!(&elements[2], MessageElement::Face(Face { index: -1, name }) if name == "微笑")
+        );
+        // Display不含服务号
+        assert!(matches!(
+            &elements[3],
+            MessageElement::RichCard(RichCard::Xml { service_id: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn plain_text() {
+        for s in ["", "hello", r"C:\dir\file", "$5 [x] $", "a\\b"] {
+            assert_eq!(round_trip(s), s);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for s in [
+            "$[At:1]",
+            "$[At:x(y)]",
+            "$[AtAll:1]",
+            "$[Image:http://x]",
+            "$[Foo]",
+            "$[Dice:x]",
+            r"$[MarketFace:[贴贴\]]",
+            "$[At:a(1)",
+        ] {
+            assert!(MessageChain::from_code_str(s).is_err(), "{}", s);
+        }
+    }
+}
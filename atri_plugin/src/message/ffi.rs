@@ -1,7 +1,14 @@
 use crate::message::at::At;
 use crate::message::face::Face;
-use crate::message::image::Image;
+use crate::message::file::File;
+use crate::message::image::{FlashImage, Image};
+use crate::message::market_face::MarketFace;
 use crate::message::meta::{Anonymous, MessageMetadata, Reply};
+use crate::message::poke::{Dice, Poke};
+use crate::message::rich::{LightApp, RichCard};
+use crate::message::unknown::UnknownElement;
+use crate::message::video::Video;
+use crate::message::voice::Voice;
 use crate::message::{MessageChain, MessageElement};
 use atri_ffi::ffi::ForFFI;
 use atri_ffi::message::meta::{
@@ -79,23 +86,69 @@ impl ForFFI for MessageElement {
                     }),
                 },
             },
-            MessageElement::Unknown(ma) => FFIMessageElement {
-                t: 255,
+            MessageElement::Voice(voice) => FFIMessageElement {
+                t: MessageElementFlag::Voice.value(),
                 union: MessageElementUnion {
-                    unknown: ManuallyDrop::new(ma),
+                    voice: ManuallyDrop::new(voice.0),
                 },
             },
+            MessageElement::Video(video) => FFIMessageElement {
+                t: MessageElementFlag::Video.value(),
+                union: MessageElementUnion {
+                    video: ManuallyDrop::new(video.0),
+                },
+            },
+            MessageElement::File(file) => FFIMessageElement {
+                t: MessageElementFlag::File.value(),
+                union: MessageElementUnion {
+                    file: ManuallyDrop::new(file.0),
+                },
+            },
+            MessageElement::RichCard(card) => FFIMessageElement {
+                t: MessageElementFlag::RichCard.value(),
+                union: MessageElementUnion {
+                    rich_card: ManuallyDrop::new(card.into_ffi()),
+                },
+            },
+            MessageElement::Poke(poke) => FFIMessageElement {
+                t: MessageElementFlag::Poke.value(),
+                union: MessageElementUnion {
+                    poke: ManuallyDrop::new(poke.into_ffi()),
+                },
+            },
+            MessageElement::Dice(Dice(value)) => FFIMessageElement {
+                t: MessageElementFlag::Dice.value(),
+                union: MessageElementUnion { dice: value },
+            },
+            MessageElement::MarketFace(face) => FFIMessageElement {
+                t: MessageElementFlag::MarketFace.value(),
+                union: MessageElementUnion {
+                    market_face: Box::into_raw(Box::new(face.into_ffi())),
+                },
+            },
+            MessageElement::FlashImage(FlashImage(img)) => FFIMessageElement {
+                t: MessageElementFlag::FlashImage.value(),
+                union: MessageElementUnion {
+                    flash_image: ManuallyDrop::new(img.0),
+                },
+            },
+            MessageElement::LightApp(LightApp(content)) => FFIMessageElement {
+                t: MessageElementFlag::LightApp.value(),
+                union: MessageElementUnion {
+                    light_app: ManuallyDrop::new(RustString::from(content)),
+                },
+            },
+            MessageElement::Unknown(unknown) => unknown.into_ffi(),
         }
     }
 
     fn from_ffi(value: Self::FFIValue) -> Self {
         unsafe {
-            match MessageElementFlag::try_from(value.t).unwrap_or_else(|t| {
-                panic!(
-                    "Unknown message flag: {}, please update your atri_plugin crate",
-                    t
-                )
-            }) {
+            let Ok(flag) = MessageElementFlag::try_from(value.t) else {
+                return Self::Unknown(UnknownElement::from_ffi(value.t, value));
+            };
+
+            match flag {
                 MessageElementFlag::Text => {
                     Self::Text(ManuallyDrop::into_inner(value.union.text).into())
                 }
@@ -115,8 +168,33 @@ impl ForFFI for MessageElement {
 
                     Self::Face(Face { index, name })
                 }
+                MessageElementFlag::Voice => {
+                    Self::Voice(Voice(ManuallyDrop::into_inner(value.union.voice)))
+                }
+                MessageElementFlag::Video => {
+                    Self::Video(Video(ManuallyDrop::into_inner(value.union.video)))
+                }
+                MessageElementFlag::File => {
+                    Self::File(File(ManuallyDrop::into_inner(value.union.file)))
+                }
+                MessageElementFlag::RichCard => Self::RichCard(RichCard::from_ffi(
+                    ManuallyDrop::into_inner(value.union.rich_card),
+                )),
+                MessageElementFlag::Poke => {
+                    Self::Poke(Poke::from_ffi(ManuallyDrop::into_inner(value.union.poke)))
+                }
+                MessageElementFlag::Dice => Self::Dice(Dice(value.union.dice)),
+                MessageElementFlag::MarketFace => Self::MarketFace(MarketFace::from_ffi(
+                    *Box::from_raw(value.union.market_face),
+                )),
+                MessageElementFlag::FlashImage => Self::FlashImage(FlashImage(Image(
+                    ManuallyDrop::into_inner(value.union.flash_image),
+                ))),
+                MessageElementFlag::LightApp => Self::LightApp(LightApp(
+                    ManuallyDrop::into_inner(value.union.light_app).into(),
+                )),
                 MessageElementFlag::Unknown => {
-                    Self::Unknown(ManuallyDrop::into_inner(value.union.unknown))
+                    Self::Unknown(UnknownElement::from_ffi(value.t, value))
                 }
             }
         }
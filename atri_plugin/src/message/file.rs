@@ -0,0 +1,40 @@
+use crate::error::{AtriError, AtriResult};
+use crate::loader::{ensure_supported, get_vtb};
+use crate::message::{MessageElement, PushMessage};
+use atri_ffi::{ManagedCloneable, RustVec};
+
+/// 消息中的文件, 如离线文件或群文件
+#[derive(Clone)]
+pub struct File(pub(crate) ManagedCloneable);
+
+impl File {
+    pub fn name(&self) -> &str {
+        let rs = (get_vtb().file_get_name)(self.0.pointer);
+        rs.as_str()
+    }
+
+    /// 文件大小, 单位为字节
+    pub fn size(&self) -> u64 {
+        (get_vtb().file_get_size)(self.0.pointer)
+    }
+
+    pub fn url(&self) -> String {
+        (get_vtb().file_get_url)(self.0.pointer).into()
+    }
+
+    pub async fn download(&self) -> AtriResult<Vec<u8>> {
+        ensure_supported!(file_download);
+
+        let fu = { (get_vtb().file_download)(self.0.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(RustVec::into_vec)
+            .map_err(AtriError::ClientError)
+    }
+}
+
+impl PushMessage for File {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::File(self));
+    }
+}
@@ -16,7 +16,11 @@ impl ForwardMessage {
         self.0.len()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<ForwardNode> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ForwardNode> {
         self.0.iter()
     }
 
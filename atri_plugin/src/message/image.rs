@@ -15,6 +15,11 @@ impl Image {
         let rs = (get_vtb().image_get_url)(self.0.pointer);
         rs.into()
     }
+
+    /// 作为闪照发送
+    pub fn flash(self) -> FlashImage {
+        FlashImage(self)
+    }
 }
 
 impl PushMessage for Image {
@@ -22,3 +27,19 @@ impl PushMessage for Image {
         v.push(MessageElement::Image(self));
     }
 }
+
+/// 闪照
+#[derive(Clone)]
+pub struct FlashImage(pub Image);
+
+impl FlashImage {
+    pub fn image(&self) -> &Image {
+        &self.0
+    }
+}
+
+impl PushMessage for FlashImage {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::FlashImage(self));
+    }
+}
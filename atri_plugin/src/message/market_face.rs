@@ -0,0 +1,53 @@
+use crate::message::{MessageElement, PushMessage};
+
+/// 商城表情
+#[derive(Clone, Debug, Default)]
+pub struct MarketFace {
+    pub name: String,
+    pub face_id: Vec<u8>,
+    pub tab_id: u32,
+    pub item_type: u32,
+    pub sub_type: u32,
+    pub media_type: u32,
+    pub encrypt_key: Vec<u8>,
+}
+
+impl PushMessage for MarketFace {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::MarketFace(self));
+    }
+}
+
+mod ffi {
+    use crate::message::market_face::MarketFace;
+    use atri_ffi::ffi::ForFFI;
+    use atri_ffi::message::FFIMarketFace;
+
+    impl ForFFI for MarketFace {
+        type FFIValue = FFIMarketFace;
+
+        fn into_ffi(self) -> Self::FFIValue {
+            FFIMarketFace {
+                name: self.name.into(),
+                face_id: self.face_id.into(),
+                tab_id: self.tab_id,
+                item_type: self.item_type,
+                sub_type: self.sub_type,
+                media_type: self.media_type,
+                encrypt_key: self.encrypt_key.into(),
+            }
+        }
+
+        fn from_ffi(ffi: Self::FFIValue) -> Self {
+            Self {
+                name: ffi.name.into(),
+                face_id: ffi.face_id.into_vec(),
+                tab_id: ffi.tab_id,
+                item_type: ffi.item_type,
+                sub_type: ffi.sub_type,
+                media_type: ffi.media_type,
+                encrypt_key: ffi.encrypt_key.into_vec(),
+            }
+        }
+    }
+}
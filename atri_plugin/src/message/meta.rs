@@ -1,4 +1,4 @@
-use crate::message::MessageElement;
+use crate::message::{MessageChain, MessageElement};
 
 #[derive(Debug, Clone, Default)]
 pub struct MessageReceipt {
@@ -17,6 +17,17 @@ pub struct MessageMetadata {
     pub reply: Option<Reply>,
 }
 
+impl MessageMetadata {
+    /// 以该消息的序号构造回执, 可用于撤回收到的消息
+    pub fn receipt(&self) -> MessageReceipt {
+        MessageReceipt {
+            seqs: self.seqs.clone(),
+            rands: self.rands.clone(),
+            time: self.time as i64,
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct Reply {
     pub reply_seq: i32,
@@ -25,6 +36,24 @@ pub struct Reply {
     pub elements: Vec<MessageElement>,
 }
 
+impl Reply {
+    /// 以回执引用已发送或收到的消息, `sender`为原消息的发送者, `message`为引用中展示的内容
+    ///
+    /// 回执中没有序号时返回`None`
+    pub fn from_receipt<M: Into<MessageChain>>(
+        receipt: &MessageReceipt,
+        sender: i64,
+        message: M,
+    ) -> Option<Self> {
+        Some(Self {
+            reply_seq: *receipt.seqs.first()?,
+            sender,
+            time: receipt.time as i32,
+            elements: message.into().into_iter().collect(),
+        })
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Anonymous {
     pub anon_id: Vec<u8>,
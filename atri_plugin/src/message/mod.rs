@@ -1,21 +1,37 @@
 pub mod at;
+mod code;
 pub mod face;
 mod ffi;
+pub mod file;
 pub mod forward;
 pub mod image;
 pub mod macros;
+pub mod market_face;
 pub mod meta;
+pub mod poke;
+pub mod rich;
+pub mod unknown;
+pub mod video;
+pub mod voice;
 
-use atri_ffi::{ManagedCloneable, RustStr};
+use atri_ffi::RustStr;
 
+use crate::contact::Contact;
 use crate::error::{AtriError, AtriResult};
-use crate::loader::get_vtb;
+use crate::loader::{ensure_supported, get_vtb};
 use crate::message::at::At;
 use crate::message::face::Face;
-use crate::message::image::Image;
+use crate::message::file::File;
+use crate::message::image::{FlashImage, Image};
+use crate::message::market_face::MarketFace;
 use crate::message::meta::{Anonymous, MessageMetadata, Reply};
+use crate::message::poke::{Dice, Poke};
+use crate::message::rich::{LightApp, RichCard};
+use crate::message::unknown::UnknownElement;
+use crate::message::video::Video;
+use crate::message::voice::Voice;
 use atri_ffi::ffi::ForFFI;
-use std::fmt::Write;
+use std::fmt::{Display, Formatter, Write};
 use std::slice::Iter;
 use std::{mem, vec};
 
@@ -30,16 +46,32 @@ impl MessageChain {
         MessageChainBuilder::new()
     }
 
-    pub fn iter(&self) -> Iter<MessageElement> {
+    pub fn iter(&self) -> Iter<'_, MessageElement> {
         self.into_iter()
     }
 
-    pub fn into_reply(self) -> Reply {
-        Reply {
-            reply_seq: self.meta.seqs[0],
+    /// 构造引用本消息的回复, 本消息没有序号(如自行构造的消息)时返回`None`
+    pub fn into_reply(self) -> Option<Reply> {
+        Some(Reply {
+            reply_seq: *self.meta.seqs.first()?,
             sender: self.meta.sender,
             time: self.meta.time,
             elements: self.elements,
+        })
+    }
+
+    /// 本消息引用的消息, 完整的原消息可通过[`MessageChain::resolve_reply`]获取
+    pub fn reply(&self) -> Option<&Reply> {
+        self.meta.reply.as_ref()
+    }
+
+    /// 从宿主的消息缓存中获取本消息引用的原消息, `contact`为消息所在的群或好友
+    ///
+    /// 本消息未引用消息或原消息不在缓存中时为`None`
+    pub async fn resolve_reply(&self, contact: &Contact) -> AtriResult<Option<MessageChain>> {
+        match self.reply() {
+            Some(reply) => contact.get_message(reply.reply_seq).await,
+            None => Ok(None),
         }
     }
 
@@ -51,6 +83,11 @@ impl MessageChain {
         &mut self.meta
     }
 
+    /// 撤回收到的消息, `contact`为消息所在的群或好友
+    pub async fn recall(&self, contact: &Contact) -> AtriResult<()> {
+        contact.recall(&self.meta.receipt()).await
+    }
+
     pub fn with_reply(&mut self, reply: Reply) {
         self.metadata_mut().reply = Some(reply);
     }
@@ -64,6 +101,8 @@ impl MessageChain {
     }
 
     pub fn from_json(json: &str) -> AtriResult<Self> {
+        ensure_supported!(message_chain_from_json);
+
         let rs = RustStr::from(json);
         let result = (get_vtb().message_chain_from_json)(rs);
         Result::from(result)
@@ -72,6 +111,15 @@ impl MessageChain {
     }
 }
 
+impl FromIterator<MessageElement> for MessageChain {
+    fn from_iter<T: IntoIterator<Item = MessageElement>>(iter: T) -> Self {
+        Self {
+            meta: MessageMetadata::default(),
+            elements: iter.into_iter().collect(),
+        }
+    }
+}
+
 impl IntoIterator for MessageChain {
     type Item = MessageElement;
     type IntoIter = vec::IntoIter<Self::Item>;
@@ -90,50 +138,114 @@ impl<'a> IntoIterator for &'a MessageChain {
     }
 }
 
-impl ToString for MessageChain {
-    fn to_string(&self) -> String {
+impl Display for MessageChain {
+    /// 便于阅读的形式, 文本不会转义, 需要解析时应使用[`MessageChain::to_code_string`]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut s = String::new();
         for value in self {
-            value.push_to_string(&mut s);
+            value.push_to_string(&mut s, false);
         }
-        s
+        f.write_str(&s)
     }
 }
 
 #[derive(Clone)]
+#[non_exhaustive]
 pub enum MessageElement {
     Text(String),
     Image(Image),
     At(At),
     AtAll,
     Face(Face),
-    Unknown(ManagedCloneable),
+    Voice(Voice),
+    Video(Video),
+    File(File),
+    RichCard(RichCard),
+    Poke(Poke),
+    Dice(Dice),
+    MarketFace(MarketFace),
+    FlashImage(FlashImage),
+    LightApp(LightApp),
+    Unknown(UnknownElement),
 }
 
 impl MessageElement {
-    fn push_to_string(&self, str: &mut String) {
+    /// 消息码形式, 可由[`MessageChain::from_code_str`]解析
+    pub fn to_code_string(&self) -> String {
+        let mut s = String::new();
+        self.push_to_string(&mut s, true);
+        s
+    }
+
+    /// `code`为`true`时转义文本与参数, 并写出表情的序号
+    fn push_to_string(&self, str: &mut String, code: bool) {
+        let text = |str: &mut String, text: &str| match code {
+            true => code::push_text(str, text),
+            false => str.push_str(text),
+        };
+        let arg = |str: &mut String, arg: &str| match code {
+            true => code::push_arg(str, arg),
+            false => str.push_str(arg),
+        };
+        let code_with_arg = |str: &mut String, kind: &str, a: &str| {
+            let _ = write!(str, "$[{}:", kind);
+            arg(str, a);
+            str.push(']');
+        };
+
         match self {
-            Self::Text(text) => str.push_str(text),
-            Self::Image(img) => {
-                let _ = write!(str, "$[Image:{}]", img.url());
-            }
+            Self::Text(s) => text(str, s),
+            Self::Image(img) => code_with_arg(str, "Image", &img.url()),
             Self::At(At { target, display }) => {
-                let _ = write!(str, "$[At:{}({})]", display, target);
+                str.push_str("$[At:");
+                arg(str, display);
+                let _ = write!(str, "({})]", target);
             }
             Self::AtAll => str.push_str("$[AtAll]"),
             Self::Face(face) => {
-                let _ = write!(str, "$[Face:{}]", face.name);
+                str.push_str("$[Face:");
+                arg(str, &face.name);
+                if code {
+                    let _ = write!(str, "({})", face.index);
+                }
+                str.push(']');
+            }
+            Self::Voice(voice) => code_with_arg(str, "Voice", &voice.url()),
+            Self::Video(video) => code_with_arg(str, "Video", video.name()),
+            Self::File(file) => code_with_arg(str, "File", file.name()),
+            Self::RichCard(RichCard::Json(content)) => code_with_arg(str, "Json", content),
+            Self::RichCard(RichCard::Xml {
+                service_id,
+                content,
+            }) => {
+                str.push_str("$[Xml:");
+                if code {
+                    let _ = write!(str, "{}:", service_id);
+                }
+                arg(str, content);
+                str.push(']');
             }
+            Self::Poke(Poke { kind, name }) => {
+                str.push_str("$[Poke:");
+                arg(str, name);
+                let _ = write!(str, "({})]", kind);
+            }
+            Self::Dice(Dice(value)) => {
+                let _ = write!(str, "$[Dice:{}]", value);
+            }
+            Self::MarketFace(face) => code_with_arg(str, "MarketFace", &face.name),
+            Self::FlashImage(FlashImage(img)) => code_with_arg(str, "FlashImage", &img.url()),
+            Self::LightApp(LightApp(content)) => code_with_arg(str, "LightApp", content),
             Self::Unknown(_) => {}
         }
     }
 }
 
-impl ToString for MessageElement {
-    fn to_string(&self) -> String {
+impl Display for MessageElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut s = String::new();
-        self.push_to_string(&mut s);
-        s
+        self.push_to_string(&mut s, false);
+        f.write_str(&s)
     }
 }
 
@@ -227,3 +339,80 @@ impl PushMessage for MessageChainBuilder {
         v.extend(chain.elements);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MessageChain;
+    use crate::contact::member::Member;
+    use crate::contact::Contact;
+    use crate::error::AtriError;
+    use crate::message::meta::{MessageReceipt, Reply};
+    use crate::testing::{block_on, fixture};
+
+    #[test]
+    fn quote_messages() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let e = host.group_message_event(&group, 1, "原消息").unwrap();
+        let seq = e.message().metadata().seqs[0];
+        assert!(e.message().reply().is_none());
+        assert!(MessageChain::from("自行构造").into_reply().is_none());
+
+        block_on(async {
+            let original = group.get_message(seq).await.unwrap().unwrap();
+            assert_eq!(original.to_string(), "原消息");
+            assert_eq!(original.metadata().sender, 1);
+            assert!(group.get_message(-1).await.unwrap().is_none());
+
+            let mut chain = MessageChain::from("回复");
+            chain.with_reply(e.message().into_reply().unwrap());
+            let receipt = group.send_message(chain).await.unwrap();
+
+            let sent = group.get_message(receipt.seqs[0]).await.unwrap().unwrap();
+            assert_eq!(sent.metadata().sender, client.id());
+            let quoted = sent.reply().unwrap();
+            assert_eq!((quoted.reply_seq, quoted.sender), (seq, 1));
+            let contact = Contact::Group(group.clone());
+            let resolved = sent.resolve_reply(&contact).await.unwrap().unwrap();
+            assert_eq!(resolved.to_string(), "原消息");
+            assert!(original.resolve_reply(&contact).await.unwrap().is_none());
+
+            let reply = Reply::from_receipt(&receipt, client.id(), "回复").unwrap();
+            let mut builder = MessageChain::builder();
+            builder.push_str("再次引用");
+            builder.with_reply(reply);
+            group.send_message(builder.build()).await.unwrap();
+        });
+
+        let friend = client.find_friend(3).unwrap();
+        let e = host.friend_message_event(&friend, "好友消息");
+        let seq = e.message().metadata().seqs[0];
+        let contact = Contact::Friend(friend.clone());
+
+        block_on(async {
+            let original = friend.get_message(seq).await.unwrap().unwrap();
+            assert_eq!(original.to_string(), "好友消息");
+
+            let mut chain = MessageChain::from("回复");
+            chain.with_reply(e.message().into_reply().unwrap());
+            let receipt = friend.send_message(chain).await.unwrap();
+
+            let sent = friend.get_message(receipt.seqs[0]).await.unwrap().unwrap();
+            let resolved = sent.resolve_reply(&contact).await.unwrap().unwrap();
+            assert_eq!(resolved.to_string(), "好友消息");
+
+            let group = client.find_group(123).unwrap();
+            let member = Contact::Member(Member::Named(group.find_member(1).await.unwrap()));
+            assert!(matches!(
+                sent.resolve_reply(&member).await,
+                Err(AtriError::NotSupported)
+            ));
+        });
+
+        let quoted = host.group_messages(123)[1].reply().unwrap().clone();
+        assert_eq!(quoted.sender, 10000);
+        assert_eq!(MessageChain::from_iter(quoted.elements).to_string(), "回复");
+        assert!(Reply::from_receipt(&MessageReceipt::default(), 1, "").is_none());
+    }
+}
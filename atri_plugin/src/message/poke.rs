@@ -0,0 +1,50 @@
+use crate::message::{MessageElement, PushMessage};
+
+/// 戳一戳
+#[derive(Clone, Debug)]
+pub struct Poke {
+    pub kind: i32,
+    pub name: String,
+}
+
+impl PushMessage for Poke {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::Poke(self));
+    }
+}
+
+/// 骰子, 点数为1到6
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dice(pub i32);
+
+impl PushMessage for Dice {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::Dice(self));
+    }
+}
+
+mod ffi {
+    use crate::message::poke::Poke;
+    use atri_ffi::ffi::ForFFI;
+    use atri_ffi::message::FFIPoke;
+
+    impl ForFFI for Poke {
+        type FFIValue = FFIPoke;
+
+        fn into_ffi(self) -> Self::FFIValue {
+            let Self { kind, name } = self;
+
+            FFIPoke {
+                kind,
+                name: name.into(),
+            }
+        }
+
+        fn from_ffi(FFIPoke { kind, name }: Self::FFIValue) -> Self {
+            Self {
+                kind,
+                name: name.into(),
+            }
+        }
+    }
+}
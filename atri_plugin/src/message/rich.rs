@@ -0,0 +1,136 @@
+use crate::message::{MessageElement, PushMessage};
+
+/// json或xml卡片
+#[derive(Clone, Debug)]
+pub enum RichCard {
+    Json(String),
+    Xml { service_id: i32, content: String },
+}
+
+impl RichCard {
+    pub fn content(&self) -> &str {
+        match self {
+            Self::Json(content) | Self::Xml { content, .. } => content,
+        }
+    }
+}
+
+impl PushMessage for RichCard {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::RichCard(self));
+    }
+}
+
+/// 小程序, 内容为json
+#[derive(Clone, Debug)]
+pub struct LightApp(pub String);
+
+impl PushMessage for LightApp {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::LightApp(self));
+    }
+}
+
+mod ffi {
+    use crate::message::rich::RichCard;
+    use atri_ffi::ffi::ForFFI;
+    use atri_ffi::message::FFIRichCard;
+
+    impl ForFFI for RichCard {
+        type FFIValue = FFIRichCard;
+
+        fn into_ffi(self) -> Self::FFIValue {
+            let (is_xml, service_id, content) = match self {
+                Self::Json(content) => (false, 0, content),
+                Self::Xml {
+                    service_id,
+                    content,
+                } => (true, service_id, content),
+            };
+
+            FFIRichCard {
+                is_xml,
+                service_id,
+                content: content.into(),
+            }
+        }
+
+        fn from_ffi(
+            FFIRichCard {
+                is_xml,
+                service_id,
+                content,
+            }: Self::FFIValue,
+        ) -> Self {
+            let content = String::from(content);
+            if is_xml {
+                Self::Xml {
+                    service_id,
+                    content,
+                }
+            } else {
+                Self::Json(content)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LightApp, RichCard};
+    use crate::message::market_face::MarketFace;
+    use crate::message::poke::{Dice, Poke};
+    use crate::message::{MessageChain, MessageElement};
+    use crate::testing::{block_on, fixture};
+
+    #[test]
+    fn rich_elements() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        block_on(async {
+            let img = group.upload_image(vec![1, 2, 3]).await.unwrap();
+            let mut chain = MessageChain::builder();
+            chain
+                .push(RichCard::Xml {
+                    service_id: 35,
+                    content: "<msg/>".into(),
+                })
+                .push(Poke {
+                    kind: 1,
+                    name: "戳一戳".into(),
+                })
+                .push(Dice(3))
+                .push(MarketFace {
+                    name: "[贴贴]".into(),
+                    face_id: vec![1, 2],
+                    ..MarketFace::default()
+                })
+                .push(img.flash())
+                .push(LightApp("{}".into()));
+            group.send_message(chain.build()).await.unwrap();
+        });
+
+        let sent = &host.group_messages(123)[0];
+        let elements: Vec<_> = sent
+            .iter()
+            .filter(|e| !matches!(e, MessageElement::Text(s) if s.is_empty()))
+            .collect();
+        assert!(matches!(
+            elements[0],
+            MessageElement::RichCard(RichCard::Xml { service_id: 35, .. })
+        ));
+        assert!(matches!(
+            elements[1],
+            MessageElement::Poke(Poke { kind: 1, .. })
+        ));
+        assert!(matches!(elements[2], MessageElement::Dice(Dice(3))));
+        assert!(matches!(elements[3], MessageElement::MarketFace(f) if f.face_id == [1, 2]));
+        assert!(
+            matches!(elements[4], MessageElement::FlashImage(f) if f.image().url().starts_with("https://mock.atri/image/"))
+        );
+        assert!(sent.to_string().starts_with(
+            "$[Xml:<msg/>]$[Poke:戳一戳(1)]$[Dice:3]$[MarketFace:[贴贴]]$[FlashImage:"
+        ));
+    }
+}
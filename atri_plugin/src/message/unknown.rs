@@ -0,0 +1,110 @@
+use crate::loader::{capabilities, get_vtb};
+use crate::message::{MessageElement, PushMessage};
+use atri_ffi::message::{FFIMessageElement, MessageElementFlag, MessageElementUnion};
+use atri_ffi::ManagedCloneable;
+use std::mem::ManuallyDrop;
+
+/// 无法识别的元素, 通常来自更新版本的宿主, 可原样发送
+#[derive(Clone)]
+pub struct UnknownElement {
+    raw_flag: u8,
+    inner: ManagedCloneable,
+}
+
+impl UnknownElement {
+    /// 元素的原始类型, 为[`MessageElementFlag::Unknown`]时表示宿主也无法识别该元素
+    pub fn raw_flag(&self) -> u8 {
+        self.raw_flag
+    }
+
+    /// 由宿主序列化的json, 宿主不支持或无法序列化时为`None`
+    pub fn to_json(&self) -> Option<String> {
+        if !self.is_wrapped() || !capabilities().message_element_to_json {
+            return None;
+        }
+
+        let json: String = (get_vtb().message_element_to_json)(self.inner.pointer).into();
+        (!json.is_empty()).then_some(json)
+    }
+
+    /// 是否为交由宿主保管的元素
+    fn is_wrapped(&self) -> bool {
+        self.raw_flag != MessageElementFlag::Unknown.value() && !self.inner.pointer.is_null()
+    }
+
+    pub(crate) fn from_ffi(raw_flag: u8, elem: FFIMessageElement) -> Self {
+        if raw_flag == MessageElementFlag::Unknown.value() {
+            let inner = unsafe { ManuallyDrop::into_inner(elem.union.unknown) };
+            return Self { raw_flag, inner };
+        }
+
+        let inner = if capabilities().message_element_wrap {
+            (get_vtb().message_element_wrap)(elem)
+        } else {
+            // 宿主无法保管该元素, 只能放弃其内容
+            crate::warn!(
+                "宿主不支持保管未知消息元素, 类型为{}的元素内容已丢弃",
+                raw_flag
+            );
+            unsafe { ManagedCloneable::null() }
+        };
+
+        Self { raw_flag, inner }
+    }
+
+    pub(crate) fn into_ffi(self) -> FFIMessageElement {
+        if self.is_wrapped() && capabilities().message_element_unwrap {
+            return (get_vtb().message_element_unwrap)(self.inner.pointer);
+        }
+
+        FFIMessageElement {
+            t: MessageElementFlag::Unknown.value(),
+            union: MessageElementUnion {
+                unknown: ManuallyDrop::new(self.inner),
+            },
+        }
+    }
+}
+
+impl PushMessage for UnknownElement {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::Unknown(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{MessageChain, MessageElement};
+    use crate::testing::{block_on, fixture};
+    use atri_ffi::ffi::ForFFI;
+    use atri_ffi::message::{FFIMessageElement, MessageElementUnion};
+    use atri_ffi::ManagedCloneable;
+    use std::mem::ManuallyDrop;
+
+    #[test]
+    fn unknown_element() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let elem = MessageElement::from_ffi(FFIMessageElement {
+            t: 200,
+            union: MessageElementUnion {
+                unknown: ManuallyDrop::new(ManagedCloneable::from_value(42)),
+            },
+        });
+        let MessageElement::Unknown(unknown) = &elem else {
+            panic!("expected unknown element");
+        };
+        assert_eq!(unknown.raw_flag(), 200);
+        assert_eq!(
+            unknown.to_json().as_deref(),
+            Some(r#"{"type":"Unknown","flag":200}"#)
+        );
+        assert_eq!(elem.to_string(), "");
+
+        block_on(group.send_message(MessageChain::from_iter([elem]))).unwrap();
+
+        let sent: Vec<_> = host.group_messages(123)[0].iter().cloned().collect();
+        assert!(matches!(&sent[..], [MessageElement::Unknown(u)] if u.raw_flag() == 200));
+    }
+}
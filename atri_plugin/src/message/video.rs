@@ -0,0 +1,85 @@
+use crate::error::{AtriError, AtriResult};
+use crate::loader::{ensure_supported, get_vtb};
+use crate::message::{MessageElement, PushMessage};
+use atri_ffi::{ManagedCloneable, RustVec};
+
+/// 消息中的视频
+#[derive(Clone)]
+pub struct Video(pub(crate) ManagedCloneable);
+
+impl Video {
+    pub fn name(&self) -> &str {
+        let rs = (get_vtb().video_get_name)(self.0.pointer);
+        rs.as_str()
+    }
+
+    /// 文件大小, 单位为字节
+    pub fn size(&self) -> u64 {
+        (get_vtb().video_get_size)(self.0.pointer)
+    }
+
+    pub fn url(&self) -> String {
+        (get_vtb().video_get_url)(self.0.pointer).into()
+    }
+
+    pub async fn download(&self) -> AtriResult<Vec<u8>> {
+        ensure_supported!(video_download);
+
+        let fu = { (get_vtb().video_download)(self.0.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(RustVec::into_vec)
+            .map_err(AtriError::ClientError)
+    }
+}
+
+impl PushMessage for Video {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::Video(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::GroupMessageEvent;
+    use crate::listener::Listener;
+    use crate::message::{MessageChain, MessageElement};
+    use crate::testing::fixture;
+
+    #[test]
+    fn video_and_file() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _l = Listener::listening_on_always(|e: GroupMessageEvent| async move {
+            for elem in e.message() {
+                let (name, data) = match elem {
+                    MessageElement::Video(video) => {
+                        (video.name().to_owned(), video.download().await)
+                    }
+                    MessageElement::File(file) => (file.name().to_owned(), file.download().await),
+                    _ => continue,
+                };
+                let size = data.unwrap().len();
+                e.group()
+                    .send_message(format!("{}: {}", name, size))
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let mut chain = MessageChain::builder();
+        chain.push(host.video("a.mp4", vec![0; 16]));
+        chain.push(host.file("b.zip", vec![0; 8]));
+        let chain = chain.build();
+        assert_eq!(chain.to_string(), "$[Video:a.mp4]$[File:b.zip]");
+
+        host.dispatch(host.group_message_event(&group, 1, chain).unwrap());
+        let replies: Vec<_> = host
+            .group_messages(123)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(replies, ["a.mp4: 16", "b.zip: 8"]);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::{AtriError, AtriResult};
+use crate::loader::{ensure_supported, get_vtb};
+use crate::message::{MessageElement, PushMessage};
+use atri_ffi::{ManagedCloneable, RustVec};
+use std::time::Duration;
+
+/// 语音消息, 可由[`Group::upload_voice`](crate::contact::group::Group::upload_voice)上传获得
+#[derive(Clone)]
+pub struct Voice(pub(crate) ManagedCloneable);
+
+impl Voice {
+    pub fn url(&self) -> String {
+        (get_vtb().voice_get_url)(self.0.pointer).into()
+    }
+
+    pub fn md5(&self) -> Vec<u8> {
+        (get_vtb().voice_get_md5)(self.0.pointer).into_vec()
+    }
+
+    /// 文件大小, 单位为字节
+    pub fn size(&self) -> u32 {
+        (get_vtb().voice_get_size)(self.0.pointer)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs((get_vtb().voice_get_duration)(self.0.pointer))
+    }
+
+    /// 下载语音文件
+    pub async fn download(&self) -> AtriResult<Vec<u8>> {
+        ensure_supported!(voice_download);
+
+        let fu = { (get_vtb().voice_download)(self.0.pointer) };
+        let result = crate::runtime::spawn(fu).await.unwrap();
+        Result::from(result)
+            .map(RustVec::into_vec)
+            .map_err(AtriError::ClientError)
+    }
+}
+
+impl PushMessage for Voice {
+    fn push_to(self, v: &mut Vec<MessageElement>) {
+        v.push(MessageElement::Voice(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::GroupMessageEvent;
+    use crate::listener::Listener;
+    use crate::message::MessageElement;
+    use crate::testing::{block_on, fixture, Action};
+    use std::time::Duration;
+
+    #[test]
+    fn voice() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+        let data = vec![7; 2048];
+
+        let voice = block_on(async {
+            let voice = group.upload_voice(data.clone()).await.unwrap();
+            assert_eq!(voice.size(), 2048);
+            assert_eq!(voice.md5().len(), 16);
+            assert_eq!(voice.duration(), Duration::from_secs(2));
+            assert_eq!(voice.download().await.unwrap(), data);
+
+            friend.upload_voice(vec![1]).await.unwrap();
+            group.send_message(voice.clone()).await.unwrap();
+            voice
+        });
+
+        let url = voice.url();
+        assert!(matches!(
+            &host.transcript()[..2],
+            [
+                Action::GroupUploadVoice { group: 123, data: a },
+                Action::FriendUploadVoice { friend: 3, data: b },
+            ] if *a == data && *b == [1]
+        ));
+        assert_eq!(
+            host.group_messages(123)[0].to_string(),
+            format!("$[Voice:{}]", url)
+        );
+
+        let _l = Listener::listening_on_always(|e: GroupMessageEvent| async move {
+            let chain = e.message();
+            let Some(MessageElement::Voice(voice)) = chain.iter().next() else {
+                return;
+            };
+            let data = voice.download().await.unwrap();
+            e.group()
+                .send_message(format!("{} bytes", data.len()))
+                .await
+                .unwrap();
+        });
+        host.dispatch(host.group_message_event(&group, 1, voice).unwrap());
+        assert_eq!(host.group_messages(123)[1].to_string(), "2048 bytes");
+    }
+}
@@ -1,7 +1,14 @@
-pub use atri_ffi::plugin::PluginInstance;
+use crate::command::{Command, CommandRegistry};
+use crate::listener::ListenerGuard;
 use atri_ffi::plugin::PluginVTable;
-use atri_ffi::RustStr;
+pub use atri_ffi::plugin::{PluginInfo, PluginInstance};
+use atri_ffi::{RustStr, RustVec};
+use std::sync::{Mutex, OnceLock};
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is marked with `#[plugin]` but does not implement `atri_plugin::Plugin`",
+    label = "the plugin type must implement `atri_plugin::Plugin`"
+)]
 pub trait Plugin
 where
     Self: Sized,
@@ -27,11 +34,23 @@ where
     fn should_drop() -> bool {
         true
     }
+
+    /// 插件启用时开始处理的命令, 可在此设置前缀与权限
+    ///
+    /// `#[plugin(commands = [...])]`中列出的命令会加入其中, 插件禁用时停止处理
+    fn commands() -> CommandRegistry {
+        CommandRegistry::new()
+    }
 }
 
+/// `#[plugin(commands = [...])]`中列出的命令
+static COMMANDS: OnceLock<fn() -> Vec<Command>> = OnceLock::new();
+
+static COMMAND_GUARD: Mutex<Option<ListenerGuard>> = Mutex::new(None);
+
 #[doc(hidden)]
 /// 从已实现Plugin的结构体获取一个标准的PluginInstance
-pub fn __get_instance<P: Plugin>(name: &str) -> PluginInstance {
+pub fn __get_instance<P: Plugin>(name: &str, commands: fn() -> Vec<Command>) -> PluginInstance {
     extern "C" fn _new<P: Plugin>() -> *mut () {
         let b = Box::new(P::new());
         Box::into_raw(b) as *mut ()
@@ -41,11 +60,20 @@ pub fn __get_instance<P: Plugin>(name: &str) -> PluginInstance {
         // Safety: Plugin is pinned by box
         let p = unsafe { &mut *(ptr as *mut P) };
         p.enable();
+
+        let mut registry = P::commands();
+        for command in COMMANDS.get().map(|f| f()).unwrap_or_default() {
+            registry.register(command);
+        }
+        if !registry.is_empty() {
+            *COMMAND_GUARD.lock().unwrap() = Some(registry.start());
+        }
     }
 
     extern "C" fn _disable<P: Plugin>(ptr: *mut ()) {
         // Safety: Plugin is pinned by box
         let p = unsafe { &mut *(ptr as *mut P) };
+        COMMAND_GUARD.lock().unwrap().take();
         p.disable();
     }
 
@@ -53,6 +81,7 @@ pub fn __get_instance<P: Plugin>(name: &str) -> PluginInstance {
         drop(unsafe { Box::from_raw(ptr.cast::<T>()) })
     }
 
+    let _ = COMMANDS.set(commands);
     let should_drop = P::should_drop();
 
     let vtb = PluginVTable {
@@ -69,3 +98,33 @@ pub fn __get_instance<P: Plugin>(name: &str) -> PluginInstance {
         name: RustStr::from(name),
     }
 }
+
+#[doc(hidden)]
+/// 由`#[plugin]`生成, 未指定的项取自插件的`CARGO_PKG_*`
+pub struct __PluginMeta {
+    pub version: &'static str,
+    pub authors: &'static [&'static str],
+    pub description: &'static str,
+    pub homepage: &'static str,
+    pub depends: &'static [&'static str],
+}
+
+#[doc(hidden)]
+pub fn __get_info(meta: &__PluginMeta) -> PluginInfo {
+    fn strs<'a>(iter: impl Iterator<Item = &'a str>) -> RustVec<RustStr> {
+        iter.map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(RustStr::from)
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    PluginInfo {
+        version: RustStr::from(meta.version),
+        // CARGO_PKG_AUTHORS以冒号分隔
+        authors: strs(meta.authors.iter().flat_map(|s| s.split(':'))),
+        description: RustStr::from(meta.description),
+        homepage: RustStr::from(meta.homepage),
+        depends: strs(meta.depends.iter().copied()),
+    }
+}
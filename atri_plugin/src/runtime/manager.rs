@@ -23,8 +23,7 @@ impl PluginRuntime {
         });
 
         let f = (get_vtb().plugin_manager_spawn)(get_plugin_manager(), ffi);
-        let handle = JoinHandle::<F::Output>::from(f);
-        handle
+        JoinHandle::<F::Output>::from(f)
     }
 
     /// 阻塞当前线程执行协程，并返回Future的返回值
@@ -80,3 +80,17 @@ pub fn after_panic() {
 pub fn is_panicked() -> bool {
     PANICKED.load(Ordering::Relaxed)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{block_on, fixture};
+
+    #[test]
+    fn spawn() {
+        let (_host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let handle = super::spawn(async move { group.id() });
+        assert_eq!(block_on(handle).unwrap(), 123);
+    }
+}
@@ -0,0 +1,508 @@
+use crate::contact::group::{Announcement, GroupSettings};
+use crate::contact::group_file::{GroupFile, GroupFileEntry, GroupFiles, GroupFolder};
+use crate::contact::member::MemberRole;
+use crate::message::meta::MessageReceipt;
+use crate::message::MessageChain;
+use atri_ffi::{Handle, RustStr};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{MockClient, MockFriend, MockGroup, MockMember, MockStranger};
+
+static NEXT_SEQ: AtomicI32 = AtomicI32::new(1);
+
+pub(crate) fn next_seq() -> i32 {
+    NEXT_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// 以`RustStr`借出的字符串, 旧值会一直保留到本值被销毁, 以保证借出的切片有效
+pub(crate) struct StrCell(Mutex<Vec<Box<str>>>);
+
+impl StrCell {
+    pub fn new(s: String) -> Self {
+        Self(Mutex::new(vec![s.into_boxed_str()]))
+    }
+
+    pub fn as_rs(&self) -> RustStr {
+        let v = self.0.lock().unwrap();
+        RustStr::from(&**v.last().unwrap())
+    }
+
+    pub fn set(&self, s: String) {
+        self.0.lock().unwrap().push(s.into_boxed_str());
+    }
+}
+
+pub(crate) struct ClientData {
+    pub id: i64,
+    pub nickname: String,
+    pub groups: Mutex<Vec<Arc<GroupData>>>,
+    pub friends: Mutex<Vec<Arc<FriendData>>>,
+    pub strangers: Vec<Arc<StrangerData>>,
+}
+
+impl ClientData {
+    pub fn build(client: MockClient) -> Arc<Self> {
+        Arc::new_cyclic(|weak| Self {
+            id: client.id,
+            nickname: client.nickname,
+            groups: Mutex::new(
+                client
+                    .groups
+                    .into_iter()
+                    .map(|g| GroupData::build(g, weak.clone()))
+                    .collect(),
+            ),
+            friends: Mutex::new(
+                client
+                    .friends
+                    .into_iter()
+                    .map(|f| FriendData::build(f, weak.clone()))
+                    .collect(),
+            ),
+            strangers: client
+                .strangers
+                .into_iter()
+                .map(|s| StrangerData::build(s, weak.clone()))
+                .collect(),
+        })
+    }
+
+    pub fn find_group(&self, id: i64) -> Option<Arc<GroupData>> {
+        let groups = self.groups.lock().unwrap();
+        groups.iter().find(|g| g.id == id).cloned()
+    }
+
+    pub fn find_friend(&self, id: i64) -> Option<Arc<FriendData>> {
+        let friends = self.friends.lock().unwrap();
+        friends.iter().find(|f| f.id == id).cloned()
+    }
+
+    pub fn add_friend(self: &Arc<Self>, friend: MockFriend) -> Arc<FriendData> {
+        let friend = FriendData::build(friend, Arc::downgrade(self));
+        self.friends.lock().unwrap().push(friend.clone());
+        friend
+    }
+
+    pub fn remove_friend(&self, id: i64) -> Option<Arc<FriendData>> {
+        let mut friends = self.friends.lock().unwrap();
+        let pos = friends.iter().position(|f| f.id == id)?;
+        Some(friends.remove(pos))
+    }
+}
+
+pub(crate) struct GroupData {
+    pub id: i64,
+    pub name: StrCell,
+    pub client: Weak<ClientData>,
+    pub members: Mutex<Vec<Arc<MemberData>>>,
+    pub settings: Mutex<GroupSettings>,
+    pub announcements: Mutex<Vec<Announcement>>,
+    pub files: Mutex<FileSystem>,
+    pub messages: MessageCache,
+    quit: AtomicBool,
+}
+
+impl GroupData {
+    pub fn build(group: MockGroup, client: Weak<ClientData>) -> Arc<Self> {
+        Arc::new_cyclic(|weak| Self {
+            id: group.id,
+            name: StrCell::new(group.name),
+            client,
+            members: Mutex::new(
+                group
+                    .members
+                    .into_iter()
+                    .map(|m| MemberData::build(m, weak.clone()))
+                    .collect(),
+            ),
+            settings: Mutex::new(group.settings),
+            announcements: Mutex::new(vec![]),
+            files: Mutex::new(FileSystem::new(group.folders)),
+            messages: MessageCache::default(),
+            quit: AtomicBool::new(false),
+        })
+    }
+
+    /// 退出本群, 若已退出则返回`false`
+    pub fn quit(&self) -> bool {
+        if self.quit.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        if let Some(client) = self.client.upgrade() {
+            client.groups.lock().unwrap().retain(|g| g.id != self.id);
+        }
+        true
+    }
+
+    pub fn is_quit(&self) -> bool {
+        self.quit.load(Ordering::SeqCst)
+    }
+
+    pub fn find_member(&self, id: i64) -> Option<Arc<MemberData>> {
+        let members = self.members.lock().unwrap();
+        members.iter().find(|m| m.id == id).cloned()
+    }
+
+    pub fn add_member(self: &Arc<Self>, member: MockMember) -> Arc<MemberData> {
+        let member = MemberData::build(member, Arc::downgrade(self));
+        self.members.lock().unwrap().push(member.clone());
+        member
+    }
+
+    pub fn remove_member(&self, id: i64) -> Option<Arc<MemberData>> {
+        let mut members = self.members.lock().unwrap();
+        let pos = members.iter().position(|m| m.id == id)?;
+        Some(members.remove(pos))
+    }
+}
+
+/// 消息缓存, 包括收到和插件发送的消息, 按时间顺序排列
+#[derive(Default)]
+pub(crate) struct MessageCache(Mutex<Vec<MessageChain>>);
+
+impl MessageCache {
+    pub fn push(&self, message: MessageChain) {
+        self.0.lock().unwrap().push(message);
+    }
+
+    /// 以回执的序号记录插件发送的消息
+    pub fn push_sent(&self, mut message: MessageChain, receipt: &MessageReceipt, sender: i64) {
+        let meta = message.metadata_mut();
+        meta.seqs = receipt.seqs.clone();
+        meta.rands = receipt.rands.clone();
+        meta.time = receipt.time as i32;
+        meta.sender = sender;
+        self.push(message);
+    }
+
+    pub fn find(&self, seq: i32) -> Option<MessageChain> {
+        let messages = self.0.lock().unwrap();
+        messages
+            .iter()
+            .find(|m| m.metadata().seqs.contains(&seq))
+            .cloned()
+    }
+
+    /// 序号小于`before_seq`的最近`count`条消息
+    pub fn history(&self, before_seq: i32, count: u32) -> Vec<MessageChain> {
+        let messages = self.0.lock().unwrap();
+        let before: Vec<_> = messages
+            .iter()
+            .filter(|m| {
+                m.metadata()
+                    .seqs
+                    .first()
+                    .is_some_and(|&seq| seq < before_seq)
+            })
+            .collect();
+        let skip = before.len().saturating_sub(count as usize);
+
+        before.into_iter().skip(skip).cloned().collect()
+    }
+}
+
+/// 模拟的群文件系统, 文件夹只有一层, id为`/名称`
+pub(crate) struct FileSystem {
+    entries: Vec<GroupFileEntry>,
+    contents: HashMap<String, Vec<u8>>,
+}
+
+impl FileSystem {
+    fn new(folders: Vec<String>) -> Self {
+        let entries = folders
+            .into_iter()
+            .map(|name| {
+                GroupFileEntry::Folder(GroupFolder {
+                    id: format!("/{}", name),
+                    name,
+                    parent: GroupFiles::ROOT.into(),
+                    file_count: 0,
+                })
+            })
+            .collect();
+
+        Self {
+            entries,
+            contents: HashMap::new(),
+        }
+    }
+
+    fn check_folder(&self, id: &str) -> Result<(), String> {
+        let exists = id == GroupFiles::ROOT
+            || self
+                .entries
+                .iter()
+                .any(|e| matches!(e, GroupFileEntry::Folder(f) if f.id == id));
+
+        if exists {
+            Ok(())
+        } else {
+            Err(format!("Folder {} not found", id))
+        }
+    }
+
+    fn file_mut(&mut self, id: &str) -> Result<&mut GroupFile, String> {
+        self.entries
+            .iter_mut()
+            .find_map(|e| match e {
+                GroupFileEntry::File(f) if f.id == id => Some(f),
+                _ => None,
+            })
+            .ok_or_else(|| format!("File {} not found", id))
+    }
+
+    pub fn list(&self, folder: &str) -> Result<Vec<GroupFileEntry>, String> {
+        self.check_folder(folder)?;
+
+        let entries = self
+            .entries
+            .iter()
+            .filter_map(|e| match e {
+                GroupFileEntry::Folder(f) if f.parent == folder => {
+                    let mut f = f.clone();
+                    f.file_count = self.list(&f.id).map(|v| v.len() as u32).unwrap_or(0);
+                    Some(GroupFileEntry::Folder(f))
+                }
+                GroupFileEntry::File(f) if f.folder == folder => Some(e.clone()),
+                _ => None,
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    pub fn upload(
+        &mut self,
+        folder: String,
+        name: String,
+        data: Vec<u8>,
+        uploader: i64,
+    ) -> Result<GroupFile, String> {
+        self.check_folder(&folder)?;
+
+        let file = GroupFile {
+            id: format!("mock-file-{}", next_seq()),
+            name,
+            folder,
+            size: data.len() as u64,
+            uploader,
+            upload_time: now(),
+        };
+        self.contents.insert(file.id.clone(), data);
+        self.entries.push(GroupFileEntry::File(file.clone()));
+        Ok(file)
+    }
+
+    pub fn download(&self, id: &str) -> Result<Vec<u8>, String> {
+        self.contents
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("File {} not found", id))
+    }
+
+    pub fn rename(&mut self, id: &str, name: String) -> Result<(), String> {
+        self.file_mut(id)?.name = name;
+        Ok(())
+    }
+
+    pub fn move_to(&mut self, id: &str, folder: String) -> Result<(), String> {
+        self.check_folder(&folder)?;
+        self.file_mut(id)?.folder = folder;
+        Ok(())
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<(), String> {
+        self.file_mut(id)?;
+        self.entries.retain(|e| e.id() != id);
+        self.contents.remove(id);
+        Ok(())
+    }
+}
+
+pub(crate) struct FriendData {
+    pub id: i64,
+    pub nickname: String,
+    pub client: Weak<ClientData>,
+    pub messages: MessageCache,
+}
+
+impl FriendData {
+    pub fn build(friend: MockFriend, client: Weak<ClientData>) -> Arc<Self> {
+        Arc::new(Self {
+            id: friend.id,
+            nickname: friend.nickname,
+            client,
+            messages: MessageCache::default(),
+        })
+    }
+}
+
+pub(crate) struct StrangerData {
+    pub id: i64,
+    pub nickname: String,
+    pub client: Weak<ClientData>,
+}
+
+impl StrangerData {
+    pub fn build(stranger: MockStranger, client: Weak<ClientData>) -> Arc<Self> {
+        Arc::new(Self {
+            id: stranger.id,
+            nickname: stranger.nickname,
+            client,
+        })
+    }
+}
+
+pub(crate) struct MemberData {
+    pub id: i64,
+    pub nickname: String,
+    pub card_name: StrCell,
+    pub role: Mutex<MemberRole>,
+    pub join_time: i64,
+    pub last_speak_time: AtomicI64,
+    pub level: u16,
+    pub group: Weak<GroupData>,
+}
+
+impl MemberData {
+    pub fn build(member: MockMember, group: Weak<GroupData>) -> Arc<Self> {
+        Arc::new(Self {
+            id: member.id,
+            nickname: member.nickname,
+            card_name: StrCell::new(member.card_name),
+            role: Mutex::new(member.role),
+            join_time: now(),
+            last_speak_time: AtomicI64::new(0),
+            level: member.level,
+            group,
+        })
+    }
+
+    pub fn group_id(&self) -> i64 {
+        self.group.upgrade().map(|g| g.id).unwrap_or_default()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ImageData {
+    pub id: String,
+    pub url: String,
+}
+
+impl ImageData {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let id = format!("{{{:08X}-{:04X}}}.image", fnv1a(data), data.len() & 0xFFFF);
+        let url = format!("https://mock.atri/image/{}", id);
+
+        Self { id, url }
+    }
+}
+
+/// 模拟的语音, 时长按每KiB一秒计算, 摘要并非真正的md5
+#[derive(Clone)]
+pub(crate) struct VoiceData {
+    pub url: String,
+    pub md5: Vec<u8>,
+    pub duration: u64,
+    pub data: Vec<u8>,
+}
+
+impl VoiceData {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let hash = fnv1a(data);
+        let md5 = [hash, !hash, hash.rotate_left(16), !hash.rotate_left(16)]
+            .iter()
+            .flat_map(|h| h.to_be_bytes())
+            .collect();
+        let url = format!("https://mock.atri/voice/{:08X}.amr", hash);
+
+        Self {
+            url,
+            md5,
+            duration: (data.len() as u64).div_ceil(1024),
+            data: data.to_vec(),
+        }
+    }
+}
+
+/// 模拟的视频或文件
+#[derive(Clone)]
+pub(crate) struct FileData {
+    pub name: String,
+    pub url: String,
+    pub data: Vec<u8>,
+}
+
+impl FileData {
+    pub fn new(name: String, data: Vec<u8>) -> Self {
+        let url = format!("https://mock.atri/file/{:08X}/{}", fnv1a(&data), name);
+        Self { name, url, data }
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c9dc5, |hash, b| {
+        (hash ^ *b as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[derive(Default)]
+pub(crate) struct InterceptFlag(AtomicBool);
+
+impl InterceptFlag {
+    pub fn intercept(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_intercepted(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+pub(crate) fn into_handle<T>(arc: Arc<T>) -> Handle {
+    Arc::into_raw(arc).cast()
+}
+
+pub(crate) fn opt_into_handle<T>(arc: Option<Arc<T>>) -> Handle {
+    arc.map(into_handle).unwrap_or_else(std::ptr::null)
+}
+
+/// # Safety
+/// `handle` must point to a live `T`
+pub(crate) unsafe fn handle_ref<'a, T>(handle: Handle) -> &'a T {
+    &*handle.cast::<T>()
+}
+
+/// # Safety
+/// `handle` must be created by [`into_handle`] with the same type
+pub(crate) unsafe fn handle_arc<T>(handle: Handle) -> Arc<T> {
+    Arc::increment_strong_count(handle.cast::<T>());
+    Arc::from_raw(handle.cast::<T>())
+}
+
+/// # Safety
+/// `handle` must be created by [`into_handle`] with the same type
+pub(crate) unsafe fn clone_handle<T>(handle: Handle) -> Handle {
+    if !handle.is_null() {
+        Arc::increment_strong_count(handle.cast::<T>());
+    }
+    handle
+}
+
+/// # Safety
+/// `handle` must be created by [`into_handle`] with the same type
+pub(crate) unsafe fn drop_handle<T>(handle: Handle) {
+    if !handle.is_null() {
+        Arc::decrement_strong_count(handle.cast::<T>());
+    }
+}
@@ -0,0 +1,155 @@
+use super::data::{InterceptFlag, MemberData};
+use crate::client::Client;
+use crate::contact::friend::Friend;
+use crate::contact::group::Group;
+use crate::event::Event;
+use crate::message::meta::Anonymous;
+use crate::message::MessageChain;
+use atri_ffi::ffi::FFIEvent;
+use atri_ffi::ManagedCloneable;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub(crate) const CLIENT_LOGIN: u8 = 0;
+pub(crate) const GROUP_MESSAGE: u8 = 1;
+pub(crate) const FRIEND_MESSAGE: u8 = 2;
+pub(crate) const NEW_FRIEND: u8 = 3;
+pub(crate) const DELETE_FRIEND: u8 = 4;
+pub(crate) const GROUP_MEMBER_JOIN: u8 = 5;
+pub(crate) const GROUP_MEMBER_LEAVE: u8 = 6;
+pub(crate) const GROUP_RECALL: u8 = 7;
+pub(crate) const FRIEND_RECALL: u8 = 8;
+pub(crate) const FRIEND_REQUEST: u8 = 9;
+pub(crate) const GROUP_INVITED: u8 = 10;
+pub(crate) const GROUP_JOIN_REQUEST: u8 = 11;
+pub(crate) const GROUP_TEMP_MESSAGE: u8 = 12;
+
+/// 事件的宿主侧数据, 拦截标志随事件一同克隆
+#[derive(Clone)]
+pub(crate) struct EventData<T> {
+    pub intercepted: Arc<InterceptFlag>,
+    pub inner: T,
+}
+
+impl<T: Clone> EventData<T> {
+    pub fn into_ffi(t: u8, inner: T) -> FFIEvent {
+        let data = Self {
+            intercepted: Arc::default(),
+            inner,
+        };
+        let intercepted = Arc::as_ptr(&data.intercepted).cast();
+
+        FFIEvent::from(t, intercepted, ManagedCloneable::from_value(data))
+    }
+
+    pub fn into_event(t: u8, inner: T) -> Event {
+        Event::from_ffi(Self::into_ffi(t, inner))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupMessageData {
+    pub group: Group,
+    pub sender: SenderData,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupTempMessageData {
+    pub group: Group,
+    pub sender: Arc<MemberData>,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) enum SenderData {
+    Named(Arc<MemberData>),
+    Anonymous(AnonymousData),
+}
+
+/// 插件持有的匿名成员, 附带其所在的群
+#[derive(Clone)]
+pub(crate) struct AnonymousData {
+    pub group: Group,
+    pub anonymous: Anonymous,
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendMessageData {
+    pub friend: Friend,
+    pub message: MessageChain,
+}
+
+#[derive(Clone)]
+pub(crate) struct DeleteFriendData {
+    pub client: Client,
+    pub friend_id: i64,
+}
+
+/// 成员入群与离开事件共用
+#[derive(Clone)]
+pub(crate) struct GroupMemberData {
+    pub group: Group,
+    pub member: Arc<MemberData>,
+    pub operator: Option<Arc<MemberData>>,
+    /// 仅用于离开事件
+    pub kicked: bool,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupRecallData {
+    pub group: Group,
+    pub operator: Option<Arc<MemberData>>,
+    pub operator_id: i64,
+    pub sender: i64,
+    pub seqs: Vec<i32>,
+    pub time: i32,
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendRecallData {
+    pub friend: Friend,
+    pub operator_id: i64,
+    pub sender: i64,
+    pub seqs: Vec<i32>,
+    pub time: i32,
+}
+
+/// 申请是否已被处理, 随事件一同克隆
+#[derive(Clone, Default)]
+pub(crate) struct RequestState(Arc<AtomicBool>);
+
+impl RequestState {
+    /// 标记为已处理, 若之前已被处理则返回`false`
+    pub fn handle(&self) -> bool {
+        !self.0.swap(true, Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FriendRequestData {
+    pub client: Client,
+    pub requester_id: i64,
+    pub requester_nickname: String,
+    pub message: String,
+    pub state: RequestState,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupInvitedData {
+    pub client: Client,
+    pub group_id: i64,
+    pub group_name: String,
+    pub inviter_id: i64,
+    pub inviter_nickname: String,
+    pub state: RequestState,
+}
+
+#[derive(Clone)]
+pub(crate) struct GroupJoinRequestData {
+    pub group: Group,
+    pub requester_id: i64,
+    pub requester_nickname: String,
+    pub message: String,
+    pub state: RequestState,
+}
@@ -0,0 +1,133 @@
+use super::current;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// 单线程协程执行器, 由安装模拟宿主的线程驱动
+pub(crate) struct Executor {
+    thread: Thread,
+    queue: Mutex<VecDeque<Arc<Task>>>,
+}
+
+impl Executor {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            thread: thread::current(),
+            queue: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn spawn<F>(self: &Arc<Self>, future: F)
+    where
+        F: Future<Output = ()>,
+        F: Send + 'static,
+    {
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(future))),
+            queued: AtomicBool::new(true),
+            executor: Arc::downgrade(self),
+        });
+
+        self.queue.lock().unwrap().push_back(task);
+        self.thread.unpark();
+    }
+
+    /// 执行所有就绪的协程, 直至没有协程可以继续执行
+    pub fn run_until_stalled(&self) {
+        loop {
+            let task = self.queue.lock().unwrap().pop_front();
+            match task {
+                Some(task) => task.run(),
+                None => break,
+            }
+        }
+    }
+}
+
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    queued: AtomicBool,
+    executor: Weak<Executor>,
+}
+
+impl Task {
+    fn run(self: Arc<Self>) {
+        self.queued.store(false, Ordering::SeqCst);
+
+        // 取出协程以避免在重入时被重复执行
+        let future = self.future.lock().unwrap().take();
+        if let Some(mut future) = future {
+            let waker = Waker::from(self.clone());
+            let mut cx = Context::from_waker(&waker);
+
+            if future.as_mut().poll(&mut cx).is_pending() {
+                *self.future.lock().unwrap() = Some(future);
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        let Some(executor) = self.executor.upgrade() else {
+            return;
+        };
+
+        if !self.queued.swap(true, Ordering::SeqCst) {
+            executor.queue.lock().unwrap().push_back(self);
+        }
+        executor.thread.unpark();
+    }
+}
+
+struct ThreadWaker {
+    thread: Thread,
+    woken: AtomicBool,
+}
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
+
+/// 在当前线程上执行协程直至完成
+///
+/// 期间会一并执行当前模拟宿主中通过`spawn`派生的协程
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let executor = current().map(|host| host.executor.clone());
+    let waker = Arc::new(ThreadWaker {
+        thread: thread::current(),
+        woken: AtomicBool::new(true),
+    });
+    let main_waker = Waker::from(waker.clone());
+    let mut cx = Context::from_waker(&main_waker);
+    let mut future = pin!(future);
+
+    loop {
+        if waker.woken.swap(false, Ordering::SeqCst) {
+            if let Poll::Ready(val) = future.as_mut().poll(&mut cx) {
+                return val;
+            }
+        }
+
+        if let Some(executor) = &executor {
+            executor.run_until_stalled();
+        }
+
+        if !waker.woken.load(Ordering::SeqCst) {
+            thread::park();
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use super::HostState;
+use atri_ffi::closure::FFIFn;
+use atri_ffi::ffi::FFIEvent;
+use atri_ffi::future::FFIFuture;
+use atri_ffi::{FFIOption, ManagedCloneable};
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub(crate) struct ListenerEntry {
+    id: u64,
+    priority: u8,
+    gate: Gate,
+    kind: ListenerKind,
+}
+
+enum ListenerKind {
+    Handler {
+        concurrent: bool,
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+    },
+    Waiter(Arc<Waiter>),
+}
+
+/// 移除时关闭监听器
+pub(crate) struct ListenerHandle {
+    id: u64,
+    host: Weak<HostState>,
+}
+
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        if let Some(host) = self.host.upgrade() {
+            host.remove_listener(self.id);
+        }
+    }
+}
+
+impl HostState {
+    fn push_listener(&self, priority: u8, kind: ListenerKind) -> u64 {
+        let mut listeners = self.listeners.lock().unwrap();
+        let id = self.next_listener_id.fetch_add(1, Ordering::Relaxed);
+
+        listeners.push(Arc::new(ListenerEntry {
+            id,
+            priority,
+            gate: Gate::default(),
+            kind,
+        }));
+        id
+    }
+
+    pub(crate) fn add_listener(
+        self: &Arc<Self>,
+        concurrent: bool,
+        handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+        priority: u8,
+    ) -> ListenerHandle {
+        let id = self.push_listener(
+            priority,
+            ListenerKind::Handler {
+                concurrent,
+                handler,
+            },
+        );
+
+        ListenerHandle {
+            id,
+            host: Arc::downgrade(self),
+        }
+    }
+
+    pub(crate) fn next_event(
+        self: &Arc<Self>,
+        timeout: Duration,
+        filter: FFIFn<FFIEvent, bool>,
+        priority: u8,
+    ) -> NextEvent {
+        let waiter = Arc::new(Waiter {
+            filter,
+            state: Mutex::new(WaiterState::default()),
+        });
+        let id = self.push_listener(priority, ListenerKind::Waiter(waiter.clone()));
+
+        NextEvent {
+            handle: ListenerHandle {
+                id,
+                host: Arc::downgrade(self),
+            },
+            waiter,
+            deadline: Instant::now() + timeout,
+            timer: false,
+        }
+    }
+
+    pub(crate) fn remove_listener(&self, id: u64) {
+        self.listeners.lock().unwrap().retain(|l| l.id != id);
+    }
+
+    /// 按优先级将事件依次交给各监听器处理, 返回事件是否被拦截
+    ///
+    /// 每个监听器的处理协程都会在执行器中派生并执行至挂起,
+    /// 非并发的监听器会等待其上一个事件处理完毕
+    pub(crate) fn dispatch(
+        self: &Arc<Self>,
+        t: u8,
+        intercepted: *const (),
+        base: ManagedCloneable,
+    ) -> bool {
+        let is_intercepted = || (crate::loader::get_vtb().event_is_intercepted)(intercepted);
+        let ffi = || FFIEvent::from(t, intercepted, base.clone());
+
+        let mut listeners = self.listeners.lock().unwrap().clone();
+        listeners.sort_by_key(|l| (l.priority, l.id));
+
+        for l in listeners {
+            if is_intercepted() {
+                break;
+            }
+
+            match &l.kind {
+                ListenerKind::Handler {
+                    concurrent,
+                    handler,
+                } => {
+                    let concurrent = *concurrent;
+                    let fu = handler.invoke(ffi());
+                    let host = Arc::downgrade(self);
+                    let entry = l.clone();
+
+                    self.executor.spawn(async move {
+                        let keep = if concurrent {
+                            fu.await
+                        } else {
+                            let _guard = entry.gate.lock().await;
+                            fu.await
+                        };
+
+                        if let (false, Some(host)) = (keep, host.upgrade()) {
+                            host.remove_listener(entry.id);
+                        }
+                    });
+                }
+                ListenerKind::Waiter(waiter) => {
+                    if waiter.offer(ffi) {
+                        self.remove_listener(l.id);
+                    }
+                }
+            }
+
+            self.executor.run_until_stalled();
+        }
+
+        is_intercepted()
+    }
+}
+
+pub(crate) struct Waiter {
+    filter: FFIFn<FFIEvent, bool>,
+    state: Mutex<WaiterState>,
+}
+
+#[derive(Default)]
+struct WaiterState {
+    event: Option<FFIEvent>,
+    waker: Option<Waker>,
+}
+
+impl Waiter {
+    /// 若事件满足过滤条件则接收该事件
+    fn offer<F: Fn() -> FFIEvent>(&self, event: F) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.event.is_some() || !self.filter.invoke(event()) {
+            return false;
+        }
+
+        state.event = Some(event());
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+}
+
+pub(crate) struct NextEvent {
+    handle: ListenerHandle,
+    waiter: Arc<Waiter>,
+    deadline: Instant,
+    timer: bool,
+}
+
+impl Future for NextEvent {
+    type Output = FFIOption<FFIEvent>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.waiter.state.lock().unwrap();
+        if let Some(e) = state.event.take() {
+            return Poll::Ready(Some(e).into());
+        }
+
+        let now = Instant::now();
+        if now >= self.deadline {
+            drop(state);
+            if let Some(host) = self.handle.host.upgrade() {
+                host.remove_listener(self.handle.id);
+            }
+            return Poll::Ready(None.into());
+        }
+
+        state.waker = Some(cx.waker().clone());
+        drop(state);
+
+        if !self.timer {
+            self.timer = true;
+            let waker = cx.waker().clone();
+            let dur = self.deadline - now;
+            thread::spawn(move || {
+                thread::sleep(dur);
+                waker.wake();
+            });
+        }
+
+        Poll::Pending
+    }
+}
+
+#[derive(Default)]
+struct Gate(Mutex<GateState>);
+
+#[derive(Default)]
+struct GateState {
+    locked: bool,
+    waiters: Vec<Waker>,
+}
+
+struct GateGuard<'a>(&'a Gate);
+
+impl Gate {
+    async fn lock(&self) -> GateGuard<'_> {
+        poll_fn(|cx| {
+            let mut state = self.0.lock().unwrap();
+            if state.locked {
+                state.waiters.push(cx.waker().clone());
+                Poll::Pending
+            } else {
+                state.locked = true;
+                Poll::Ready(())
+            }
+        })
+        .await;
+
+        GateGuard(self)
+    }
+}
+
+impl Drop for GateGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0 .0.lock().unwrap();
+        state.locked = false;
+        for waker in state.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+}
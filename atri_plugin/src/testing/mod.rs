@@ -0,0 +1,804 @@
+//! 进程内的模拟宿主, 用于在没有真实插件管理器的情况下对插件进行单元测试
+//!
+//! 启用`testing`特性后可用
+//!
+//! ```rust,ignore
+//! use atri_plugin::event::GroupMessageEvent;
+//! use atri_plugin::listener::Listener;
+//! use atri_plugin::testing::{MockClient, MockGroup, MockHost, MockMember};
+//!
+//! let host = MockHost::install();
+//! let client = host.add_client(
+//!     MockClient::new(10000, "Atri")
+//!         .group(MockGroup::new(123, "测试群").member(MockMember::new(1, "Alice"))),
+//! );
+//!
+//! let _guard = Listener::listening_on_always(|e: GroupMessageEvent| async move {
+//!     let _ = e.group().send_message("Hello").await;
+//! });
+//!
+//! let group = client.find_group(123).unwrap();
+//! let e = host.group_message_event(&group, 1, "Hi").unwrap();
+//! host.dispatch(e);
+//!
+//! assert_eq!(host.group_messages(123)[0].to_string(), "Hello");
+//! ```
+
+mod data;
+mod event;
+mod executor;
+mod listener;
+mod transcript;
+mod vtable;
+
+pub use executor::block_on;
+pub use transcript::Action;
+
+use crate::client::Client;
+use crate::contact::friend::Friend;
+use crate::contact::group::{Group, GroupSettings};
+use crate::contact::member::{AnonymousMember, MemberRole};
+use crate::event::{
+    ClientLoginEvent, DeleteFriendEvent, Event, FriendMessageEvent, FriendRecallEvent,
+    FriendRequestEvent, GroupInvitedEvent, GroupJoinRequestEvent, GroupMemberJoinEvent,
+    GroupMemberLeaveEvent, GroupMessageEvent, GroupRecallEvent, GroupTempMessageEvent,
+    NewFriendEvent,
+};
+use crate::loader::atri_manager_init;
+use crate::message::file::File;
+use crate::message::meta::Anonymous;
+use crate::message::video::Video;
+use crate::message::MessageChain;
+use atri_ffi::ffi::AtriManager;
+use atri_ffi::plugin::AbiInfo;
+use atri_ffi::ManagedCloneable;
+use data::{
+    handle_arc, handle_ref, into_handle, next_seq, now, ClientData, FileData, FriendData, GroupData,
+};
+use event::{
+    AnonymousData, DeleteFriendData, EventData, FriendMessageData, FriendRecallData,
+    FriendRequestData, GroupInvitedData, GroupJoinRequestData, GroupMemberData, GroupMessageData,
+    GroupRecallData, GroupTempMessageData, RequestState, SenderData, CLIENT_LOGIN, DELETE_FRIEND,
+    FRIEND_MESSAGE, FRIEND_RECALL, FRIEND_REQUEST, GROUP_INVITED, GROUP_JOIN_REQUEST,
+    GROUP_MEMBER_JOIN, GROUP_MEMBER_LEAVE, GROUP_MESSAGE, GROUP_RECALL, GROUP_TEMP_MESSAGE,
+    NEW_FRIEND,
+};
+use executor::Executor;
+use listener::ListenerEntry;
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Once};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<HostState>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn current() -> Option<Arc<HostState>> {
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+pub(crate) struct HostState {
+    executor: Arc<Executor>,
+    clients: Mutex<Vec<Arc<ClientData>>>,
+    listeners: Mutex<Vec<Arc<ListenerEntry>>>,
+    next_listener_id: AtomicU64,
+    transcript: Mutex<Vec<Action>>,
+    workspace: Mutex<PathBuf>,
+    logs: Mutex<Vec<(u8, String)>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct MockError(String);
+
+impl Display for MockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MockError {}
+
+/// 模拟宿主
+///
+/// 宿主状态绑定于调用[`MockHost::install`]的线程,
+/// 插件代码应在同一线程内通过[`block_on`]或[`MockHost::dispatch`]执行
+#[derive(Clone)]
+pub struct MockHost(Arc<HostState>);
+
+impl MockHost {
+    /// 为当前线程安装一个新的模拟宿主, 并替换之前安装的宿主
+    pub fn install() -> Self {
+        static INIT: Once = Once::new();
+
+        INIT.call_once(|| unsafe {
+            let compatible = atri_manager_init(AtriManager {
+                manager_ptr: std::ptr::null(),
+                handle: 0,
+                get_fun: vtable::get_fun,
+                abi: AbiInfo::new(env!("CARGO_PKG_VERSION"), crate::loader::SIGNATURES),
+            });
+            assert!(compatible, "mock host is incompatible with atri_plugin");
+        });
+
+        let state = Arc::new(HostState {
+            executor: Executor::new(),
+            clients: Mutex::new(vec![]),
+            listeners: Mutex::new(vec![]),
+            next_listener_id: AtomicU64::new(0),
+            transcript: Mutex::new(vec![]),
+            workspace: Mutex::new(std::env::temp_dir()),
+            logs: Mutex::new(vec![]),
+        });
+        CURRENT.with(|c| *c.borrow_mut() = Some(state.clone()));
+
+        Self(state)
+    }
+
+    /// 添加一个模拟的客户端
+    pub fn add_client(&self, client: MockClient) -> Client {
+        let data = ClientData::build(client);
+        self.0.clients.lock().unwrap().push(data.clone());
+
+        Client(into_handle(data))
+    }
+
+    pub fn set_workspace<P: Into<PathBuf>>(&self, path: P) {
+        *self.0.workspace.lock().unwrap() = path.into();
+    }
+
+    /// 插件输出的所有日志, 值为`(等级, 内容)`, 模拟宿主只记录而不打印
+    pub fn logs(&self) -> Vec<(u8, String)> {
+        self.0.logs.lock().unwrap().clone()
+    }
+
+    /// 将事件按优先级分发给已注册的监听器, 返回事件是否被拦截
+    ///
+    /// 各监听器的处理协程会执行至完成或挂起(如等待下一个事件)后才分发给下一个监听器,
+    /// 挂起的协程会在之后的分发或[`block_on`]中继续执行
+    pub fn dispatch<E: Into<Event>>(&self, event: E) -> bool {
+        let (t, intercepted, base) = event.into().into_ffi().get();
+        self.0.dispatch(t, intercepted, base)
+    }
+
+    /// 执行所有就绪的协程, 直至没有协程可以继续执行
+    pub fn run_until_stalled(&self) {
+        self.0.executor.run_until_stalled();
+    }
+
+    /// 当前仍在监听的监听器数量, 包括等待下一个事件的监听器
+    pub fn listener_count(&self) -> usize {
+        self.0.listeners.lock().unwrap().len()
+    }
+
+    /// 插件至今执行的所有外部操作
+    pub fn transcript(&self) -> Vec<Action> {
+        self.0.transcript.lock().unwrap().clone()
+    }
+
+    pub fn clear_transcript(&self) {
+        self.0.transcript.lock().unwrap().clear();
+    }
+
+    /// 插件向该群发送的所有消息
+    pub fn group_messages(&self, group: i64) -> Vec<MessageChain> {
+        let transcript = self.0.transcript.lock().unwrap();
+        transcript
+            .iter()
+            .filter_map(|action| match action {
+                Action::GroupMessage { group: g, message } if *g == group => Some(message.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 插件向该好友发送的所有消息
+    pub fn friend_messages(&self, friend: i64) -> Vec<MessageChain> {
+        let transcript = self.0.transcript.lock().unwrap();
+        transcript
+            .iter()
+            .filter_map(|action| match action {
+                Action::FriendMessage { friend: f, message } if *f == friend => {
+                    Some(message.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 插件通过临时会话向该群成员发送的所有消息
+    pub fn member_messages(&self, group: i64, member: i64) -> Vec<MessageChain> {
+        let transcript = self.0.transcript.lock().unwrap();
+        transcript
+            .iter()
+            .filter_map(|action| match action {
+                Action::MemberMessage {
+                    group: g,
+                    member: m,
+                    message,
+                } if *g == group && *m == member => Some(message.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 构造一个视频, 可放入消息中模拟收到的视频
+    pub fn video<S: Into<String>>(&self, name: S, data: Vec<u8>) -> Video {
+        Video(ManagedCloneable::from_value(FileData::new(
+            name.into(),
+            data,
+        )))
+    }
+
+    /// 构造一个文件, 可放入消息中模拟收到的离线文件
+    pub fn file<S: Into<String>>(&self, name: S, data: Vec<u8>) -> File {
+        File(ManagedCloneable::from_value(FileData::new(
+            name.into(),
+            data,
+        )))
+    }
+
+    /// 构造一个群消息事件, 若发送者不是该群成员则返回`None`
+    pub fn group_message_event<M: Into<MessageChain>>(
+        &self,
+        group: &Group,
+        sender: i64,
+        message: M,
+    ) -> Option<GroupMessageEvent> {
+        let data = unsafe { handle_ref::<GroupData>(group.0) };
+        let sender = data.find_member(sender)?;
+        let message = incoming(message.into(), sender.id);
+        sender.last_speak_time.store(now(), Ordering::SeqCst);
+        data.messages.push(message.clone());
+
+        let e = EventData::into_event(
+            GROUP_MESSAGE,
+            GroupMessageData {
+                group: group.clone(),
+                sender: SenderData::Named(sender),
+                message,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e)
+    }
+
+    /// 构造一个临时会话消息事件, 若发送者不是该群成员则返回`None`
+    pub fn group_temp_message_event<M: Into<MessageChain>>(
+        &self,
+        group: &Group,
+        sender: i64,
+        message: M,
+    ) -> Option<GroupTempMessageEvent> {
+        let data = unsafe { handle_ref::<GroupData>(group.0) };
+        let sender = data.find_member(sender)?;
+        let message = incoming(message.into(), sender.id);
+
+        let e = EventData::into_event(
+            GROUP_TEMP_MESSAGE,
+            GroupTempMessageData {
+                group: group.clone(),
+                sender,
+                message,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e)
+    }
+
+    /// 构造一个由匿名成员发送的群消息事件
+    pub fn anonymous_message_event<M: Into<MessageChain>>(
+        &self,
+        group: &Group,
+        anonymous: Anonymous,
+        message: M,
+    ) -> GroupMessageEvent {
+        let mut message = incoming(message.into(), AnonymousMember::ID);
+        message.with_anonymous(anonymous.clone());
+        let data = unsafe { handle_ref::<GroupData>(group.0) };
+        data.messages.push(message.clone());
+
+        let e = EventData::into_event(
+            GROUP_MESSAGE,
+            GroupMessageData {
+                group: group.clone(),
+                sender: SenderData::Anonymous(AnonymousData {
+                    group: group.clone(),
+                    anonymous,
+                }),
+                message,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("GroupMessageEvent")
+    }
+
+    /// 构造一个好友消息事件
+    pub fn friend_message_event<M: Into<MessageChain>>(
+        &self,
+        friend: &Friend,
+        message: M,
+    ) -> FriendMessageEvent {
+        let message = incoming(message.into(), friend.id());
+        let data = unsafe { handle_ref::<FriendData>(friend.0) };
+        data.messages.push(message.clone());
+
+        let e = EventData::into_event(
+            FRIEND_MESSAGE,
+            FriendMessageData {
+                friend: friend.clone(),
+                message,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("FriendMessageEvent")
+    }
+
+    pub fn client_login_event(&self, client: &Client) -> ClientLoginEvent {
+        let e = EventData::into_event(CLIENT_LOGIN, client.clone());
+
+        crate::event::FromEvent::from_event(e).expect("ClientLoginEvent")
+    }
+
+    /// 添加好友, 并构造对应的新增好友事件
+    pub fn new_friend_event(&self, client: &Client, friend: MockFriend) -> NewFriendEvent {
+        let data = unsafe { handle_arc::<ClientData>(client.0) };
+        let friend = Friend(into_handle(data.add_friend(friend)));
+
+        let e = EventData::into_event(NEW_FRIEND, friend);
+
+        crate::event::FromEvent::from_event(e).expect("NewFriendEvent")
+    }
+
+    /// 删除好友, 并构造对应的事件, 若不存在该好友则返回`None`
+    pub fn delete_friend_event(&self, client: &Client, friend: i64) -> Option<DeleteFriendEvent> {
+        let data = unsafe { handle_ref::<ClientData>(client.0) };
+        data.remove_friend(friend)?;
+
+        let e = EventData::into_event(
+            DELETE_FRIEND,
+            DeleteFriendData {
+                client: client.clone(),
+                friend_id: friend,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e)
+    }
+
+    /// 将成员加入群, 并构造对应的入群事件
+    ///
+    /// `operator`为邀请或同意入群的成员, 不是该群成员时视为`None`
+    pub fn member_join_event(
+        &self,
+        group: &Group,
+        member: MockMember,
+        operator: Option<i64>,
+    ) -> GroupMemberJoinEvent {
+        let data = unsafe { handle_arc::<GroupData>(group.0) };
+        let operator = operator.and_then(|id| data.find_member(id));
+        let member = data.add_member(member);
+
+        let e = EventData::into_event(
+            GROUP_MEMBER_JOIN,
+            GroupMemberData {
+                group: group.clone(),
+                member,
+                operator,
+                kicked: false,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("GroupMemberJoinEvent")
+    }
+
+    /// 将成员移出群, 并构造对应的离开事件, 若该成员不在群中则返回`None`
+    ///
+    /// `operator`为`None`时视为主动退群, 否则视为被移出, 即使操作者不是该群成员
+    pub fn member_leave_event(
+        &self,
+        group: &Group,
+        member: i64,
+        operator: Option<i64>,
+    ) -> Option<GroupMemberLeaveEvent> {
+        let data = unsafe { handle_ref::<GroupData>(group.0) };
+        let kicked = operator.is_some();
+        let operator = operator.and_then(|id| data.find_member(id));
+        let member = data.remove_member(member)?;
+
+        let e = EventData::into_event(
+            GROUP_MEMBER_LEAVE,
+            GroupMemberData {
+                group: group.clone(),
+                member,
+                operator,
+                kicked,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e)
+    }
+
+    /// 构造群消息被撤回的事件, `message`为被撤回的消息
+    pub fn group_recall_event(
+        &self,
+        group: &Group,
+        operator: i64,
+        message: &MessageChain,
+    ) -> GroupRecallEvent {
+        let data = unsafe { handle_ref::<GroupData>(group.0) };
+        let meta = message.metadata();
+
+        let e = EventData::into_event(
+            GROUP_RECALL,
+            GroupRecallData {
+                group: group.clone(),
+                operator: data.find_member(operator),
+                operator_id: operator,
+                sender: meta.sender,
+                seqs: meta.seqs.clone(),
+                time: meta.time,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("GroupRecallEvent")
+    }
+
+    /// 构造好友撤回消息的事件, `operator`为撤回消息的用户, `message`为被撤回的消息
+    pub fn friend_recall_event(
+        &self,
+        friend: &Friend,
+        operator: i64,
+        message: &MessageChain,
+    ) -> FriendRecallEvent {
+        let meta = message.metadata();
+
+        let e = EventData::into_event(
+            FRIEND_RECALL,
+            FriendRecallData {
+                friend: friend.clone(),
+                operator_id: operator,
+                sender: meta.sender,
+                seqs: meta.seqs.clone(),
+                time: meta.time,
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("FriendRecallEvent")
+    }
+
+    /// 构造收到好友申请的事件
+    pub fn friend_request_event<S: Into<String>, M: Into<String>>(
+        &self,
+        client: &Client,
+        requester: i64,
+        nickname: S,
+        message: M,
+    ) -> FriendRequestEvent {
+        let e = EventData::into_event(
+            FRIEND_REQUEST,
+            FriendRequestData {
+                client: client.clone(),
+                requester_id: requester,
+                requester_nickname: nickname.into(),
+                message: message.into(),
+                state: RequestState::default(),
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("FriendRequestEvent")
+    }
+
+    /// 构造机器人被邀请入群的事件
+    pub fn group_invited_event<S: Into<String>, N: Into<String>>(
+        &self,
+        client: &Client,
+        group_id: i64,
+        group_name: S,
+        inviter_id: i64,
+        inviter_nickname: N,
+    ) -> GroupInvitedEvent {
+        let e = EventData::into_event(
+            GROUP_INVITED,
+            GroupInvitedData {
+                client: client.clone(),
+                group_id,
+                group_name: group_name.into(),
+                inviter_id,
+                inviter_nickname: inviter_nickname.into(),
+                state: RequestState::default(),
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("GroupInvitedEvent")
+    }
+
+    /// 构造加群申请的事件, 同意后申请者会被加入群
+    pub fn group_join_request_event<S: Into<String>, M: Into<String>>(
+        &self,
+        group: &Group,
+        requester: i64,
+        nickname: S,
+        message: M,
+    ) -> GroupJoinRequestEvent {
+        let e = EventData::into_event(
+            GROUP_JOIN_REQUEST,
+            GroupJoinRequestData {
+                group: group.clone(),
+                requester_id: requester,
+                requester_nickname: nickname.into(),
+                message: message.into(),
+                state: RequestState::default(),
+            },
+        );
+
+        crate::event::FromEvent::from_event(e).expect("GroupJoinRequestEvent")
+    }
+}
+
+fn incoming(mut message: MessageChain, sender: i64) -> MessageChain {
+    let seq = next_seq();
+    let meta = message.metadata_mut();
+    meta.seqs = vec![seq];
+    meta.rands = vec![seq];
+    meta.time = now() as i32;
+    meta.sender = sender;
+
+    message
+}
+
+/// 模拟客户端
+pub struct MockClient {
+    id: i64,
+    nickname: String,
+    groups: Vec<MockGroup>,
+    friends: Vec<MockFriend>,
+    strangers: Vec<MockStranger>,
+}
+
+impl MockClient {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+            groups: vec![],
+            friends: vec![],
+            strangers: vec![],
+        }
+    }
+
+    pub fn group(mut self, group: MockGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn friend(mut self, friend: MockFriend) -> Self {
+        self.friends.push(friend);
+        self
+    }
+
+    /// 可通过[`Client::find_stranger`]查找到的陌生人
+    pub fn stranger(mut self, stranger: MockStranger) -> Self {
+        self.strangers.push(stranger);
+        self
+    }
+}
+
+/// 模拟群
+pub struct MockGroup {
+    id: i64,
+    name: String,
+    members: Vec<MockMember>,
+    settings: GroupSettings,
+    folders: Vec<String>,
+}
+
+impl MockGroup {
+    pub fn new<S: Into<String>>(id: i64, name: S) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            members: vec![],
+            settings: GroupSettings::default(),
+            folders: vec![],
+        }
+    }
+
+    pub fn member(mut self, member: MockMember) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    pub fn settings(mut self, settings: GroupSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// 在群文件根目录下创建文件夹, 其id为`/名称`
+    pub fn folder<S: Into<String>>(mut self, name: S) -> Self {
+        self.folders.push(name.into());
+        self
+    }
+}
+
+/// 模拟好友
+pub struct MockFriend {
+    id: i64,
+    nickname: String,
+}
+
+impl MockFriend {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+        }
+    }
+}
+
+/// 模拟陌生人
+pub struct MockStranger {
+    id: i64,
+    nickname: String,
+}
+
+impl MockStranger {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+        }
+    }
+}
+
+/// 模拟群成员
+pub struct MockMember {
+    id: i64,
+    nickname: String,
+    card_name: String,
+    role: MemberRole,
+    level: u16,
+}
+
+impl MockMember {
+    pub fn new<S: Into<String>>(id: i64, nickname: S) -> Self {
+        Self {
+            id,
+            nickname: nickname.into(),
+            card_name: String::new(),
+            role: MemberRole::Member,
+            level: 1,
+        }
+    }
+
+    pub fn card_name<S: Into<String>>(mut self, card_name: S) -> Self {
+        self.card_name = card_name.into();
+        self
+    }
+
+    pub fn role(mut self, role: MemberRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    pub fn level(mut self, level: u16) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+/// 单元测试共用的模拟宿主: 客户端10000, 群123(成员1, 2)与好友3
+#[cfg(test)]
+pub(crate) fn fixture() -> (MockHost, Client) {
+    let host = MockHost::install();
+    let client = host.add_client(
+        MockClient::new(10000, "Atri")
+            .group(
+                MockGroup::new(123, "测试群")
+                    .member(MockMember::new(1, "Alice").card_name("爱丽丝"))
+                    .member(MockMember::new(2, "Bob")),
+            )
+            .friend(MockFriend::new(3, "Carol")),
+    );
+
+    (host, client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{block_on, fixture, Action};
+    use crate::client::Client;
+    use crate::contact::member::Member;
+    use crate::event::{FriendMessageEvent, GroupMessageEvent};
+    use crate::listener::Listener;
+
+    #[test]
+    fn contacts() {
+        let (_host, client) = fixture();
+
+        assert_eq!(client.id(), 10000);
+        assert_eq!(client.nickname(), "Atri");
+        assert_eq!(Client::list().len(), 1);
+        assert!(Client::find(10000).is_some());
+        assert!(Client::find(1).is_none());
+
+        let group = client.find_group(123).unwrap();
+        assert_eq!(group.name(), "测试群");
+        assert_eq!(group.client().id(), 10000);
+        assert_eq!(client.groups().len(), 1);
+
+        let friend = client.find_friend(3).unwrap();
+        assert_eq!(friend.nickname(), "Carol");
+        assert_eq!(client.friends().len(), 1);
+
+        block_on(async {
+            assert_eq!(group.members().await.len(), 2);
+
+            let alice = group.find_member(1).await.unwrap();
+            assert_eq!(alice.card_name(), "爱丽丝");
+            assert_eq!(alice.group().id(), 123);
+            assert!(group.find_member(4).await.is_none());
+        });
+    }
+
+    #[test]
+    fn events() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+        let friend = client.find_friend(3).unwrap();
+
+        let e = host.group_message_event(&group, 1, "hi").unwrap();
+        assert_eq!(e.group().id(), 123);
+        assert_eq!(e.message().to_string(), "hi");
+        assert!(matches!(e.sender(), Member::Named(m) if m.id() == 1));
+
+        assert!(!e.is_intercepted());
+        let cloned = e.clone();
+        e.intercept();
+        assert!(cloned.is_intercepted());
+
+        assert!(host.group_message_event(&group, 4, "hi").is_none());
+
+        let e = host.friend_message_event(&friend, "hello");
+        assert_eq!(e.friend().id(), 3);
+        assert_eq!(e.message().metadata().sender, 3);
+    }
+
+    #[test]
+    fn dispatch() {
+        let (host, client) = fixture();
+        let group = client.find_group(123).unwrap();
+
+        let _echo = Listener::listening_on_always(|e: GroupMessageEvent| async move {
+            let msg = e.message().to_string();
+            let _ = e.group().send_message(format!("echo: {}", msg)).await;
+        });
+        let _friend = Listener::listening_on_always(|e: FriendMessageEvent| async move {
+            let _ = e.friend().send_message("pong").await;
+        });
+
+        let e = host.group_message_event(&group, 1, "ping").unwrap();
+        assert!(!host.dispatch(e));
+
+        let replies = host.group_messages(123);
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].to_string(), "echo: ping");
+        assert!(host.friend_messages(3).is_empty());
+
+        block_on(async {
+            group.change_name("新群名").await.unwrap();
+            assert!(group.quit().await);
+        });
+
+        let transcript = host.transcript();
+        assert!(matches!(
+            &transcript[1],
+            Action::GroupChangeName { group: 123, name } if name == "新群名"
+        ));
+        assert!(matches!(transcript[2], Action::GroupQuit { group: 123 }));
+
+        host.clear_transcript();
+        assert!(host.transcript().is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::message::forward::ForwardMessage;
+use crate::message::MessageChain;
+use std::time::Duration;
+
+/// 插件通过宿主执行的一次外部操作
+#[derive(Clone)]
+pub enum Action {
+    GroupMessage {
+        group: i64,
+        message: MessageChain,
+    },
+    GroupForwardMessage {
+        group: i64,
+        message: ForwardMessage,
+    },
+    GroupUploadImage {
+        group: i64,
+        data: Vec<u8>,
+    },
+    GroupUploadVoice {
+        group: i64,
+        data: Vec<u8>,
+    },
+    GroupUploadFile {
+        group: i64,
+        folder: String,
+        name: String,
+        data: Vec<u8>,
+    },
+    GroupRenameFile {
+        group: i64,
+        id: String,
+        name: String,
+    },
+    GroupMoveFile {
+        group: i64,
+        id: String,
+        folder: String,
+    },
+    GroupDeleteFile {
+        group: i64,
+        id: String,
+    },
+    GroupChangeName {
+        group: i64,
+        name: String,
+    },
+    GroupQuit {
+        group: i64,
+    },
+    GroupInvite {
+        group: i64,
+        target: i64,
+    },
+    GroupRecall {
+        group: i64,
+        seqs: Vec<i32>,
+    },
+    FriendRequestAccept {
+        requester: i64,
+    },
+    FriendRequestReject {
+        requester: i64,
+        reason: String,
+        block: bool,
+    },
+    GroupInvitedAccept {
+        group: i64,
+    },
+    GroupInvitedReject {
+        group: i64,
+        reason: String,
+        block: bool,
+    },
+    GroupJoinRequestAccept {
+        group: i64,
+        requester: i64,
+    },
+    GroupJoinRequestReject {
+        group: i64,
+        requester: i64,
+        reason: String,
+        block: bool,
+    },
+    GroupMuteAll {
+        group: i64,
+        mute: bool,
+    },
+    GroupSetAnonymous {
+        group: i64,
+        allow: bool,
+    },
+    GroupChangeAvatar {
+        group: i64,
+        data: Vec<u8>,
+    },
+    GroupPublishAnnouncement {
+        group: i64,
+        content: String,
+    },
+    GroupDeleteAnnouncement {
+        group: i64,
+        fid: String,
+    },
+    FriendMessage {
+        friend: i64,
+        message: MessageChain,
+    },
+    FriendUploadImage {
+        friend: i64,
+        data: Vec<u8>,
+    },
+    FriendUploadVoice {
+        friend: i64,
+        data: Vec<u8>,
+    },
+    MemberMessage {
+        group: i64,
+        member: i64,
+        message: MessageChain,
+    },
+    MemberUploadImage {
+        group: i64,
+        member: i64,
+        data: Vec<u8>,
+    },
+    StrangerMessage {
+        stranger: i64,
+        message: MessageChain,
+    },
+    StrangerUploadImage {
+        stranger: i64,
+        data: Vec<u8>,
+    },
+    FriendRecall {
+        friend: i64,
+        seqs: Vec<i32>,
+    },
+    MemberChangeCardName {
+        group: i64,
+        member: i64,
+        card_name: String,
+    },
+    /// 时长为0表示解除禁言
+    MemberMute {
+        group: i64,
+        member: i64,
+        duration: Duration,
+    },
+    AnonymousMute {
+        group: i64,
+        anon_id: Vec<u8>,
+        duration: Duration,
+    },
+    MemberKick {
+        group: i64,
+        member: i64,
+        reason: String,
+        block: bool,
+    },
+    MemberSetAdmin {
+        group: i64,
+        member: i64,
+        admin: bool,
+    },
+    MemberSetSpecialTitle {
+        group: i64,
+        member: i64,
+        title: String,
+    },
+}
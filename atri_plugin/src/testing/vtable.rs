@@ -0,0 +1,1749 @@
+use super::data::{
+    clone_handle, drop_handle, handle_arc, handle_ref, into_handle, next_seq, now, opt_into_handle,
+    ClientData, FileData, FriendData, GroupData, ImageData, InterceptFlag, MemberData,
+    StrangerData, VoiceData,
+};
+use super::event::{
+    AnonymousData, DeleteFriendData, EventData, FriendMessageData, FriendRecallData,
+    FriendRequestData, GroupInvitedData, GroupJoinRequestData, GroupMemberData, GroupMessageData,
+    GroupRecallData, GroupTempMessageData, RequestState, SenderData,
+};
+use super::executor::block_on;
+use super::transcript::Action;
+use super::{current, MockError, MockMember};
+use crate::client::Client;
+use crate::contact::friend::Friend;
+use crate::contact::group::Announcement;
+use crate::contact::group_file::GroupFileEntry;
+use crate::contact::member::MemberRole;
+use crate::loader::AtriVTable;
+use crate::message::forward::ForwardMessage;
+use crate::message::meta::MessageReceipt;
+use crate::message::{MessageChain, MessageElement};
+use atri_ffi::closure::FFIFn;
+use atri_ffi::contact::FFIMember;
+use atri_ffi::contact::{FFIAnnouncement, FFIGroupFile, FFIGroupSettings};
+use atri_ffi::error::FFIResult;
+use atri_ffi::ffi::{FFIEvent, ForFFI};
+use atri_ffi::future::FFIFuture;
+use atri_ffi::message::forward::FFIForwardNode;
+use atri_ffi::message::{
+    FFIMessageChain, FFIMessageElement, FFIMessageReceipt, MessageElementUnion,
+};
+use atri_ffi::{
+    FFIOption, Handle, Managed, ManagedCloneable, PHandle, RustStr, RustString, RustVec,
+};
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::future::{poll_fn, ready};
+use std::mem::ManuallyDrop;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
+
+pub(crate) extern "C" fn get_fun(sig: u16) -> *const () {
+    crate::loader::vtable_fun(&MOCK_VTABLE, sig)
+}
+
+/// 由编译器检查每个函数的签名与[`AtriVTable`]一致
+static MOCK_VTABLE: AtriVTable = AtriVTable {
+    plugin_manager_spawn,
+    plugin_manager_block_on,
+    new_listener,
+    listener_next_event_with_priority,
+    new_listener_with_manager,
+    listener_next_event_with_manager,
+    event_intercept,
+    event_is_intercepted,
+    client_get_id,
+    client_get_nickname,
+    client_get_list,
+    find_client,
+    client_find_group,
+    client_find_friend,
+    client_get_groups,
+    client_get_friends,
+    client_find_stranger,
+    client_get_list_with_manager,
+    find_client_with_manager,
+    client_clone,
+    client_drop,
+    group_message_event_get_group,
+    group_message_event_get_message,
+    group_message_event_get_sender,
+    group_get_id,
+    group_get_name,
+    group_get_client,
+    group_get_members,
+    group_find_member,
+    group_send_message,
+    group_upload_image,
+    group_quit,
+    group_change_name,
+    group_send_forward_message,
+    group_invite,
+    group_recall,
+    group_mute_all,
+    group_get_announcements,
+    group_publish_announcement,
+    group_delete_announcement,
+    group_set_anonymous,
+    group_change_avatar,
+    group_get_member_count,
+    group_clone,
+    group_drop,
+    group_get_owner,
+    group_get_settings,
+    group_upload_voice,
+    group_list_files,
+    group_upload_file,
+    group_download_file,
+    group_rename_file,
+    group_move_file,
+    group_delete_file,
+    group_get_message,
+    group_get_history,
+    friend_message_event_get_friend,
+    friend_message_event_get_message,
+    group_member_join_event_get_group: group_member_event_get_group,
+    group_member_join_event_get_member: group_member_event_get_member,
+    group_member_join_event_get_operator: group_member_event_get_operator,
+    group_member_leave_event_get_group: group_member_event_get_group,
+    group_member_leave_event_get_member: group_member_event_get_member,
+    group_member_leave_event_get_operator: group_member_event_get_operator,
+    group_member_leave_event_is_kicked,
+    group_recall_event_get_group,
+    group_recall_event_get_operator,
+    group_recall_event_get_operator_id,
+    group_recall_event_get_sender_id,
+    group_recall_event_get_seqs,
+    group_recall_event_get_time,
+    friend_recall_event_get_friend,
+    friend_recall_event_get_operator_id,
+    friend_recall_event_get_seqs,
+    friend_recall_event_get_time,
+    friend_recall_event_get_sender_id,
+    friend_request_event_get_client,
+    friend_request_event_get_requester_id,
+    friend_request_event_get_requester_nickname,
+    friend_request_event_get_message,
+    friend_request_event_accept,
+    friend_request_event_reject,
+    group_invited_event_get_client,
+    group_invited_event_get_group_id,
+    group_invited_event_get_group_name,
+    group_invited_event_get_inviter_id,
+    group_invited_event_get_inviter_nickname,
+    group_invited_event_accept,
+    group_invited_event_reject,
+    group_join_request_event_get_group,
+    group_join_request_event_get_requester_id,
+    group_join_request_event_get_requester_nickname,
+    group_join_request_event_get_message,
+    group_join_request_event_accept,
+    group_join_request_event_reject,
+    client_login_event_get_client,
+    new_friend_event_get_friend,
+    delete_friend_event_get_client,
+    delete_friend_event_get_friend_id,
+    group_temp_message_event_get_group,
+    group_temp_message_event_get_sender,
+    group_temp_message_event_get_message,
+    friend_get_id,
+    friend_get_nickname,
+    friend_get_client,
+    friend_send_message,
+    friend_upload_image,
+    friend_recall,
+    friend_upload_voice,
+    friend_get_history,
+    friend_get_message,
+    friend_clone,
+    friend_drop,
+    named_member_get_id,
+    named_member_get_nickname,
+    named_member_get_card_name,
+    named_member_get_group,
+    named_member_change_card_name,
+    named_member_mute,
+    named_member_kick,
+    named_member_set_admin,
+    named_member_set_special_title,
+    named_member_get_role,
+    named_member_get_join_time,
+    named_member_get_last_speak_time,
+    named_member_get_level,
+    named_member_send_message,
+    named_member_upload_image,
+    anonymous_member_get_anon_id,
+    anonymous_member_get_nickname,
+    anonymous_member_get_portrait_index,
+    anonymous_member_get_bubble_index,
+    anonymous_member_get_expire_time,
+    anonymous_member_get_color,
+    anonymous_member_get_group,
+    anonymous_member_mute,
+    stranger_get_id,
+    stranger_get_nickname,
+    stranger_get_client,
+    stranger_send_message,
+    stranger_upload_image,
+    image_get_id,
+    image_get_url,
+    voice_get_url,
+    voice_get_md5,
+    voice_get_size,
+    voice_get_duration,
+    voice_download,
+    video_get_name,
+    video_get_size,
+    video_get_url,
+    video_download,
+    file_get_name,
+    file_get_size,
+    file_get_url,
+    file_download,
+    log,
+    env_get_workspace,
+    message_chain_to_json,
+    message_chain_from_json,
+    message_element_wrap,
+    message_element_unwrap,
+    message_element_to_json,
+};
+
+fn ok<T>(value: T) -> FFIResult<T> {
+    FFIResult::from(Ok::<T, MockError>(value))
+}
+
+fn err<T>(msg: String) -> FFIResult<T> {
+    FFIResult::from(Err::<T, MockError>(MockError(msg)))
+}
+
+fn record(action: Action) {
+    if let Some(host) = current() {
+        host.transcript.lock().unwrap().push(action);
+    }
+}
+
+fn history_into_ffi(history: Vec<MessageChain>) -> RustVec<FFIMessageChain> {
+    history
+        .into_iter()
+        .map(ForFFI::into_ffi)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn receipt() -> FFIMessageReceipt {
+    let seq = next_seq();
+    FFIMessageReceipt {
+        seqs: vec![seq].into(),
+        rands: vec![seq].into(),
+        time: now(),
+    }
+}
+
+#[derive(Default)]
+struct JoinState {
+    value: Option<Managed>,
+    waker: Option<Waker>,
+}
+
+extern "C" fn plugin_manager_spawn(
+    _manager: *const (),
+    future: FFIFuture<Managed>,
+) -> FFIFuture<FFIResult<Managed>> {
+    let Some(host) = current() else {
+        return FFIFuture::from(async move { FFIResult::from(Ok::<_, Infallible>(future.await)) });
+    };
+
+    let state = Arc::new(Mutex::new(JoinState::default()));
+    let task_state = state.clone();
+    host.executor.spawn(async move {
+        let value = future.await;
+
+        let mut state = task_state.lock().unwrap();
+        state.value = Some(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+
+    FFIFuture::from(async move {
+        let value = poll_fn(|cx| {
+            let mut state = state.lock().unwrap();
+            match state.value.take() {
+                Some(value) => Poll::Ready(value),
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await;
+
+        FFIResult::from(Ok::<_, Infallible>(value))
+    })
+}
+
+extern "C" fn plugin_manager_block_on(_manager: *const (), future: FFIFuture<Managed>) -> Managed {
+    block_on(future)
+}
+
+extern "C" fn new_listener(
+    concurrent: bool,
+    handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+    priority: u8,
+) -> Managed {
+    match current() {
+        Some(host) => Managed::from_value(host.add_listener(concurrent, handler, priority)),
+        None => Managed::from_value(handler),
+    }
+}
+
+extern "C" fn listener_next_event_with_priority(
+    millis: u64,
+    filter: FFIFn<FFIEvent, bool>,
+    priority: u8,
+) -> FFIFuture<FFIOption<FFIEvent>> {
+    match current() {
+        Some(host) => {
+            FFIFuture::from(host.next_event(Duration::from_millis(millis), filter, priority))
+        }
+        None => FFIFuture::from(ready(FFIOption::from(None))),
+    }
+}
+
+// 模拟宿主只有一个实例, 忽略管理器指针
+extern "C" fn new_listener_with_manager(
+    _manager: *const (),
+    concurrent: bool,
+    handler: FFIFn<FFIEvent, FFIFuture<bool>>,
+    priority: u8,
+) -> Managed {
+    new_listener(concurrent, handler, priority)
+}
+
+extern "C" fn listener_next_event_with_manager(
+    _manager: *const (),
+    millis: u64,
+    filter: FFIFn<FFIEvent, bool>,
+    priority: u8,
+) -> FFIFuture<FFIOption<FFIEvent>> {
+    listener_next_event_with_priority(millis, filter, priority)
+}
+
+extern "C" fn event_intercept(intercepted: *const ()) {
+    unsafe { handle_ref::<InterceptFlag>(intercepted) }.intercept();
+}
+
+extern "C" fn event_is_intercepted(intercepted: *const ()) -> bool {
+    unsafe { handle_ref::<InterceptFlag>(intercepted) }.is_intercepted()
+}
+
+extern "C" fn client_get_list_with_manager(_manager: *const ()) -> RustVec<Handle> {
+    client_get_list()
+}
+
+extern "C" fn find_client_with_manager(_manager: *const (), id: i64) -> Handle {
+    find_client(id)
+}
+
+extern "C" fn client_get_id(client: Handle) -> i64 {
+    unsafe { handle_ref::<ClientData>(client) }.id
+}
+
+extern "C" fn client_get_nickname(client: Handle) -> RustString {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    RustString::from(client.nickname.clone())
+}
+
+extern "C" fn client_get_list() -> RustVec<Handle> {
+    let clients = current()
+        .map(|host| host.clients.lock().unwrap().clone())
+        .unwrap_or_default();
+
+    clients
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn find_client(id: i64) -> Handle {
+    let client = current().and_then(|host| {
+        let clients = host.clients.lock().unwrap();
+        clients.iter().find(|c| c.id == id).cloned()
+    });
+
+    opt_into_handle(client)
+}
+
+extern "C" fn client_find_group(client: Handle, id: i64) -> Handle {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    opt_into_handle(client.find_group(id))
+}
+
+extern "C" fn client_find_friend(client: Handle, id: i64) -> Handle {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    opt_into_handle(client.find_friend(id))
+}
+
+extern "C" fn client_get_groups(client: Handle) -> RustVec<Handle> {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    let groups = client.groups.lock().unwrap().clone();
+
+    groups
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn client_get_friends(client: Handle) -> RustVec<Handle> {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    let friends = client.friends.lock().unwrap().clone();
+
+    friends
+        .into_iter()
+        .map(into_handle)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+extern "C" fn client_find_stranger(client: Handle, id: i64) -> FFIFuture<ManagedCloneable> {
+    let client = unsafe { handle_ref::<ClientData>(client) };
+    let stranger = client.strangers.iter().find(|s| s.id == id).cloned();
+
+    let ma = stranger
+        .map(ManagedCloneable::from_value)
+        .unwrap_or_else(|| unsafe { ManagedCloneable::null() });
+    FFIFuture::from(ready(ma))
+}
+
+extern "C" fn client_clone(client: Handle) -> Handle {
+    unsafe { clone_handle::<ClientData>(client) }
+}
+
+extern "C" fn client_drop(client: Handle) {
+    unsafe { drop_handle::<ClientData>(client) }
+}
+
+extern "C" fn group_message_event_get_group(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupMessageData>>(event) };
+    &event.inner.group.0
+}
+
+extern "C" fn group_message_event_get_message(event: *const ()) -> FFIMessageChain {
+    let event = unsafe { handle_ref::<EventData<GroupMessageData>>(event) };
+    event.inner.message.clone().into_ffi()
+}
+
+extern "C" fn group_message_event_get_sender(event: *const ()) -> FFIMember {
+    let event = unsafe { handle_ref::<EventData<GroupMessageData>>(event) };
+    match event.inner.sender.clone() {
+        SenderData::Named(named) => FFIMember {
+            is_named: true,
+            inner: ManagedCloneable::from_value(named),
+        },
+        SenderData::Anonymous(ano) => FFIMember {
+            is_named: false,
+            inner: ManagedCloneable::from_value(ano),
+        },
+    }
+}
+
+extern "C" fn group_get_id(group: Handle) -> i64 {
+    unsafe { handle_ref::<GroupData>(group) }.id
+}
+
+extern "C" fn group_get_name(group: Handle) -> RustStr {
+    unsafe { handle_ref::<GroupData>(group) }.name.as_rs()
+}
+
+extern "C" fn group_get_client(group: Handle) -> Handle {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    opt_into_handle(group.client.upgrade())
+}
+
+extern "C" fn group_get_members(group: Handle) -> FFIFuture<RustVec<ManagedCloneable>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let members: Vec<ManagedCloneable> = group
+        .members
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .map(ManagedCloneable::from_value)
+        .collect();
+
+    FFIFuture::from(ready(members.into()))
+}
+
+extern "C" fn group_find_member(group: Handle, id: i64) -> FFIFuture<ManagedCloneable> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let ma = group
+        .find_member(id)
+        .map(ManagedCloneable::from_value)
+        .unwrap_or_else(|| unsafe { ManagedCloneable::null() });
+
+    FFIFuture::from(ready(ma))
+}
+
+extern "C" fn group_send_message(
+    group: Handle,
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let message = MessageChain::from_ffi(chain);
+
+    let result = if group.is_quit() {
+        err(format!("Group({}) has been quit", group.id))
+    } else {
+        let receipt = MessageReceipt::from_ffi(receipt());
+        let bot = group.client.upgrade().map(|c| c.id).unwrap_or_default();
+        group.messages.push_sent(message.clone(), &receipt, bot);
+
+        record(Action::GroupMessage {
+            group: group.id,
+            message,
+        });
+        ok(receipt.into_ffi())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_get_message(
+    group: Handle,
+    seq: i32,
+) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let message = group.messages.find(seq).map(ForFFI::into_ffi);
+
+    FFIFuture::from(ready(ok(FFIOption::from(message))))
+}
+
+extern "C" fn group_get_history(
+    group: Handle,
+    before_seq: i32,
+    count: u32,
+) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let history = history_into_ffi(group.messages.history(before_seq, count));
+
+    FFIFuture::from(ready(ok(history)))
+}
+
+extern "C" fn group_upload_image(
+    group: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let data = data.into_vec();
+    let img = ImageData::from_bytes(&data);
+    record(Action::GroupUploadImage {
+        group: group.id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(img))))
+}
+
+extern "C" fn group_upload_voice(
+    group: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let data = data.into_vec();
+    let voice = VoiceData::from_bytes(&data);
+    record(Action::GroupUploadVoice {
+        group: group.id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(voice))))
+}
+
+fn group_file_into_ffi(entry: GroupFileEntry) -> FFIGroupFile {
+    match entry {
+        GroupFileEntry::Folder(folder) => FFIGroupFile {
+            is_folder: true,
+            id: folder.id.into(),
+            name: folder.name.into(),
+            folder: folder.parent.into(),
+            size: 0,
+            uploader: 0,
+            time: 0,
+            file_count: folder.file_count,
+        },
+        GroupFileEntry::File(file) => FFIGroupFile {
+            is_folder: false,
+            id: file.id.into(),
+            name: file.name.into(),
+            folder: file.folder.into(),
+            size: file.size,
+            uploader: file.uploader,
+            time: file.upload_time,
+            file_count: 0,
+        },
+    }
+}
+
+extern "C" fn group_list_files(
+    group: Handle,
+    folder: RustStr,
+) -> FFIFuture<FFIResult<RustVec<FFIGroupFile>>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let result = group.files.lock().unwrap().list(folder.as_str());
+    let result = match result {
+        Ok(entries) => ok(entries
+            .into_iter()
+            .map(group_file_into_ffi)
+            .collect::<Vec<_>>()
+            .into()),
+        Err(e) => err(e),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_upload_file(
+    group: Handle,
+    folder: RustStr,
+    name: RustStr,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<FFIGroupFile>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let (folder, name, data) = (folder.to_string(), name.to_string(), data.into_vec());
+    let uploader = group.client.upgrade().map(|c| c.id).unwrap_or_default();
+
+    let result =
+        group
+            .files
+            .lock()
+            .unwrap()
+            .upload(folder.clone(), name.clone(), data.clone(), uploader);
+    let result = match result {
+        Ok(file) => {
+            record(Action::GroupUploadFile {
+                group: group.id,
+                folder,
+                name,
+                data,
+            });
+            ok(group_file_into_ffi(GroupFileEntry::File(file)))
+        }
+        Err(e) => err(e),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_download_file(group: Handle, id: RustStr) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let result = group.files.lock().unwrap().download(id.as_str());
+
+    FFIFuture::from(ready(match result {
+        Ok(data) => ok(data.into()),
+        Err(e) => err(e),
+    }))
+}
+
+extern "C" fn group_rename_file(
+    group: Handle,
+    id: RustStr,
+    name: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let (id, name) = (id.to_string(), name.to_string());
+
+    let result = match group.files.lock().unwrap().rename(&id, name.clone()) {
+        Ok(()) => {
+            record(Action::GroupRenameFile {
+                group: group.id,
+                id,
+                name,
+            });
+            ok(())
+        }
+        Err(e) => err(e),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_move_file(
+    group: Handle,
+    id: RustStr,
+    folder: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let (id, folder) = (id.to_string(), folder.to_string());
+
+    let result = match group.files.lock().unwrap().move_to(&id, folder.clone()) {
+        Ok(()) => {
+            record(Action::GroupMoveFile {
+                group: group.id,
+                id,
+                folder,
+            });
+            ok(())
+        }
+        Err(e) => err(e),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_delete_file(group: Handle, id: RustStr) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let id = id.to_string();
+
+    let result = match group.files.lock().unwrap().delete(&id) {
+        Ok(()) => {
+            record(Action::GroupDeleteFile {
+                group: group.id,
+                id,
+            });
+            ok(())
+        }
+        Err(e) => err(e),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_quit(group: Handle) -> FFIFuture<bool> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    record(Action::GroupQuit { group: group.id });
+
+    FFIFuture::from(ready(group.quit()))
+}
+
+extern "C" fn group_change_name(group: Handle, name: RustStr) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let name = name.to_string();
+    group.name.set(name.clone());
+    record(Action::GroupChangeName {
+        group: group.id,
+        name,
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn group_send_forward_message(
+    group: *const (),
+    msg: RustVec<FFIForwardNode>,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let message = ForwardMessage::from_ffi(msg);
+
+    let result = if group.is_quit() {
+        err(format!("Group({}) has been quit", group.id))
+    } else {
+        record(Action::GroupForwardMessage {
+            group: group.id,
+            message,
+        });
+        ok(receipt())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_invite(group: Handle, id: i64) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+
+    let result = if group.find_member(id).is_some() {
+        err(format!("{} is already a member of Group({})", id, group.id))
+    } else {
+        record(Action::GroupInvite {
+            group: group.id,
+            target: id,
+        });
+        ok(())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_recall(group: Handle, receipt: FFIMessageReceipt) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let seqs = receipt.seqs.into_vec();
+
+    let result = if seqs.is_empty() {
+        err(String::from("Nothing to recall"))
+    } else {
+        record(Action::GroupRecall {
+            group: group.id,
+            seqs,
+        });
+        ok(())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_mute_all(group: Handle, mute: bool) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    group.settings.lock().unwrap().mute_all = mute;
+    record(Action::GroupMuteAll {
+        group: group.id,
+        mute,
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn group_get_announcements(
+    group: Handle,
+) -> FFIFuture<FFIResult<RustVec<FFIAnnouncement>>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let announcements: Vec<FFIAnnouncement> = group
+        .announcements
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .map(|a| FFIAnnouncement {
+            fid: a.fid.into(),
+            sender: a.sender,
+            time: a.time,
+            content: a.content.into(),
+        })
+        .collect();
+
+    FFIFuture::from(ready(ok(announcements.into())))
+}
+
+extern "C" fn group_publish_announcement(
+    group: Handle,
+    content: RustStr,
+) -> FFIFuture<FFIResult<RustString>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let content = content.to_string();
+    let fid = format!("mock-announcement-{}", next_seq());
+    let sender = group.client.upgrade().map(|c| c.id).unwrap_or_default();
+
+    group.announcements.lock().unwrap().push(Announcement {
+        fid: fid.clone(),
+        sender,
+        time: now(),
+        content: content.clone(),
+    });
+    record(Action::GroupPublishAnnouncement {
+        group: group.id,
+        content,
+    });
+
+    FFIFuture::from(ready(ok(RustString::from(fid))))
+}
+
+extern "C" fn group_delete_announcement(group: Handle, fid: RustStr) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let fid = fid.to_string();
+
+    let mut announcements = group.announcements.lock().unwrap();
+    let result = match announcements.iter().position(|a| a.fid == fid) {
+        Some(pos) => {
+            announcements.remove(pos);
+            record(Action::GroupDeleteAnnouncement {
+                group: group.id,
+                fid,
+            });
+            ok(())
+        }
+        None => err(format!("Announcement {} not found", fid)),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn group_set_anonymous(group: Handle, allow: bool) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    group.settings.lock().unwrap().allow_anonymous = allow;
+    record(Action::GroupSetAnonymous {
+        group: group.id,
+        allow,
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn group_change_avatar(group: Handle, data: RustVec<u8>) -> FFIFuture<FFIResult<()>> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    record(Action::GroupChangeAvatar {
+        group: group.id,
+        data: data.into_vec(),
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn group_get_member_count(group: Handle) -> u32 {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    group.members.lock().unwrap().len() as u32
+}
+
+extern "C" fn group_get_owner(group: Handle) -> FFIFuture<ManagedCloneable> {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let owner = group
+        .members
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|m| *m.role.lock().unwrap() == MemberRole::Owner)
+        .cloned();
+
+    let ma = owner
+        .map(ManagedCloneable::from_value)
+        .unwrap_or_else(|| unsafe { ManagedCloneable::null() });
+    FFIFuture::from(ready(ma))
+}
+
+extern "C" fn group_get_settings(group: Handle) -> FFIGroupSettings {
+    let group = unsafe { handle_ref::<GroupData>(group) };
+    let settings = *group.settings.lock().unwrap();
+
+    FFIGroupSettings {
+        mute_all: settings.mute_all,
+        allow_anonymous: settings.allow_anonymous,
+        allow_member_invite: settings.allow_member_invite,
+    }
+}
+
+extern "C" fn group_clone(group: Handle) -> Handle {
+    unsafe { clone_handle::<GroupData>(group) }
+}
+
+extern "C" fn group_drop(group: Handle) {
+    unsafe { drop_handle::<GroupData>(group) }
+}
+
+extern "C" fn friend_message_event_get_friend(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<FriendMessageData>>(event) };
+    &event.inner.friend.0
+}
+
+extern "C" fn friend_message_event_get_message(event: *const ()) -> FFIMessageChain {
+    let event = unsafe { handle_ref::<EventData<FriendMessageData>>(event) };
+    event.inner.message.clone().into_ffi()
+}
+
+extern "C" fn group_member_event_get_group(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupMemberData>>(event) };
+    &event.inner.group.0
+}
+
+extern "C" fn group_member_event_get_member(event: *const ()) -> ManagedCloneable {
+    let event = unsafe { handle_ref::<EventData<GroupMemberData>>(event) };
+    ManagedCloneable::from_value(event.inner.member.clone())
+}
+
+extern "C" fn group_member_event_get_operator(event: *const ()) -> ManagedCloneable {
+    let event = unsafe { handle_ref::<EventData<GroupMemberData>>(event) };
+    match event.inner.operator.clone() {
+        Some(operator) => ManagedCloneable::from_value(operator),
+        None => unsafe { ManagedCloneable::null() },
+    }
+}
+
+extern "C" fn group_member_leave_event_is_kicked(event: *const ()) -> bool {
+    let event = unsafe { handle_ref::<EventData<GroupMemberData>>(event) };
+    event.inner.kicked
+}
+
+extern "C" fn group_recall_event_get_group(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupRecallData>>(event) };
+    &event.inner.group.0
+}
+
+extern "C" fn group_recall_event_get_operator(event: *const ()) -> ManagedCloneable {
+    let event = unsafe { handle_ref::<EventData<GroupRecallData>>(event) };
+    match event.inner.operator.clone() {
+        Some(operator) => ManagedCloneable::from_value(operator),
+        None => unsafe { ManagedCloneable::null() },
+    }
+}
+
+extern "C" fn group_recall_event_get_operator_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<GroupRecallData>>(event) }
+        .inner
+        .operator_id
+}
+
+extern "C" fn group_recall_event_get_sender_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<GroupRecallData>>(event) }
+        .inner
+        .sender
+}
+
+extern "C" fn group_recall_event_get_seqs(event: *const ()) -> RustVec<i32> {
+    let event = unsafe { handle_ref::<EventData<GroupRecallData>>(event) };
+    event.inner.seqs.clone().into()
+}
+
+extern "C" fn group_recall_event_get_time(event: *const ()) -> i32 {
+    unsafe { handle_ref::<EventData<GroupRecallData>>(event) }
+        .inner
+        .time
+}
+
+extern "C" fn friend_recall_event_get_friend(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<FriendRecallData>>(event) };
+    &event.inner.friend.0
+}
+
+extern "C" fn friend_recall_event_get_operator_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<FriendRecallData>>(event) }
+        .inner
+        .operator_id
+}
+
+extern "C" fn friend_recall_event_get_seqs(event: *const ()) -> RustVec<i32> {
+    let event = unsafe { handle_ref::<EventData<FriendRecallData>>(event) };
+    event.inner.seqs.clone().into()
+}
+
+extern "C" fn friend_recall_event_get_time(event: *const ()) -> i32 {
+    unsafe { handle_ref::<EventData<FriendRecallData>>(event) }
+        .inner
+        .time
+}
+
+extern "C" fn friend_recall_event_get_sender_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<FriendRecallData>>(event) }
+        .inner
+        .sender
+}
+
+extern "C" fn group_temp_message_event_get_group(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupTempMessageData>>(event) };
+    &event.inner.group.0
+}
+
+extern "C" fn group_temp_message_event_get_sender(event: *const ()) -> ManagedCloneable {
+    let event = unsafe { handle_ref::<EventData<GroupTempMessageData>>(event) };
+    ManagedCloneable::from_value(event.inner.sender.clone())
+}
+
+extern "C" fn group_temp_message_event_get_message(event: *const ()) -> FFIMessageChain {
+    let event = unsafe { handle_ref::<EventData<GroupTempMessageData>>(event) };
+    event.inner.message.clone().into_ffi()
+}
+
+extern "C" fn client_login_event_get_client(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<Client>>(event) };
+    &event.inner.0
+}
+
+extern "C" fn new_friend_event_get_friend(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<Friend>>(event) };
+    &event.inner.0
+}
+
+extern "C" fn delete_friend_event_get_client(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<DeleteFriendData>>(event) };
+    &event.inner.client.0
+}
+
+extern "C" fn delete_friend_event_get_friend_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<DeleteFriendData>>(event) }
+        .inner
+        .friend_id
+}
+
+/// 申请只能被处理一次, 重复处理时返回错误
+fn handle_request<F: FnOnce()>(state: &RequestState, on_handle: F) -> FFIFuture<FFIResult<()>> {
+    let result = if state.handle() {
+        on_handle();
+        ok(())
+    } else {
+        err(String::from("Request has already been handled"))
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn friend_request_event_get_client(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<FriendRequestData>>(event) };
+    &event.inner.client.0
+}
+
+extern "C" fn friend_request_event_get_requester_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<FriendRequestData>>(event) }
+        .inner
+        .requester_id
+}
+
+extern "C" fn friend_request_event_get_requester_nickname(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<FriendRequestData>>(event) };
+    RustStr::from(event.inner.requester_nickname.as_str())
+}
+
+extern "C" fn friend_request_event_get_message(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<FriendRequestData>>(event) };
+    RustStr::from(event.inner.message.as_str())
+}
+
+extern "C" fn friend_request_event_accept(event: *const ()) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<FriendRequestData>>(event) };
+    handle_request(&event.inner.state, || {
+        record(Action::FriendRequestAccept {
+            requester: event.inner.requester_id,
+        })
+    })
+}
+
+extern "C" fn friend_request_event_reject(
+    event: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<FriendRequestData>>(event) };
+    handle_request(&event.inner.state, || {
+        record(Action::FriendRequestReject {
+            requester: event.inner.requester_id,
+            reason: reason.to_string(),
+            block,
+        })
+    })
+}
+
+extern "C" fn group_invited_event_get_client(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupInvitedData>>(event) };
+    &event.inner.client.0
+}
+
+extern "C" fn group_invited_event_get_group_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<GroupInvitedData>>(event) }
+        .inner
+        .group_id
+}
+
+extern "C" fn group_invited_event_get_group_name(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<GroupInvitedData>>(event) };
+    RustStr::from(event.inner.group_name.as_str())
+}
+
+extern "C" fn group_invited_event_get_inviter_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<GroupInvitedData>>(event) }
+        .inner
+        .inviter_id
+}
+
+extern "C" fn group_invited_event_get_inviter_nickname(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<GroupInvitedData>>(event) };
+    RustStr::from(event.inner.inviter_nickname.as_str())
+}
+
+extern "C" fn group_invited_event_accept(event: *const ()) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<GroupInvitedData>>(event) };
+    handle_request(&event.inner.state, || {
+        record(Action::GroupInvitedAccept {
+            group: event.inner.group_id,
+        })
+    })
+}
+
+extern "C" fn group_invited_event_reject(
+    event: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<GroupInvitedData>>(event) };
+    handle_request(&event.inner.state, || {
+        record(Action::GroupInvitedReject {
+            group: event.inner.group_id,
+            reason: reason.to_string(),
+            block,
+        })
+    })
+}
+
+extern "C" fn group_join_request_event_get_group(event: *const ()) -> PHandle {
+    let event = unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) };
+    &event.inner.group.0
+}
+
+extern "C" fn group_join_request_event_get_requester_id(event: *const ()) -> i64 {
+    unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) }
+        .inner
+        .requester_id
+}
+
+extern "C" fn group_join_request_event_get_requester_nickname(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) };
+    RustStr::from(event.inner.requester_nickname.as_str())
+}
+
+extern "C" fn group_join_request_event_get_message(event: *const ()) -> RustStr {
+    let event = unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) };
+    RustStr::from(event.inner.message.as_str())
+}
+
+/// 同意后申请者成为群成员
+extern "C" fn group_join_request_event_accept(event: *const ()) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) };
+    let group = unsafe { handle_arc::<GroupData>(event.inner.group.0) };
+    handle_request(&event.inner.state, || {
+        group.add_member(MockMember::new(
+            event.inner.requester_id,
+            event.inner.requester_nickname.clone(),
+        ));
+        record(Action::GroupJoinRequestAccept {
+            group: group.id,
+            requester: event.inner.requester_id,
+        })
+    })
+}
+
+extern "C" fn group_join_request_event_reject(
+    event: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let event = unsafe { handle_ref::<EventData<GroupJoinRequestData>>(event) };
+    let group = unsafe { handle_ref::<GroupData>(event.inner.group.0) };
+    handle_request(&event.inner.state, || {
+        record(Action::GroupJoinRequestReject {
+            group: group.id,
+            requester: event.inner.requester_id,
+            reason: reason.to_string(),
+            block,
+        })
+    })
+}
+
+extern "C" fn friend_get_id(friend: Handle) -> i64 {
+    unsafe { handle_ref::<FriendData>(friend) }.id
+}
+
+extern "C" fn friend_get_nickname(friend: Handle) -> RustStr {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    RustStr::from(&*friend.nickname)
+}
+
+extern "C" fn friend_get_client(friend: Handle) -> Handle {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    opt_into_handle(friend.client.upgrade())
+}
+
+extern "C" fn friend_send_message(
+    friend: Handle,
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let message = MessageChain::from_ffi(chain);
+
+    let receipt = MessageReceipt::from_ffi(receipt());
+    let bot = friend.client.upgrade().map(|c| c.id).unwrap_or_default();
+    friend.messages.push_sent(message.clone(), &receipt, bot);
+
+    record(Action::FriendMessage {
+        friend: friend.id,
+        message,
+    });
+
+    FFIFuture::from(ready(ok(receipt.into_ffi())))
+}
+
+extern "C" fn friend_get_history(
+    friend: Handle,
+    before_seq: i32,
+    count: u32,
+) -> FFIFuture<FFIResult<RustVec<FFIMessageChain>>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let history = history_into_ffi(friend.messages.history(before_seq, count));
+
+    FFIFuture::from(ready(ok(history)))
+}
+
+extern "C" fn friend_get_message(
+    friend: Handle,
+    seq: i32,
+) -> FFIFuture<FFIResult<FFIOption<FFIMessageChain>>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let message = friend.messages.find(seq).map(ForFFI::into_ffi);
+
+    FFIFuture::from(ready(ok(FFIOption::from(message))))
+}
+
+extern "C" fn friend_upload_image(
+    friend: Handle,
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let data = img.into_vec();
+    let img = ImageData::from_bytes(&data);
+    record(Action::FriendUploadImage {
+        friend: friend.id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(img))))
+}
+
+extern "C" fn friend_recall(
+    friend: Handle,
+    receipt: FFIMessageReceipt,
+) -> FFIFuture<FFIResult<()>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let seqs = receipt.seqs.into_vec();
+
+    let result = if seqs.is_empty() {
+        err(String::from("Nothing to recall"))
+    } else {
+        record(Action::FriendRecall {
+            friend: friend.id,
+            seqs,
+        });
+        ok(())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn friend_upload_voice(
+    friend: Handle,
+    data: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let friend = unsafe { handle_ref::<FriendData>(friend) };
+    let data = data.into_vec();
+    let voice = VoiceData::from_bytes(&data);
+    record(Action::FriendUploadVoice {
+        friend: friend.id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(voice))))
+}
+
+extern "C" fn friend_clone(friend: Handle) -> Handle {
+    unsafe { clone_handle::<FriendData>(friend) }
+}
+
+extern "C" fn friend_drop(friend: Handle) {
+    unsafe { drop_handle::<FriendData>(friend) }
+}
+
+fn named_member<'a>(named: *const ()) -> &'a MemberData {
+    unsafe { handle_ref::<Arc<MemberData>>(named) }
+}
+
+extern "C" fn named_member_get_id(named: *const ()) -> i64 {
+    named_member(named).id
+}
+
+extern "C" fn named_member_get_nickname(named: *const ()) -> RustStr {
+    RustStr::from(&*named_member(named).nickname)
+}
+
+extern "C" fn named_member_get_card_name(named: *const ()) -> RustStr {
+    named_member(named).card_name.as_rs()
+}
+
+extern "C" fn named_member_get_group(named: *const ()) -> Handle {
+    opt_into_handle(named_member(named).group.upgrade())
+}
+
+extern "C" fn named_member_change_card_name(
+    named: *const (),
+    card: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let member = named_member(named);
+    let card_name = card.to_string();
+    member.card_name.set(card_name.clone());
+    record(Action::MemberChangeCardName {
+        group: member.group_id(),
+        member: member.id,
+        card_name,
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn named_member_mute(named: *const (), seconds: u64) -> FFIFuture<FFIResult<()>> {
+    let member = named_member(named);
+
+    let result = if *member.role.lock().unwrap() == MemberRole::Owner {
+        err(String::from("Cannot mute the owner"))
+    } else {
+        record(Action::MemberMute {
+            group: member.group_id(),
+            member: member.id,
+            duration: Duration::from_secs(seconds),
+        });
+        ok(())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn named_member_kick(
+    named: *const (),
+    reason: RustStr,
+    block: bool,
+) -> FFIFuture<FFIResult<()>> {
+    let member = named_member(named);
+    let removed = member
+        .group
+        .upgrade()
+        .and_then(|g| g.remove_member(member.id));
+
+    let result = match removed {
+        Some(_) => {
+            record(Action::MemberKick {
+                group: member.group_id(),
+                member: member.id,
+                reason: reason.to_string(),
+                block,
+            });
+            ok(())
+        }
+        None => err(format!("Member({}) is not in the group", member.id)),
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn named_member_set_admin(named: *const (), admin: bool) -> FFIFuture<FFIResult<()>> {
+    let member = named_member(named);
+    let mut role = member.role.lock().unwrap();
+
+    let result = if *role == MemberRole::Owner {
+        err(String::from("Cannot change the role of the owner"))
+    } else {
+        *role = if admin {
+            MemberRole::Administrator
+        } else {
+            MemberRole::Member
+        };
+        record(Action::MemberSetAdmin {
+            group: member.group_id(),
+            member: member.id,
+            admin,
+        });
+        ok(())
+    };
+
+    FFIFuture::from(ready(result))
+}
+
+extern "C" fn named_member_set_special_title(
+    named: *const (),
+    title: RustStr,
+) -> FFIFuture<FFIResult<()>> {
+    let member = named_member(named);
+    record(Action::MemberSetSpecialTitle {
+        group: member.group_id(),
+        member: member.id,
+        title: title.to_string(),
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+extern "C" fn named_member_get_role(named: *const ()) -> u8 {
+    named_member(named).role.lock().unwrap().as_u8()
+}
+
+extern "C" fn named_member_get_join_time(named: *const ()) -> i64 {
+    named_member(named).join_time
+}
+
+extern "C" fn named_member_get_last_speak_time(named: *const ()) -> i64 {
+    named_member(named).last_speak_time.load(Ordering::SeqCst)
+}
+
+extern "C" fn named_member_get_level(named: *const ()) -> u16 {
+    named_member(named).level
+}
+
+extern "C" fn named_member_send_message(
+    named: *const (),
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    let member = named_member(named);
+    record(Action::MemberMessage {
+        group: member.group_id(),
+        member: member.id,
+        message: MessageChain::from_ffi(chain),
+    });
+
+    FFIFuture::from(ready(ok(receipt())))
+}
+
+extern "C" fn named_member_upload_image(
+    named: *const (),
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let member = named_member(named);
+    let data = img.into_vec();
+    let img = ImageData::from_bytes(&data);
+    record(Action::MemberUploadImage {
+        group: member.group_id(),
+        member: member.id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(img))))
+}
+
+fn anonymous_member<'a>(ano: *const ()) -> &'a AnonymousData {
+    unsafe { handle_ref::<AnonymousData>(ano) }
+}
+
+extern "C" fn anonymous_member_get_anon_id(ano: *const ()) -> RustVec<u8> {
+    anonymous_member(ano).anonymous.anon_id.clone().into()
+}
+
+extern "C" fn anonymous_member_get_nickname(ano: *const ()) -> RustStr {
+    RustStr::from(anonymous_member(ano).anonymous.nick.as_str())
+}
+
+extern "C" fn anonymous_member_get_portrait_index(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.portrait_index
+}
+
+extern "C" fn anonymous_member_get_bubble_index(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.bubble_index
+}
+
+extern "C" fn anonymous_member_get_expire_time(ano: *const ()) -> i32 {
+    anonymous_member(ano).anonymous.expire_time
+}
+
+extern "C" fn anonymous_member_get_color(ano: *const ()) -> RustStr {
+    RustStr::from(anonymous_member(ano).anonymous.color.as_str())
+}
+
+extern "C" fn anonymous_member_get_group(ano: *const ()) -> Handle {
+    group_clone(anonymous_member(ano).group.0)
+}
+
+extern "C" fn anonymous_member_mute(ano: *const (), seconds: u64) -> FFIFuture<FFIResult<()>> {
+    let ano = anonymous_member(ano);
+    let group = unsafe { handle_ref::<GroupData>(ano.group.0) };
+
+    record(Action::AnonymousMute {
+        group: group.id,
+        anon_id: ano.anonymous.anon_id.clone(),
+        duration: Duration::from_secs(seconds),
+    });
+
+    FFIFuture::from(ready(ok(())))
+}
+
+fn stranger<'a>(stranger: *const ()) -> &'a StrangerData {
+    unsafe { handle_ref::<Arc<StrangerData>>(stranger) }
+}
+
+extern "C" fn stranger_get_id(stranger_ptr: *const ()) -> i64 {
+    stranger(stranger_ptr).id
+}
+
+extern "C" fn stranger_get_nickname(stranger_ptr: *const ()) -> RustStr {
+    RustStr::from(stranger(stranger_ptr).nickname.as_str())
+}
+
+extern "C" fn stranger_get_client(stranger_ptr: *const ()) -> Handle {
+    opt_into_handle(stranger(stranger_ptr).client.upgrade())
+}
+
+extern "C" fn stranger_send_message(
+    stranger_ptr: *const (),
+    chain: FFIMessageChain,
+) -> FFIFuture<FFIResult<FFIMessageReceipt>> {
+    record(Action::StrangerMessage {
+        stranger: stranger(stranger_ptr).id,
+        message: MessageChain::from_ffi(chain),
+    });
+
+    FFIFuture::from(ready(ok(receipt())))
+}
+
+extern "C" fn stranger_upload_image(
+    stranger_ptr: *const (),
+    img: RustVec<u8>,
+) -> FFIFuture<FFIResult<ManagedCloneable>> {
+    let data = img.into_vec();
+    let img = ImageData::from_bytes(&data);
+    record(Action::StrangerUploadImage {
+        stranger: stranger(stranger_ptr).id,
+        data,
+    });
+
+    FFIFuture::from(ready(ok(ManagedCloneable::from_value(img))))
+}
+
+extern "C" fn image_get_id(img: *const ()) -> RustStr {
+    let img = unsafe { handle_ref::<ImageData>(img) };
+    RustStr::from(&*img.id)
+}
+
+extern "C" fn image_get_url(img: *const ()) -> RustString {
+    let img = unsafe { handle_ref::<ImageData>(img) };
+    RustString::from(img.url.clone())
+}
+
+extern "C" fn voice_get_url(voice: *const ()) -> RustString {
+    let voice = unsafe { handle_ref::<VoiceData>(voice) };
+    RustString::from(voice.url.clone())
+}
+
+extern "C" fn voice_get_md5(voice: *const ()) -> RustVec<u8> {
+    let voice = unsafe { handle_ref::<VoiceData>(voice) };
+    RustVec::from(voice.md5.clone())
+}
+
+extern "C" fn voice_get_size(voice: *const ()) -> u32 {
+    let voice = unsafe { handle_ref::<VoiceData>(voice) };
+    voice.data.len() as u32
+}
+
+extern "C" fn voice_get_duration(voice: *const ()) -> u64 {
+    let voice = unsafe { handle_ref::<VoiceData>(voice) };
+    voice.duration
+}
+
+extern "C" fn voice_download(voice: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let voice = unsafe { handle_ref::<VoiceData>(voice) };
+    FFIFuture::from(ready(ok(RustVec::from(voice.data.clone()))))
+}
+
+extern "C" fn video_get_name(video: *const ()) -> RustStr {
+    let video = unsafe { handle_ref::<FileData>(video) };
+    RustStr::from(&*video.name)
+}
+
+extern "C" fn video_get_size(video: *const ()) -> u64 {
+    let video = unsafe { handle_ref::<FileData>(video) };
+    video.data.len() as u64
+}
+
+extern "C" fn video_get_url(video: *const ()) -> RustString {
+    let video = unsafe { handle_ref::<FileData>(video) };
+    RustString::from(video.url.clone())
+}
+
+extern "C" fn video_download(video: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let video = unsafe { handle_ref::<FileData>(video) };
+    FFIFuture::from(ready(ok(RustVec::from(video.data.clone()))))
+}
+
+extern "C" fn file_get_name(file: *const ()) -> RustStr {
+    let file = unsafe { handle_ref::<FileData>(file) };
+    RustStr::from(&*file.name)
+}
+
+extern "C" fn file_get_size(file: *const ()) -> u64 {
+    let file = unsafe { handle_ref::<FileData>(file) };
+    file.data.len() as u64
+}
+
+extern "C" fn file_get_url(file: *const ()) -> RustString {
+    let file = unsafe { handle_ref::<FileData>(file) };
+    RustString::from(file.url.clone())
+}
+
+extern "C" fn file_download(file: *const ()) -> FFIFuture<FFIResult<RustVec<u8>>> {
+    let file = unsafe { handle_ref::<FileData>(file) };
+    FFIFuture::from(ready(ok(RustVec::from(file.data.clone()))))
+}
+
+extern "C" fn log(_handle: usize, _manager: *const (), level: u8, log: RustStr) {
+    if let Some(host) = current() {
+        host.logs.lock().unwrap().push((level, log.to_string()));
+    }
+}
+
+extern "C" fn env_get_workspace(_handle: usize, _manager: *const ()) -> RustString {
+    let workspace = current()
+        .map(|host| host.workspace.lock().unwrap().clone())
+        .unwrap_or_else(std::env::temp_dir);
+
+    RustString::from(workspace.to_string_lossy().into_owned())
+}
+
+extern "C" fn message_chain_to_json(chain: FFIMessageChain) -> RustString {
+    let chain = MessageChain::from_ffi(chain);
+
+    let mut json = String::from("[");
+    for (i, elem) in chain.iter().enumerate() {
+        if i != 0 {
+            json.push(',');
+        }
+        match elem {
+            MessageElement::Unknown(unknown) => {
+                let _ = write!(
+                    json,
+                    r#"{{"type":"Unknown","flag":{}}}"#,
+                    unknown.raw_flag()
+                );
+            }
+            elem => push_json_str(&mut json, &elem.to_string()),
+        }
+    }
+    json.push(']');
+
+    RustString::from(json)
+}
+
+/// 模拟宿主保管的未知元素, 约定其内容总是以`unknown`成员传递
+#[derive(Clone)]
+struct UnknownData {
+    flag: u8,
+    inner: ManagedCloneable,
+}
+
+extern "C" fn message_element_wrap(elem: FFIMessageElement) -> ManagedCloneable {
+    let inner = unsafe { ManuallyDrop::into_inner(elem.union.unknown) };
+    ManagedCloneable::from_value(UnknownData {
+        flag: elem.t,
+        inner,
+    })
+}
+
+extern "C" fn message_element_unwrap(elem: *const ()) -> FFIMessageElement {
+    let elem = unsafe { handle_ref::<UnknownData>(elem) };
+    FFIMessageElement {
+        t: elem.flag,
+        union: MessageElementUnion {
+            unknown: ManuallyDrop::new(elem.inner.clone()),
+        },
+    }
+}
+
+extern "C" fn message_element_to_json(elem: *const ()) -> RustString {
+    let elem = unsafe { handle_ref::<UnknownData>(elem) };
+    RustString::from(format!(r#"{{"type":"Unknown","flag":{}}}"#, elem.flag))
+}
+
+fn push_json_str(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+extern "C" fn message_chain_from_json(json: RustStr) -> FFIResult<FFIMessageChain> {
+    err(format!(
+        "Deserializing message chain is not supported by the mock host: {}",
+        json.as_str()
+    ))
+}
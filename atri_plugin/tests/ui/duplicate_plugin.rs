@@ -0,0 +1,29 @@
+use atri_plugin::Plugin;
+
+mod first {
+    #[atri_plugin::plugin]
+    pub struct First;
+
+    impl super::Plugin for First {
+        fn new() -> Self {
+            Self
+        }
+
+        fn enable(&mut self) {}
+    }
+}
+
+mod second {
+    #[atri_plugin::plugin]
+    pub struct Second;
+
+    impl super::Plugin for Second {
+        fn new() -> Self {
+            Self
+        }
+
+        fn enable(&mut self) {}
+    }
+}
+
+fn main() {}